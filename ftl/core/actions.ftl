@@ -45,6 +45,8 @@ actions-update-note = Update Note
 actions-update-card = Update Card
 actions-update-deck = Update Deck
 actions-forget-card = Forget Card
+actions-set-prerequisites = Set Prerequisites
+actions-set-interval-limits = Set Interval Limits
 actions-build-filtered-deck = Build Deck
 actions-add-notetype = Add Notetype
 actions-remove-notetype = Remove Notetype
@@ -69,6 +69,13 @@ deck-config-learning-steps-tooltip =
     The `Good` button will advance to the next step, which is 10 minutes by default.
     Once all steps have been passed, the card will become a review card, and
     will appear on a different day. { -deck-config-delay-hint }
+deck-config-adaptive-learning-steps = Adaptive steps
+deck-config-adaptive-learning-steps-tooltip =
+    When enabled, the learning and relearning steps adapt to how you answered
+    the card earlier today: repeated `Again` answers bring the card back sooner,
+    and repeated successes push it back further. When FSRS is enabled, its
+    estimate is used for the `Good` delay. The delays stay within the range of
+    your steps, and steps due today are not moved to the next day.
 deck-config-graduating-interval-tooltip =
     The number of days to wait before showing a card again, after the `Good` button
     is pressed on the final learning step.
@@ -105,6 +112,24 @@ deck-config-leech-action-tooltip =
     
     `Suspend Card`: In addition to tagging the note, hide the card until it is
     manually unsuspended.
+deck-config-leech-repeat-interval = Repeat leech actions every
+deck-config-leech-repeat-interval-tooltip =
+    After a card has become a leech, the leech actions are repeated every time it has
+    been forgotten this many more times. When 0, half the leech threshold is used.
+deck-config-leech-deck = Move leeches to deck
+deck-config-leech-deck-tooltip =
+    The name of a deck that leeches will be moved into. The deck will be created if
+    it doesn't exist. Leave empty to keep leeches in their current deck.
+deck-config-leech-reset-to-new = Reset leeches to new
+deck-config-leech-reset-to-new-tooltip =
+    Forget leeches, so they are shown again as new cards. Their review history is kept.
+deck-config-leech-flag = Flag leeches
+deck-config-leech-flag-tooltip = The flag to set on leeches.
+deck-config-leech-flag-none = No flag
+deck-config-leech-tag-lapses = Tag leeches with lapse count
+deck-config-leech-tag-lapses-tooltip =
+    In addition to the "leech" tag, add a tag like "leech::8" that records how many
+    times the card had been forgotten when it became a leech.
 
 ## Burying section
 
@@ -119,6 +144,11 @@ deck-config-bury-review-tooltip = Whether other `review` cards of the same note
 deck-config-bury-interday-learning-tooltip =
     Whether other `learning` cards of the same note with intervals > 1 day
     will be delayed until the next day.
+deck-config-sibling-dispersal-days = Sibling dispersal days
+deck-config-sibling-dispersal-tooltip =
+    When non-zero, review intervals are adjusted within the usual fuzz range,
+    so that cards are due at least this many days apart from other review cards
+    of the same note.
 deck-config-bury-priority-tooltip =
     When Anki gathers cards, it first gathers intraday learning cards, then
     interday learning cards, then reviews, and finally new cards. This affects
@@ -212,6 +242,12 @@ deck-config-sort-order-descending-ease = Descending ease
 deck-config-sort-order-ascending-difficulty = Ascending difficulty
 deck-config-sort-order-descending-difficulty = Descending difficulty
 deck-config-sort-order-relative-overdueness = Relative overdueness
+deck-config-sort-order-ascending-retrievability = Ascending retrievability
+deck-config-interleave-review-decks = Interleave subdecks
+deck-config-interleave-review-decks-tooltip =
+    When enabled, reviews are shown from each subdeck in turn, instead of
+    all the reviews of one deck being shown together. The review sort order is
+    kept within each subdeck.
 deck-config-display-order-will-use-current-deck =
     Anki will use the display order from the deck you 
     select to study, and not any subdecks it may have.
@@ -272,6 +308,10 @@ deck-config-new-interval-tooltip = The multiplier applied to a review interval w
 deck-config-minimum-interval-tooltip = The minimum interval given to a review card after answering `Again`.
 deck-config-custom-scheduling = Custom scheduling
 deck-config-custom-scheduling-tooltip = Affects the entire collection. Use at your own risk!
+deck-config-card-state-script = Card state script
+deck-config-card-state-script-tooltip =
+    A script that can adjust the intervals of the answer buttons, and store values in a card's
+    custom data. Unlike custom scheduling, it is applied on all devices. Affects the entire collection.
 
 ## Adding/renaming
 
@@ -356,6 +396,10 @@ deck-config-fsrs-on-all-clients =
 deck-config-estimated-retention = Estimated retention: { $num }
 deck-config-complete = { $num }% complete.
 deck-config-iterations = Iteration: { $count }...
+deck-config-weights-grouping = Optimize parameters for
+deck-config-weights-grouping-preset = Whole preset
+deck-config-weights-grouping-notetype = Each note type
+deck-config-weights-grouping-template = Each card template
 deck-config-reschedule-cards-on-change = Reschedule cards on change
 deck-config-fsrs-tooltip =
     The Free Spaced Repetition Scheduler (FSRS) is an alternative to Anki's legacy SuperMemo 2 (SM2) scheduler.
@@ -380,6 +424,10 @@ deck-config-weights-tooltip =
     FSRS parameters affect how cards are scheduled. Anki will start with default parameters. Once
     you've accumulated 1000+ reviews, you can use the option below to optimize the parameters to best
     match your performance in decks using this preset.
+deck-config-weights-grouping-tooltip =
+    By default, a single set of parameters is used for all cards of the preset. If the preset contains
+    very different kinds of cards, parameters can also be optimized separately for each note type or
+    card template. Note types and templates with fewer than 1000 reviews use the preset's parameters.
 deck-config-reschedule-cards-on-change-tooltip =
     This option controls whether the due dates of cards will be changed when you enable FSRS, or optimize
     the parameters. The default is not to reschedule cards: future reviews will use the new scheduling, but
@@ -16,7 +16,10 @@ exporting-include-scheduling-information = Include scheduling information
 exporting-include-deck-configs = Include deck presets
 exporting-include-tags = Include tags
 exporting-support-older-anki-versions = Support older Anki versions (slower/larger files)
+exporting-password-placeholder = Password to encrypt the package (optional)
 exporting-notes-in-plain-text = Notes in Plain Text
+exporting-notes-in-json = Notes in JSON
+exporting-review-history-in-plain-text = Review History in Plain Text
 exporting-selected-notes = Selected Notes
 exporting-card-exported =
     { $count ->
@@ -33,6 +36,11 @@ exporting-note-exported =
         [one] { $count } note exported.
        *[other] { $count } notes exported.
     }
+exporting-review-exported =
+    { $count ->
+        [one] { $count } review exported.
+       *[other] { $count } reviews exported.
+    }
 exporting-exporting-file = Exporting file...
 exporting-processed-media-files =
     { $count ->
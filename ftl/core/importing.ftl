@@ -138,6 +138,8 @@ importing-duplicate = Duplicate
 importing-preserve = Preserve
 # "Existing notes: Update" (verb)
 importing-update = Update
+# "Existing notes: Update if newer"
+importing-update-if-newer-than-existing = Update if newer
 importing-tag-all-notes = Tag all notes
 importing-tag-updated-notes = Tag updated notes
 importing-file = File
@@ -151,6 +153,10 @@ importing-cards-added =
        *[other] { $count } cards added.
     }
 importing-file-empty = The file you selected is empty.
+importing-package-password-required = This package is encrypted. Please enter its password.
+importing-package-wrong-password = The password is incorrect.
+importing-package-password = Password (for encrypted packages)
+importing-scheduling = Scheduling
 importing-notes-added =
     { $count ->
         [one] { $count } new note imported.
@@ -194,6 +200,7 @@ importing-note-updated-as-file-had-newer = Note updated, as file had newer versi
 importing-note-skipped-due-to-missing-notetype = Note skipped, as its notetype was missing
 importing-note-skipped-due-to-missing-deck = Note skipped, as its deck was missing
 importing-note-skipped-due-to-empty-first-field = Note skipped, as its first field is empty
+importing-note-skipped-as-collection-had-newer = Note not updated, as your collection had a newer version
 importing-field-separator-help =
     The character separating fields in the text file. You can use the preview to check
     if the fields are separated correctly.
@@ -216,6 +223,8 @@ importing-existing-notes-help =
     What to do if an imported note matches an existing one.
     
     - `{ importing-update }`: Update the existing note.
+    - `{ importing-update-if-newer-than-existing }`: Update the existing note if the file's version was
+      modified more recently. Requires a column with modification times.
     - `{ importing-preserve }`: Do nothing.
     - `{ importing-duplicate }`: Create a new note.
 importing-match-scope-help =
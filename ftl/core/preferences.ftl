@@ -43,6 +43,8 @@ preferences-backup-explanation =
     Anki periodically backs up your collection. After backups are more than 2 days old,
     Anki will start removing some of them to free up disk space.
 preferences-daily-backups = Daily backups to keep:
+preferences-backup-password = Backup password:
+preferences-backup-password-placeholder = Leave empty to keep backups unencrypted
 preferences-weekly-backups = Weekly backups to keep:
 preferences-monthly-backups = Monthly backups to keep:
 preferences-minutes-between-backups = Minutes between automatic backups:
@@ -172,6 +172,21 @@ scheduling-set-due-date-done =
         [one] Set due date of { $cards } card.
        *[other] Set due date of { $cards } cards.
     }
+scheduling-set-interval-limits-prompt =
+    { $cards ->
+        [one] Minimum and maximum interval of the card, in days:
+       *[other] Minimum and maximum interval of the cards, in days:
+    }
+scheduling-set-interval-limits-prompt-hint =
+    7 30 = at least a week, at most a month
+    0 30 = at most a month
+    0 0 = no limits
+scheduling-set-interval-limits-invalid = Please enter a minimum and maximum number of days, separated by a space.
+scheduling-set-interval-limits-done =
+    { $cards ->
+        [one] Set interval limits of { $cards } card.
+       *[other] Set interval limits of { $cards } cards.
+    }
 scheduling-forgot-cards =
     { $cards ->
         [one] Forgot { $cards } card.
@@ -31,6 +31,7 @@ qt-accel-undo = &Undo
 qt-accel-redo = &Redo
 qt-accel-set-due-date = Set &Due Date...
 qt-accel-forget = &Forget
+qt-accel-set-interval-limits = Set &Interval Limits...
 qt-accel-view = &View
 qt-accel-full-screen = Toggle &Full Screen
 qt-accel-layout = &Layout
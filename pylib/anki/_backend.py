@@ -33,6 +33,7 @@ from .errors import (
     InvalidInput,
     NetworkError,
     NotFoundError,
+    PackagePasswordError,
     SchedulerUpgradeRequired,
     SearchError,
     SyncError,
@@ -147,6 +148,10 @@ class RustBackend(RustBackendGenerated):
     def compute_weights_from_items(self, items: Iterable[FsrsItem]) -> Sequence[float]:
         return self.compute_fsrs_weights_from_items(items).weights
 
+    def compute_weights_from_file(self, path: str) -> Sequence[float]:
+        "Train on a file written by export_fsrs_items()."
+        return self.compute_fsrs_weights_from_items(items=[], path=path).weights
+
     def _run_command(self, service: int, method: int, input: bytes) -> bytes:
         try:
             return self._backend.command(service, method, input)
@@ -244,6 +249,9 @@ def backend_exception_to_pylib(err: backend_pb2.BackendError) -> Exception:
     elif val == kind.SCHEDULER_UPGRADE_REQUIRED:
         return SchedulerUpgradeRequired(err.message, help_page, context, backtrace)
 
+    elif val == kind.PACKAGE_PASSWORD_ERROR:
+        return PackagePasswordError(err.message, help_page, context, backtrace)
+
     else:
         # sadly we can't do exhaustiveness checking on protobuf enums
         # assert_exhaustive(val)
@@ -47,6 +47,10 @@ class Card(DeprecatedNamesMixin):
     type: CardType
     memory_state: FSRSMemoryState | None
     desired_retention: float | None
+    prerequisite_note_ids: list[anki.notes.NoteId]
+    memory_state_hash: int | None
+    minimum_interval: int | None
+    maximum_interval: int | None
 
     def __init__(
         self,
@@ -100,6 +104,18 @@ class Card(DeprecatedNamesMixin):
         self.desired_retention = (
             card.desired_retention if card.HasField("desired_retention") else None
         )
+        self.prerequisite_note_ids = [
+            anki.notes.NoteId(nid) for nid in card.prerequisite_note_ids
+        ]
+        self.memory_state_hash = (
+            card.memory_state_hash if card.HasField("memory_state_hash") else None
+        )
+        self.minimum_interval = (
+            card.minimum_interval if card.HasField("minimum_interval") else None
+        )
+        self.maximum_interval = (
+            card.maximum_interval if card.HasField("maximum_interval") else None
+        )
 
     def _to_backend_card(self) -> cards_pb2.Card:
         # mtime & usn are set by backend
@@ -123,6 +139,10 @@ class Card(DeprecatedNamesMixin):
             custom_data=self.custom_data,
             memory_state=self.memory_state,
             desired_retention=self.desired_retention,
+            prerequisite_note_ids=self.prerequisite_note_ids,
+            memory_state_hash=self.memory_state_hash,
+            minimum_interval=self.minimum_interval,
+            maximum_interval=self.maximum_interval,
         )
 
     @deprecated(info="please use col.update_card()")
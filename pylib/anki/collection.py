@@ -41,6 +41,7 @@ BrowserRow = search_pb2.BrowserRow
 BrowserColumns = search_pb2.BrowserColumns
 StripHtmlMode = card_rendering_pb2.StripHtmlRequest
 ImportLogWithChanges = import_export_pb2.ImportResponse
+ImportPreview = import_export_pb2.ImportPreview
 ImportAnkiPackageRequest = import_export_pb2.ImportAnkiPackageRequest
 ImportAnkiPackageOptions = import_export_pb2.ImportAnkiPackageOptions
 ExportAnkiPackageOptions = import_export_pb2.ExportAnkiPackageOptions
@@ -326,10 +327,12 @@ class Collection(DeprecatedNamesMixin):
         backup_folder: str,
         force: bool,
         wait_for_completion: bool,
+        password: str | None = None,
     ) -> bool:
         """Create a backup if enough time has elapsed, and rotate old backups.
 
         If `force` is true, the user's configured backup interval is ignored.
+        If `password` is provided, the backup is encrypted with it.
         Returns true if backup created. This may be false in the force=True case,
         if no changes have been made to the collection.
 
@@ -341,6 +344,7 @@ class Collection(DeprecatedNamesMixin):
             backup_folder=backup_folder,
             force=force,
             wait_for_completion=wait_for_completion,
+            password=password,
         )
         return created
 
@@ -349,11 +353,18 @@ class Collection(DeprecatedNamesMixin):
         self._backend.await_backup_completion()
 
     def export_collection_package(
-        self, out_path: str, include_media: bool, legacy: bool
+        self,
+        out_path: str,
+        include_media: bool,
+        legacy: bool,
+        password: str | None = None,
     ) -> None:
         self.close_for_full_sync()
         self._backend.export_collection_package(
-            out_path=out_path, include_media=include_media, legacy=legacy
+            out_path=out_path,
+            include_media=include_media,
+            legacy=legacy,
+            password=password,
         )
 
     def import_anki_package(
@@ -413,12 +424,64 @@ class Collection(DeprecatedNamesMixin):
             limit=pb_export_limit(limit),
         )
 
+    def export_revlog_csv(
+        self,
+        *,
+        out_path: str,
+        limit: ExportLimit,
+        card_stats_out_path: str | None = None,
+    ) -> int:
+        "Write the review history of the cards, and optionally their statistics."
+        return self._backend.export_revlog_csv(
+            out_path=out_path,
+            limit=pb_export_limit(limit),
+            card_stats_out_path=card_stats_out_path,
+        )
+
+    def export_json(
+        self,
+        *,
+        out_path: str,
+        limit: ExportLimit,
+        with_scheduling: bool,
+    ) -> int:
+        return self._backend.export_json(
+            out_path=out_path,
+            with_scheduling=with_scheduling,
+            limit=pb_export_limit(limit),
+        )
+
     def import_json_file(self, path: str) -> ImportLogWithChanges:
         return self._backend.import_json_file(path)
 
     def import_json_string(self, json: str) -> ImportLogWithChanges:
         return self._backend.import_json_string(json)
 
+    def import_mnemosyne(self, path: str, deck_id: DeckId) -> ImportLogWithChanges:
+        return self._backend.import_mnemosyne(path=path, deck_id=deck_id)
+
+    def import_supermemo(self, path: str, deck_id: DeckId) -> ImportLogWithChanges:
+        return self._backend.import_supermemo(path=path, deck_id=deck_id)
+
+    def preview_import_anki_package(
+        self, request: ImportAnkiPackageRequest
+    ) -> ImportPreview:
+        "Report what the import would change, without modifying the collection."
+        preview = self._backend.preview_import_anki_package_raw(
+            request.SerializeToString()
+        )
+        return ImportPreview.FromString(preview)
+
+    def preview_import_csv(self, request: ImportCsvRequest) -> ImportPreview:
+        preview = self._backend.preview_import_csv_raw(request.SerializeToString())
+        return ImportPreview.FromString(preview)
+
+    def preview_import_json_file(self, path: str) -> ImportPreview:
+        return self._backend.preview_import_json_file(path)
+
+    def preview_import_json_string(self, json: str) -> ImportPreview:
+        return self._backend.preview_import_json_string(json)
+
     # Image Occlusion
     ##########################################################################
 
@@ -610,6 +673,14 @@ class Collection(DeprecatedNamesMixin):
     def set_deck(self, card_ids: Sequence[CardId], deck_id: int) -> OpChangesWithCount:
         return self._backend.set_deck(card_ids=card_ids, deck_id=deck_id)
 
+    def set_interval_limits(
+        self, card_ids: Sequence[CardId], minimum: int, maximum: int
+    ) -> OpChangesWithCount:
+        "Limit the review intervals of the cards, in days. 0 removes a limit."
+        return self._backend.set_interval_limits(
+            card_ids=card_ids, minimum=minimum, maximum=maximum
+        )
+
     def get_empty_cards(self) -> EmptyCardsReport:
         return self._backend.get_empty_cards()
 
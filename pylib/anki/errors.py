@@ -123,6 +123,10 @@ class SchedulerUpgradeRequired(BackendError):
     pass
 
 
+class PackagePasswordError(BackendError):
+    "An encrypted package was imported without its password, or with a wrong one."
+
+
 class AbortSchemaModification(AnkiException):
     pass
 
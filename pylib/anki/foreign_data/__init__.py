@@ -70,6 +70,27 @@ class ForeignNotetype:
         )
 
 
+@dataclass
+class ForeignReview:
+    """An entry of a card's review history.
+
+    time      --  UNIX timestamp in milliseconds
+    rating    --  1-4, or 0 for manual rescheduling
+    interval  --  days if positive, seconds if negative
+    last_interval  --  the interval before the review, in the same units
+    ease_factor  --  the ease after the review, eg 2500 for 250%
+    kind      --  0: learning, 1: review, 2: relearning, 3: filtered, 4: manual
+    """
+
+    time: int = 0
+    rating: int = 0
+    interval: int = 0
+    last_interval: int = 0
+    ease_factor: int = 0
+    taken_millis: int = 0
+    kind: int = 0
+
+
 @dataclass
 class ForeignCard:
     """Data for creating an Anki card.
@@ -77,17 +98,28 @@ class ForeignCard:
     Usually a review card, as the default card generation routine will take care
     of missing new cards.
 
-    due          --  UNIX timestamp
-    interval     --  days
+    Values that are None are left unchanged on existing cards.
+
+    due          --  UNIX timestamp, ignored for new cards
+    interval     --  days, or 0 for a new card
+    position     --  of a new card, or of a learning card when it was new
+    remaining_steps  --  makes it a (re)learning card due at the time in due
     ease_factor  --  decimal fraction (2.5 corresponds to default ease)
+    stability    --  FSRS memory state, used if difficulty is also set
     """
 
-    # TODO: support new and learning cards?
-    due: int = 0
-    interval: int = 1
-    ease_factor: float = STARTING_FACTOR_FRACTION
-    reps: int = 0
-    lapses: int = 0
+    due: Union[int, None] = 0
+    interval: Union[int, None] = 1
+    position: Union[int, None] = None
+    remaining_steps: Union[int, None] = None
+    ease_factor: Union[float, None] = STARTING_FACTOR_FRACTION
+    reps: Union[int, None] = 0
+    lapses: Union[int, None] = 0
+    suspended: Union[bool, None] = None
+    flags: Union[int, None] = None
+    stability: Union[float, None] = None
+    difficulty: Union[float, None] = None
+    reviews: list[ForeignReview] = field(default_factory=list)
 
 
 @dataclass
@@ -97,6 +129,8 @@ class ForeignNote:
     notetype: Union[str, NotetypeId] = ""
     deck: Union[str, DeckId] = ""
     cards: list[ForeignCard] = field(default_factory=list)
+    # UNIX timestamp of the last modification, for updating only if newer
+    mtime: Union[int, None] = None
 
 
 @dataclass
@@ -113,7 +147,14 @@ class ForeignDataEncoder(json.JSONEncoder):
     def default(self, obj: object) -> dict:
         if isinstance(
             obj,
-            (ForeignData, ForeignNote, ForeignCard, ForeignNotetype, ForeignCardType),
+            (
+                ForeignData,
+                ForeignNote,
+                ForeignCard,
+                ForeignReview,
+                ForeignNotetype,
+                ForeignCardType,
+            ),
         ):
             return asdict(obj)
         return json.JSONEncoder.default(self, obj)
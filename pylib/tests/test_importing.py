@@ -9,6 +9,8 @@ from tempfile import NamedTemporaryFile
 import pytest
 
 from anki.consts import *
+from anki.decks import DeckId
+from anki.foreign_data import mnemosyne
 from anki.importing import (
     Anki2Importer,
     AnkiPackageImporter,
@@ -331,3 +333,11 @@ def test_mnemo():
     assert "a_longer_tag" in col.tags.all()
     assert col.db.scalar(f"select count() from cards where type = {CARD_TYPE_NEW}") == 1
     col.close()
+
+
+def test_mnemo_serialize():
+    col = getEmptyCol()
+    file = str(os.path.join(testDir, "support", "mnemo.db"))
+    col.import_json_string(mnemosyne.serialize(file, DeckId(1)))
+    assert col.card_count() == 7
+    col.close()
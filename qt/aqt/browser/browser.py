@@ -36,6 +36,7 @@ from aqt.operations.scheduling import (
     forget_cards,
     reposition_new_cards_dialog,
     set_due_date_dialog,
+    set_interval_limits_dialog,
     suspend_cards,
     unbury_cards,
     unsuspend_cards,
@@ -305,6 +306,7 @@ class Browser(QMainWindow):
         qconnect(f.action_Info.triggered, self.showCardInfo)
         qconnect(f.actionReposition.triggered, self.reposition)
         qconnect(f.action_set_due_date.triggered, self.set_due_date)
+        qconnect(f.action_set_interval_limits.triggered, self.set_interval_limits)
         qconnect(f.action_forget.triggered, self.forget_cards)
         qconnect(f.actionToggle_Suspend.triggered, self.suspend_selected_cards)
         qconnect(f.action_toggle_bury.triggered, self.bury_selected_cards)
@@ -592,6 +594,7 @@ class Browser(QMainWindow):
         self.form.actionDelete.setEnabled(has_selection)
         self.form.actionChange_Deck.setEnabled(has_selection)
         self.form.action_set_due_date.setEnabled(has_selection)
+        self.form.action_set_interval_limits.setEnabled(has_selection)
         self.form.action_forget.setEnabled(has_selection)
         self.form.actionReposition.setEnabled(has_selection)
         self.form.actionToggle_Suspend.setEnabled(has_selection)
@@ -986,6 +989,16 @@ class Browser(QMainWindow):
         ):
             op.run_in_background()
 
+    @no_arg_trigger
+    @skip_if_selection_is_empty
+    @ensure_editor_saved
+    def set_interval_limits(self) -> None:
+        if op := set_interval_limits_dialog(
+            parent=self,
+            card_ids=self.selected_cards(),
+        ):
+            op.run_in_background()
+
     @no_arg_trigger
     @skip_if_selection_is_empty
     @ensure_editor_saved
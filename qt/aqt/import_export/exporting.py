@@ -61,6 +61,8 @@ class ExportDialog(QDialog):
             ColpkgExporter,
             NoteCsvExporter,
             CardCsvExporter,
+            RevlogCsvExporter,
+            JsonExporter,
         ]
         gui_hooks.exporters_list_did_initialize(self.exporter_classes)
         self.frm.format.insertItems(
@@ -106,6 +108,7 @@ class ExportDialog(QDialog):
         self.frm.includeNotetype.setVisible(self.exporter.show_include_notetype)
         self.frm.includeGuid.setVisible(self.exporter.show_include_guid)
         self.frm.legacy_support.setVisible(self.exporter.show_legacy_support)
+        self.frm.password.setVisible(self.exporter.show_password)
         self.frm.deck.setVisible(self.exporter.show_deck_list)
 
     def accept(self) -> None:
@@ -155,6 +158,7 @@ class ExportDialog(QDialog):
             include_guid=self.frm.includeGuid.isChecked(),
             legacy_support=self.frm.legacy_support.isChecked(),
             limit=limit,
+            password=self.frm.password.text() or None,
         )
 
     def current_deck_id(self) -> DeckId | None:
@@ -189,6 +193,7 @@ class ExportOptions:
     include_guid: bool
     legacy_support: bool
     limit: ExportLimit
+    password: str | None = None
 
 
 class Exporter(ABC):
@@ -203,6 +208,7 @@ class Exporter(ABC):
     show_include_deck = False
     show_include_notetype = False
     show_include_guid = False
+    show_password = False
 
     @abstractmethod
     def export(self, mw: aqt.main.AnkiQt, options: ExportOptions) -> None:
@@ -218,6 +224,7 @@ class ColpkgExporter(Exporter):
     extension = "colpkg"
     show_include_media = True
     show_legacy_support = True
+    show_password = True
 
     @staticmethod
     def name() -> str:
@@ -242,6 +249,7 @@ class ColpkgExporter(Exporter):
                 options.out_path,
                 include_media=options.include_media,
                 legacy=options.legacy_support,
+                password=options.password,
             ),
             success=on_success,
         ).with_backend_progress(export_progress_update).failure(
@@ -256,6 +264,7 @@ class ApkgExporter(Exporter):
     show_include_deck_configs = True
     show_include_media = True
     show_legacy_support = True
+    show_password = True
 
     @staticmethod
     def name() -> str:
@@ -278,6 +287,7 @@ class ApkgExporter(Exporter):
                     with_deck_configs=options.include_deck_configs,
                     with_media=options.include_media,
                     legacy=options.legacy_support,
+                    password=options.password,
                 ),
             ),
             success=on_success,
@@ -346,6 +356,58 @@ class CardCsvExporter(Exporter):
         ).with_backend_progress(export_progress_update).run_in_background()
 
 
+class RevlogCsvExporter(Exporter):
+    extension = "txt"
+    show_deck_list = True
+
+    @staticmethod
+    def name() -> str:
+        return tr.exporting_review_history_in_plain_text()
+
+    def export(self, mw: aqt.main.AnkiQt, options: ExportOptions) -> None:
+        options = gui_hooks.exporter_will_export(options, self)
+
+        def on_success(count: int) -> None:
+            gui_hooks.exporter_did_export(options, self)
+            tooltip(tr.exporting_review_exported(count=count), parent=mw)
+
+        QueryOp(
+            parent=mw,
+            op=lambda col: col.export_revlog_csv(
+                out_path=options.out_path,
+                limit=options.limit,
+            ),
+            success=on_success,
+        ).with_backend_progress(export_progress_update).run_in_background()
+
+
+class JsonExporter(Exporter):
+    extension = "anki-json"
+    show_deck_list = True
+    show_include_scheduling = True
+
+    @staticmethod
+    def name() -> str:
+        return tr.exporting_notes_in_json()
+
+    def export(self, mw: aqt.main.AnkiQt, options: ExportOptions) -> None:
+        options = gui_hooks.exporter_will_export(options, self)
+
+        def on_success(count: int) -> None:
+            gui_hooks.exporter_did_export(options, self)
+            tooltip(tr.exporting_note_exported(count=count), parent=mw)
+
+        QueryOp(
+            parent=mw,
+            op=lambda col: col.export_json(
+                out_path=options.out_path,
+                limit=options.limit,
+                with_scheduling=options.include_scheduling,
+            ),
+            success=on_success,
+        ).with_backend_progress(export_progress_update).run_in_background()
+
+
 def export_progress_update(progress: Progress, update: ProgressUpdate) -> None:
     if not progress.HasField("exporting"):
         return
@@ -40,6 +40,17 @@ class JsonStringArgs(ImportArgs):
         return json.dumps(dict(type="json_string", path=self.path, json=self.json))
 
 
+@dataclass
+class ForeignFileArgs(ImportArgs):
+    """For files with a dedicated importer, like Mnemosyne and SuperMemo."""
+
+    type: str
+    deck_id: int
+
+    def args_json(self) -> str:
+        return json.dumps(dict(type=self.type, path=self.path, deckId=self.deck_id))
+
+
 class CsvArgs(ImportArgs):
     title = "csv import"
     kind = AnkiWebViewKind.IMPORT_CSV
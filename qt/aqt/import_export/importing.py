@@ -10,20 +10,19 @@ from typing import Type
 
 import aqt.main
 from anki.collection import Collection, Progress
-from anki.errors import Interrupted
-from anki.foreign_data import mnemosyne
+from anki.errors import Interrupted, PackagePasswordError
 from anki.lang import without_unicode_isolation
 from aqt.import_export.import_dialog import (
     AnkiPackageArgs,
     CsvArgs,
+    ForeignFileArgs,
     ImportDialog,
     JsonFileArgs,
-    JsonStringArgs,
 )
 from aqt.operations import QueryOp
 from aqt.progress import ProgressUpdate
 from aqt.qt import *
-from aqt.utils import askUser, getFile, showWarning, tooltip, tr
+from aqt.utils import askUser, getFile, getText, showWarning, tooltip, tr
 
 
 class Importer(ABC):
@@ -68,18 +67,24 @@ class ColpkgImporter(Importer):
             tooltip(tr.importing_importing_complete())
 
         def on_failure(err: Exception) -> None:
+            if isinstance(err, PackagePasswordError) and (
+                password := ask_for_package_password(mw, err)
+            ):
+                run_import(password)
+                return
             mw.loadCollection()
             if not isinstance(err, Interrupted):
                 showWarning(str(err))
 
+        def run_import(password: str | None) -> None:
+            import_collection_package_op(
+                mw, file, on_success, password=password
+            ).failure(on_failure).run_in_background()
+
         QueryOp(
             parent=mw,
             op=lambda _: mw.create_backup_now(),
-            success=lambda _: mw.unloadCollection(
-                lambda: import_collection_package_op(mw, file, on_success)
-                .failure(on_failure)
-                .run_in_background()
-            ),
+            success=lambda _: mw.unloadCollection(lambda: run_import(None)),
         ).with_progress().run_in_background()
 
 
@@ -96,11 +101,21 @@ class MnemosyneImporter(Importer):
 
     @staticmethod
     def do_import(mw: aqt.main.AnkiQt, path: str) -> None:
-        QueryOp(
-            parent=mw,
-            op=lambda col: mnemosyne.serialize(path, col.decks.current()["id"]),
-            success=lambda json: ImportDialog(mw, JsonStringArgs(path=path, json=json)),
-        ).with_progress().run_in_background()
+        deck_id = mw.col.decks.current()["id"]
+        ImportDialog(
+            mw, ForeignFileArgs(path=path, type="mnemosyne", deck_id=deck_id)
+        )
+
+
+class SupermemoImporter(Importer):
+    accepted_file_endings = [".xml"]
+
+    @staticmethod
+    def do_import(mw: aqt.main.AnkiQt, path: str) -> None:
+        deck_id = mw.col.decks.current()["id"]
+        ImportDialog(
+            mw, ForeignFileArgs(path=path, type="supermemo", deck_id=deck_id)
+        )
 
 
 class CsvImporter(Importer):
@@ -123,6 +138,7 @@ IMPORTERS: list[Type[Importer]] = [
     ColpkgImporter,
     ApkgImporter,
     MnemosyneImporter,
+    SupermemoImporter,
     CsvImporter,
 ]
 
@@ -130,12 +146,19 @@ IMPORTERS: list[Type[Importer]] = [
 def legacy_file_endings(col: Collection) -> list[str]:
     from anki.importing import AnkiPackageImporter
     from anki.importing import MnemosyneImporter as LegacyMnemosyneImporter
+    from anki.importing import SupermemoXmlImporter as LegacySupermemoImporter
     from anki.importing import TextImporter, importers
 
     return [
         ext
         for (text, importer) in importers(col)
-        if importer not in (TextImporter, AnkiPackageImporter, LegacyMnemosyneImporter)
+        if importer
+        not in (
+            TextImporter,
+            AnkiPackageImporter,
+            LegacyMnemosyneImporter,
+            LegacySupermemoImporter,
+        )
         for ext in re.findall(r"[( ]?\*(\..+?)[) ]", text)
     ]
 
@@ -185,7 +208,10 @@ def all_accepted_file_endings(mw: aqt.main.AnkiQt) -> set[str]:
 
 
 def import_collection_package_op(
-    mw: aqt.main.AnkiQt, path: str, success: Callable[[], None]
+    mw: aqt.main.AnkiQt,
+    path: str,
+    success: Callable[[], None],
+    password: str | None = None,
 ) -> QueryOp[None]:
     def op(_: Collection) -> None:
         col_path = mw.pm.collectionPath()
@@ -196,6 +222,7 @@ def import_collection_package_op(
             backup_path=path,
             media_folder=media_folder,
             media_db=media_db,
+            password=password,
         )
 
     return QueryOp(parent=mw, op=op, success=lambda _: success()).with_backend_progress(
@@ -203,6 +230,16 @@ def import_collection_package_op(
     )
 
 
+def ask_for_package_password(
+    parent: QWidget, err: PackagePasswordError
+) -> str | None:
+    "Ask for the password of an encrypted package. None if the user cancelled."
+    edit = QLineEdit()
+    edit.setEchoMode(QLineEdit.EchoMode.Password)
+    password, ok = getText(str(err), parent=parent, edit=edit)
+    return password if ok and password else None
+
+
 def import_progress_update(progress: Progress, update: ProgressUpdate) -> None:
     if not progress.HasField("importing"):
         return
@@ -31,6 +31,7 @@ from anki._backend import RustBackend as _RustBackend
 from anki._legacy import deprecated
 from anki.collection import Collection, Config, OpChanges, UndoStatus
 from anki.decks import DeckDict, DeckId
+from anki.errors import PackagePasswordError
 from anki.hooks import runHook
 from anki.notes import NoteId
 from anki.sound import AVTag, SoundOrVideoTag
@@ -49,9 +50,11 @@ from aqt.addons import DownloadLogEntry, check_and_prompt_for_updates, show_log_
 from aqt.dbcheck import check_db
 from aqt.debug_console import show_debug_console
 from aqt.emptycards import show_empty_cards
+from aqt.errors import show_exception
 from aqt.flags import FlagManager
 from aqt.import_export.exporting import ExportDialog
 from aqt.import_export.importing import (
+    ask_for_package_password,
     import_collection_package_op,
     import_file,
     prompt_for_file_then_import,
@@ -445,13 +448,22 @@ class AnkiQt(QMainWindow):
             dir=self.pm.backupFolder(),
         )
 
-    def _openBackup(self, path: str) -> None:
-        self.restoring_backup = True
-        showInfo(tr.qt_misc_automatic_syncing_and_backups_have_been())
+    def _openBackup(self, path: str, password: str | None = None) -> None:
+        if password is None:
+            self.restoring_backup = True
+            showInfo(tr.qt_misc_automatic_syncing_and_backups_have_been())
+
+        def on_failure(err: Exception) -> None:
+            if isinstance(err, PackagePasswordError) and (
+                password := ask_for_package_password(self.profileDiag, err)
+            ):
+                self._openBackup(path, password)
+            else:
+                show_exception(parent=self.profileDiag, exception=err)
 
         import_collection_package_op(
-            self, path, success=self.onOpenProfile
-        ).run_in_background()
+            self, path, success=self.onOpenProfile, password=password
+        ).failure(on_failure).run_in_background()
 
     def _on_downgrade(self) -> None:
         self.progress.start()
@@ -690,6 +702,7 @@ class AnkiQt(QMainWindow):
                         backup_folder=self.pm.backupFolder(),
                         force=False,
                         wait_for_completion=False,
+                        password=self.pm.backup_password(),
                     )
                 except:
                     print("backup on close failed")
@@ -1489,6 +1502,7 @@ title="{}" {}>{}</button>""".format(
             backup_folder=self.pm.backupFolder(),
             force=True,
             wait_for_completion=True,
+            password=self.pm.backup_password(),
         )
 
     def _create_backup_with_progress(self, user_initiated: bool) -> None:
@@ -1498,6 +1512,7 @@ title="{}" {}>{}</button>""".format(
                 backup_folder=self.pm.backupFolder(),
                 force=user_initiated,
                 wait_for_completion=False,
+                password=self.pm.backup_password(),
             )
 
         def on_success(val: None) -> None:
@@ -24,7 +24,7 @@ from anki.scheduler.v3 import CardAnswer
 from anki.scheduler.v3 import Scheduler as V3Scheduler
 from aqt.operations import CollectionOp
 from aqt.qt import *
-from aqt.utils import disable_help_button, getText, tooltip, tr
+from aqt.utils import disable_help_button, getText, showWarning, tooltip, tr
 
 
 def set_due_date_dialog(
@@ -65,6 +65,46 @@ def set_due_date_dialog(
         )
 
 
+def set_interval_limits_dialog(
+    *,
+    parent: QWidget,
+    card_ids: Sequence[CardId],
+) -> CollectionOp[OpChangesWithCount] | None:
+    if not card_ids:
+        return None
+
+    prompt = "\n".join(
+        [
+            tr.scheduling_set_interval_limits_prompt(cards=len(card_ids)),
+            tr.scheduling_set_interval_limits_prompt_hint(),
+        ]
+    )
+    (text, success) = getText(
+        prompt=prompt,
+        parent=parent,
+        default="0 0",
+        title=tr.actions_set_interval_limits(),
+    )
+    if not success or not text.strip():
+        return None
+    try:
+        minimum, maximum = (int(days) for days in text.split())
+        if minimum < 0 or maximum < 0:
+            raise ValueError
+    except ValueError:
+        showWarning(tr.scheduling_set_interval_limits_invalid(), parent=parent)
+        return None
+
+    return CollectionOp(
+        parent, lambda col: col.set_interval_limits(card_ids, minimum, maximum)
+    ).success(
+        lambda _: tooltip(
+            tr.scheduling_set_interval_limits_done(cards=len(card_ids)),
+            parent=parent,
+        )
+    )
+
+
 def forget_cards(
     *,
     parent: QWidget,
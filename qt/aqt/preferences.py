@@ -168,9 +168,11 @@ class Preferences(QDialog):
     def setup_profile(self) -> None:
         "Setup options stored in the user profile."
         self.setup_network()
+        self.setup_backups()
 
     def update_profile(self) -> None:
         self.update_network()
+        self.update_backups()
 
     # Profile: network
     ######################################################################
@@ -219,6 +221,15 @@ class Preferences(QDialog):
         self.mw.pm.set_custom_sync_url(self.form.custom_sync_url.text())
         self.mw.pm.set_network_timeout(self.form.network_timeout.value())
 
+    # Profile: backups
+    ######################################################################
+
+    def setup_backups(self) -> None:
+        self.form.backup_password.setText(self.mw.pm.backup_password() or "")
+
+    def update_backups(self) -> None:
+        self.mw.pm.set_backup_password(self.form.backup_password.text())
+
     # Global preferences
     ######################################################################
 
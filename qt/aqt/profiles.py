@@ -699,6 +699,13 @@ create table if not exists profiles
     def set_show_browser_table_tooltips(self, val: bool) -> None:
         self.profile["browserTableTooltips"] = val
 
+    def backup_password(self) -> str | None:
+        "If set, backups are encrypted with this password."
+        return self.profile.get("backupPassword") or None
+
+    def set_backup_password(self, val: str | None) -> None:
+        self.profile["backupPassword"] = val or None
+
     def set_network_timeout(self, timeout_secs: int) -> None:
         self.profile["networkTimeout"] = timeout_secs
 
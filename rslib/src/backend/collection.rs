@@ -63,7 +63,9 @@ impl BackendCollectionService for Backend {
             task.join().unwrap()?;
         }
         // start the new backup
-        let created = if let Some(task) = col.maybe_backup(input.backup_folder, input.force)? {
+        let created = if let Some(task) =
+            col.maybe_backup(input.backup_folder, input.force, input.password)?
+        {
             if input.wait_for_completion {
                 drop(col_lock);
                 task.join().unwrap()?;
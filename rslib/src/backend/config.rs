@@ -47,6 +47,7 @@ impl From<StringKeyProto> for StringKey {
             StringKeyProto::SetDueReviewer => StringKey::SetDueReviewer,
             StringKeyProto::DefaultSearchText => StringKey::DefaultSearchText,
             StringKeyProto::CardStateCustomizer => StringKey::CardStateCustomizer,
+            StringKeyProto::CardStateScript => StringKey::CardStateScript,
         }
     }
 }
@@ -5,6 +5,7 @@ use anki_proto::backend::backend_error::Kind;
 
 use crate::error::AnkiError;
 use crate::error::SyncErrorKind;
+use crate::import_export::ImportError;
 use crate::prelude::*;
 
 impl AnkiError {
@@ -36,6 +37,9 @@ impl AnkiError {
             AnkiError::MultipleNotetypesSelected => Kind::InvalidInput,
             AnkiError::DatabaseCheckRequired => Kind::InvalidInput,
             AnkiError::CustomStudyError { .. } => Kind::CustomStudyError,
+            AnkiError::ImportError {
+                source: ImportError::PasswordRequired | ImportError::WrongPassword,
+            } => Kind::PackagePasswordError,
             AnkiError::ImportError { .. } => Kind::ImportError,
             AnkiError::FileIoError { .. } => Kind::IoError,
             AnkiError::MediaCheckRequired => Kind::InvalidInput,
@@ -5,6 +5,7 @@ use std::path::Path;
 
 use super::Backend;
 use crate::import_export::package::import_colpkg;
+use crate::import_export::text::mnemosyne_foreign_data_json;
 use crate::prelude::*;
 use crate::services::BackendImportExportService;
 
@@ -19,7 +20,12 @@ impl BackendImportExportService for Backend {
 
         let col_inner = guard.take().unwrap();
         col_inner
-            .export_colpkg(input.out_path, input.include_media, input.legacy)
+            .export_colpkg(
+                input.out_path,
+                input.include_media,
+                input.legacy,
+                input.password.as_deref(),
+            )
             .map(Into::into)
     }
 
@@ -34,8 +40,16 @@ impl BackendImportExportService for Backend {
             &input.col_path,
             Path::new(&input.media_folder),
             Path::new(&input.media_db),
+            input.password.as_deref(),
             self.new_progress_handler(),
         )
         .map(Into::into)
     }
+
+    fn get_mnemosyne_foreign_data(
+        &self,
+        input: anki_proto::import_export::ImportForeignFileRequest,
+    ) -> Result<anki_proto::generic::String> {
+        mnemosyne_foreign_data_json(&input.path, DeckId(input.deck_id)).map(Into::into)
+    }
 }
@@ -0,0 +1,95 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+use crate::prelude::*;
+
+impl Card {
+    /// The minimum and maximum review interval of the card, given the
+    /// preset's maximum interval. Both are at least 1, and the minimum is at
+    /// most the maximum.
+    pub(crate) fn review_interval_limits(&self, preset_maximum: u32) -> (u32, u32) {
+        let maximum = self
+            .maximum_interval
+            .map_or(preset_maximum, |max| max.min(preset_maximum))
+            .max(1);
+        let minimum = self.minimum_interval.unwrap_or(1).clamp(1, maximum);
+        (minimum, maximum)
+    }
+}
+
+impl Collection {
+    /// Limit the review intervals of the provided cards. A limit of 0 removes
+    /// it. Returns the number of updated cards.
+    pub fn set_card_interval_limits(
+        &mut self,
+        cards: &[CardId],
+        minimum: u32,
+        maximum: u32,
+    ) -> Result<OpOutput<usize>> {
+        require!(
+            minimum == 0 || maximum == 0 || minimum <= maximum,
+            "minimum interval is above maximum"
+        );
+        let to_limit = |limit: u32| (limit > 0).then_some(limit);
+        let (minimum, maximum) = (to_limit(minimum), to_limit(maximum));
+        let usn = self.usn()?;
+        self.transact(Op::SetIntervalLimits, |col| {
+            let mut count = 0;
+            for mut card in col.all_cards_for_ids(cards, false)? {
+                if card.minimum_interval == minimum && card.maximum_interval == maximum {
+                    continue;
+                }
+                let original = card.clone();
+                card.minimum_interval = minimum;
+                card.maximum_interval = maximum;
+                col.update_card_inner(&mut card, original, usn)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::search::SortMode;
+    use crate::tests::*;
+
+    #[test]
+    fn intervals_are_limited() -> Result<()> {
+        let mut col = Collection::new();
+        col.update_default_deck_config(|config| {
+            config.learn_steps = vec![];
+            config.graduating_interval_easy = 60;
+        });
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+        let cid = col.storage.all_cards_of_note(note.id)?[0].id;
+        assert_eq!(col.set_card_interval_limits(&[cid], 0, 30)?.output, 1);
+        assert_eq!(
+            col.search_cards("prop:maxivl=30", SortMode::NoOrder)?,
+            [cid]
+        );
+        assert!(col
+            .search_cards("prop:minivl>0", SortMode::NoOrder)?
+            .is_empty());
+
+        col.answer_easy();
+        let card = col.storage.get_card(cid)?.unwrap();
+        assert_eq!(card.interval, 30);
+
+        // the minimum can't be above the maximum
+        assert!(col.set_card_interval_limits(&[cid], 40, 30).is_err());
+        // and the preset's maximum takes precedence
+        let mut card = Card {
+            minimum_interval: Some(50),
+            maximum_interval: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(card.review_interval_limits(36500), (50, 100));
+        assert_eq!(card.review_interval_limits(20), (20, 20));
+        card.maximum_interval = None;
+        assert_eq!(card.review_interval_limits(36500), (50, 36500));
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 // Copyright: Ankitects Pty Ltd and contributors
 // License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
 
+mod interval_limits;
+mod prerequisites;
 mod service;
 pub(crate) mod undo;
 
@@ -98,6 +100,15 @@ pub struct Card {
     /// JSON object or empty; exposed through the reviewer for persisting custom
     /// state
     pub(crate) custom_data: String,
+    /// Notes that must be learnt before this card is introduced, if it is new.
+    pub(crate) prerequisites: Vec<NoteId>,
+    /// Identifies the review history and parameters the memory state was last
+    /// computed from, so unchanged cards can be skipped when recomputing.
+    pub(crate) memory_state_hash: Option<u32>,
+    /// Limits the review intervals of this card, on top of the preset's
+    /// maximum interval.
+    pub(crate) minimum_interval: Option<u32>,
+    pub(crate) maximum_interval: Option<u32>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -146,6 +157,10 @@ impl Default for Card {
             memory_state: None,
             desired_retention: None,
             custom_data: String::new(),
+            prerequisites: vec![],
+            memory_state_hash: None,
+            minimum_interval: None,
+            maximum_interval: None,
         }
     }
 }
@@ -184,13 +199,13 @@ impl Card {
     }
 
     /// Caller must ensure provided deck exists and is not filtered.
-    fn set_deck(&mut self, deck: DeckId) {
+    pub(crate) fn set_deck(&mut self, deck: DeckId) {
         self.remove_from_filtered_deck_restoring_queue();
         self.deck_id = deck;
     }
 
     /// True if flag changed.
-    fn set_flag(&mut self, flag: u8) -> bool {
+    pub(crate) fn set_flag(&mut self, flag: u8) -> bool {
         // The first 3 bits represent one of the 7 supported flags, the rest of
         // the flag byte is preserved.
         let updated_flags = (self.flags & !0b111) | flag;
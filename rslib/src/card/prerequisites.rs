@@ -0,0 +1,155 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::prelude::*;
+
+impl Collection {
+    /// Require the notes in `prerequisites` to be learnt before the new cards
+    /// of `note_ids` are introduced. An empty list removes any existing
+    /// prerequisites. Returns the number of updated cards.
+    pub fn set_note_prerequisites(
+        &mut self,
+        note_ids: &[NoteId],
+        prerequisites: &[NoteId],
+    ) -> Result<OpOutput<usize>> {
+        require!(
+            !prerequisites.iter().any(|nid| note_ids.contains(nid)),
+            "a note can not be its own prerequisite"
+        );
+        let mut prerequisites = prerequisites.to_vec();
+        prerequisites.sort_unstable();
+        prerequisites.dedup();
+        for nid in &prerequisites {
+            self.storage.get_note(*nid)?.or_not_found(*nid)?;
+        }
+        self.ensure_no_prerequisite_cycle(note_ids, &prerequisites)?;
+
+        let usn = self.usn()?;
+        self.transact(Op::SetPrerequisites, |col| {
+            let mut count = 0;
+            for nid in note_ids {
+                for mut card in col.storage.all_cards_of_note(*nid)? {
+                    if card.prerequisites == prerequisites {
+                        continue;
+                    }
+                    let original = card.clone();
+                    card.prerequisites = prerequisites.clone();
+                    col.update_card_inner(&mut card, original, usn)?;
+                    count += 1;
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    /// The prerequisites of a note, as stored on its cards.
+    pub(crate) fn note_prerequisites(&self, nid: NoteId) -> Result<Vec<NoteId>> {
+        Ok(self
+            .storage
+            .all_cards_of_note(nid)?
+            .into_iter()
+            .flat_map(|card| card.prerequisites)
+            .sorted_unstable()
+            .dedup()
+            .collect())
+    }
+
+    /// Fails if any of `prerequisites` already depends on one of `note_ids`,
+    /// directly or indirectly, as the notes would then block each other
+    /// forever.
+    fn ensure_no_prerequisite_cycle(
+        &self,
+        note_ids: &[NoteId],
+        prerequisites: &[NoteId],
+    ) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut pending = prerequisites.to_vec();
+        while let Some(nid) = pending.pop() {
+            require!(
+                !note_ids.contains(&nid),
+                "prerequisites can not depend on each other in a cycle"
+            );
+            if seen.insert(nid) {
+                pending.extend(self.note_prerequisites(nid)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::*;
+    use crate::search::SearchNode;
+    use crate::search::SortMode;
+    use crate::search::StateKind;
+    use crate::tests::*;
+
+    #[test]
+    fn blocked_until_prerequisite_learnt() -> Result<()> {
+        let mut col = Collection::new();
+        // the dependent note comes first in the new queue
+        let advanced = NoteAdder::basic(&mut col).add(&mut col);
+        let basic = NoteAdder::basic(&mut col).add(&mut col);
+        assert_eq!(
+            col.set_note_prerequisites(&[advanced.id], &[basic.id])?
+                .output,
+            1
+        );
+        let blocked = SearchNode::State(StateKind::Blocked);
+        assert_eq!(
+            col.search_cards(blocked.clone(), SortMode::NoOrder)?.len(),
+            1
+        );
+
+        // the blocked card is skipped without using up the new card limit
+        col.update_default_deck_config(|config| config.new_per_day = 1);
+        let queued = col.get_queued_cards(5, false)?;
+        assert_eq!(queued.new_count, 1);
+        assert_eq!(queued.cards[0].card.note_id, basic.id);
+
+        col.answer_easy();
+        assert_eq!(col.search_cards(blocked, SortMode::NoOrder)?.len(), 0);
+
+        // a note can't be its own prerequisite
+        assert!(col
+            .set_note_prerequisites(&[basic.id], &[basic.id])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn cycles_are_rejected() -> Result<()> {
+        let mut col = Collection::new();
+        let a = NoteAdder::basic(&mut col).add(&mut col);
+        let b = NoteAdder::basic(&mut col).add(&mut col);
+        let c = NoteAdder::basic(&mut col).add(&mut col);
+        col.set_note_prerequisites(&[b.id], &[a.id])?;
+        col.set_note_prerequisites(&[c.id], &[b.id])?;
+
+        assert!(col.set_note_prerequisites(&[a.id], &[c.id]).is_err());
+        assert!(col.note_prerequisites(a.id)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn new_cards_inherit_prerequisites_of_their_note() -> Result<()> {
+        let mut col = Collection::new();
+        let basic = NoteAdder::basic(&mut col).add(&mut col);
+        let mut cloze = NoteAdder::cloze(&mut col)
+            .fields(&["{{c1::one}}", ""])
+            .add(&mut col);
+        col.set_note_prerequisites(&[cloze.id], &[basic.id])?;
+
+        cloze.fields_mut()[0] = "{{c1::one}} {{c2::two}}".into();
+        col.update_note(&mut cloze)?;
+        let cards = col.storage.all_cards_of_note(cloze.id)?;
+        assert_eq!(cards.len(), 2);
+        assert!(cards.iter().all(|card| card.prerequisites == [basic.id]));
+        Ok(())
+    }
+}
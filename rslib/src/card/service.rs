@@ -73,6 +73,28 @@ impl crate::services::CardsService for Collection {
         self.set_card_flag(&to_card_ids(input.card_ids), input.flag)
             .map(Into::into)
     }
+
+    fn set_interval_limits(
+        &mut self,
+        input: anki_proto::cards::SetIntervalLimitsRequest,
+    ) -> error::Result<anki_proto::collection::OpChangesWithCount> {
+        self.set_card_interval_limits(&to_card_ids(input.card_ids), input.minimum, input.maximum)
+            .map(Into::into)
+    }
+
+    fn set_note_prerequisites(
+        &mut self,
+        input: anki_proto::cards::SetNotePrerequisitesRequest,
+    ) -> error::Result<anki_proto::collection::OpChangesWithCount> {
+        let note_ids: Vec<_> = input.note_ids.into_iter().map(NoteId).collect();
+        let prerequisites: Vec<_> = input
+            .prerequisite_note_ids
+            .into_iter()
+            .map(NoteId)
+            .collect();
+        self.set_note_prerequisites(&note_ids, &prerequisites)
+            .map(Into::into)
+    }
 }
 
 impl TryFrom<anki_proto::cards::Card> for Card {
@@ -103,6 +125,10 @@ impl TryFrom<anki_proto::cards::Card> for Card {
             memory_state: c.memory_state.map(Into::into),
             desired_retention: c.desired_retention,
             custom_data: c.custom_data,
+            prerequisites: c.prerequisite_note_ids.into_iter().map(NoteId).collect(),
+            memory_state_hash: c.memory_state_hash,
+            minimum_interval: c.minimum_interval,
+            maximum_interval: c.maximum_interval,
         })
     }
 }
@@ -131,6 +157,10 @@ impl From<Card> for anki_proto::cards::Card {
             memory_state: c.memory_state.map(Into::into),
             desired_retention: c.desired_retention,
             custom_data: c.custom_data,
+            prerequisite_note_ids: c.prerequisites.into_iter().map(|nid| nid.0).collect(),
+            memory_state_hash: c.memory_state_hash,
+            minimum_interval: c.minimum_interval,
+            maximum_interval: c.maximum_interval,
         }
     }
 }
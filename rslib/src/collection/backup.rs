@@ -11,24 +11,27 @@ use std::thread;
 use std::thread::JoinHandle;
 use std::time::SystemTime;
 
-use anki_io::read_locked_db_file;
+use anki_io::new_tempfile_in;
 use anki_proto::config::preferences::BackupLimits;
 use chrono::prelude::*;
 use itertools::Itertools;
+use tempfile::NamedTempFile;
 use tracing::error;
 
-use crate::import_export::package::export_colpkg_from_data;
+use crate::import_export::package::export_colpkg_from_file;
 use crate::prelude::*;
 
 const BACKUP_FORMAT_STRING: &str = "backup-%Y-%m-%d-%H.%M.%S.colpkg";
 
 impl Collection {
-    /// Create a backup if enough time has elapsed, or if forced.
+    /// Create a backup if enough time has elapsed, or if forced. If a
+    /// non-empty password is provided, the backup is encrypted with it.
     /// Returns a handle that can be awaited if a backup was created.
     pub fn maybe_backup(
         &mut self,
         backup_folder: impl AsRef<Path> + Send + 'static,
         force: bool,
+        password: Option<String>,
     ) -> Result<Option<JoinHandle<Result<()>>>> {
         if !self.changed_since_last_backup()? {
             return Ok(None);
@@ -39,10 +42,12 @@ impl Collection {
         } else {
             let tr = self.tr.clone();
             self.storage.checkpoint()?;
-            let col_data = read_locked_db_file(&self.col_path)?;
+            // copied on disk, as large collections may not fit into memory
+            let col_copy = new_tempfile_in(backup_folder.as_ref())?;
+            self.storage.copy_to(col_copy.path())?;
             self.update_last_backup_timestamp()?;
             Ok(Some(thread::spawn(move || {
-                backup_inner(&col_data, &backup_folder, limits, &tr)
+                backup_inner(col_copy, &backup_folder, limits, password.as_deref(), &tr)
             })))
         }
     }
@@ -81,20 +86,27 @@ fn has_recent_backup(backup_folder: &Path, recent_mins: u32) -> Result<bool> {
         .any(|duration| duration.as_secs() < recent_secs))
 }
 
+/// The copy of the collection is removed when done.
 fn backup_inner<P: AsRef<Path>>(
-    col_data: &[u8],
+    col_copy: NamedTempFile,
     backup_folder: P,
     limits: BackupLimits,
+    password: Option<&str>,
     tr: &I18n,
 ) -> Result<()> {
-    write_backup(col_data, backup_folder.as_ref(), tr)?;
+    write_backup(col_copy.path(), backup_folder.as_ref(), password, tr)?;
     thin_backups(backup_folder, limits)
 }
 
-fn write_backup<S: AsRef<OsStr>>(col_data: &[u8], backup_folder: S, tr: &I18n) -> Result<()> {
+fn write_backup<S: AsRef<OsStr>>(
+    col_path: &Path,
+    backup_folder: S,
+    password: Option<&str>,
+    tr: &I18n,
+) -> Result<()> {
     let out_path =
         Path::new(&backup_folder).join(format!("{}", Local::now().format(BACKUP_FORMAT_STRING)));
-    export_colpkg_from_data(out_path, col_data, tr)
+    export_colpkg_from_file(out_path, col_path, password, tr)
 }
 
 fn thin_backups<P: AsRef<Path>>(backup_folder: P, limits: BackupLimits) -> Result<()> {
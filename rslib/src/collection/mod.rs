@@ -18,12 +18,14 @@ use anki_i18n::I18n;
 use anki_io::create_dir_all;
 
 use crate::browser_table;
+use crate::deckconfig::DeckConfigId;
 use crate::decks::Deck;
 use crate::decks::DeckId;
 use crate::error::Result;
 use crate::notetype::Notetype;
 use crate::notetype::NotetypeId;
 use crate::progress::ProgressState;
+use crate::scheduler::fsrs::retention_overrides::CachedRetentionOverrides;
 use crate::scheduler::queue::CardQueues;
 use crate::scheduler::SchedulerInfo;
 use crate::storage::SchemaVersion;
@@ -130,6 +132,8 @@ pub struct CollectionState {
     pub(crate) deck_cache: HashMap<DeckId, Arc<Deck>>,
     pub(crate) scheduler_info: Option<SchedulerInfo>,
     pub(crate) card_queues: Option<CardQueues>,
+    /// The cards matched by the desired retention overrides of each preset.
+    pub(crate) retention_overrides: HashMap<DeckConfigId, CachedRetentionOverrides>,
     pub(crate) active_browser_columns: Option<Arc<Vec<browser_table::Column>>>,
     /// True if legacy Python code has executed SQL that has modified the
     /// database, requiring modification time to be bumped.
@@ -71,4 +71,26 @@ impl Collection {
     {
         self.transact_inner(None, func).map(|out| out.output)
     }
+
+    /// Execute the provided closure in a transaction, then roll back any
+    /// changes it made, so its effect can be previewed. The undo queue is left
+    /// untouched.
+    pub(crate) fn transact_dry_run<F, R>(&mut self, func: F) -> Result<R>
+    where
+        F: FnOnce(&mut Collection) -> Result<R>,
+    {
+        let autocommit = self.storage.db.is_autocommit();
+        self.storage.begin_rust_trx()?;
+        let output = func(self);
+        if autocommit {
+            self.storage.rollback_trx()?;
+        } else {
+            self.storage.rollback_rust_trx()?;
+        }
+        // cached objects may reflect the discarded changes
+        self.state.deck_cache.clear();
+        self.state.notetype_cache.clear();
+        self.clear_study_queues();
+        output
+    }
 }
@@ -7,6 +7,7 @@ pub(crate) mod undo;
 mod update;
 
 pub use anki_proto::deck_config::deck_config::config::AnswerAction;
+pub use anki_proto::deck_config::deck_config::config::DesiredRetentionOverride;
 pub use anki_proto::deck_config::deck_config::config::LeechAction;
 pub use anki_proto::deck_config::deck_config::config::NewCardGatherPriority;
 pub use anki_proto::deck_config::deck_config::config::NewCardInsertOrder;
@@ -14,6 +15,8 @@ pub use anki_proto::deck_config::deck_config::config::NewCardSortOrder;
 pub use anki_proto::deck_config::deck_config::config::ReviewCardOrder;
 pub use anki_proto::deck_config::deck_config::config::ReviewMix;
 pub use anki_proto::deck_config::deck_config::Config as DeckConfigInner;
+pub use anki_proto::scheduler::FsrsWeightsGrouping;
+pub use anki_proto::scheduler::FsrsWeightsOverride;
 pub use schema11::DeckConfSchema11;
 pub use schema11::NewCardOrderSchema11;
 pub use update::UpdateDeckConfigsRequest;
@@ -60,6 +63,11 @@ const DEFAULT_DECK_CONFIG_INNER: DeckConfigInner = DeckConfigInner {
     interday_learning_mix: ReviewMix::MixWithReviews as i32,
     leech_action: LeechAction::TagOnly as i32,
     leech_threshold: 8,
+    leech_repeat_interval: 0,
+    leech_deck: String::new(),
+    leech_reset_to_new: false,
+    leech_flag: 0,
+    leech_tag_lapses: false,
     disable_autoplay: false,
     cap_answer_time_to_secs: 60,
     show_timer: false,
@@ -72,8 +80,14 @@ const DEFAULT_DECK_CONFIG_INNER: DeckConfigInner = DeckConfigInner {
     bury_new: false,
     bury_reviews: false,
     bury_interday_learning: false,
+    sibling_dispersal_days: 0,
+    adaptive_learning_steps: false,
+    interleave_review_decks: false,
     fsrs_weights: vec![],
     desired_retention: 0.9,
+    desired_retention_overrides: vec![],
+    fsrs_weights_grouping: FsrsWeightsGrouping::Preset as i32,
+    fsrs_weights_overrides: vec![],
     other: Vec::new(),
     sm2_retention: 0.9,
     weight_search: String::new(),
@@ -269,6 +283,13 @@ pub(crate) fn ensure_deck_config_values_valid(config: &mut DeckConfigInner) {
         1,
         9999,
     );
+    ensure_u32_valid(
+        &mut config.leech_repeat_interval,
+        default.leech_repeat_interval,
+        0,
+        9999,
+    );
+    ensure_u32_valid(&mut config.leech_flag, default.leech_flag, 0, 7);
     ensure_u32_valid(
         &mut config.cap_answer_time_to_secs,
         default.cap_answer_time_to_secs,
@@ -281,6 +302,14 @@ pub(crate) fn ensure_deck_config_values_valid(config: &mut DeckConfigInner) {
         0.7,
         0.99,
     );
+    for entry in &mut config.desired_retention_overrides {
+        ensure_f32_valid(
+            &mut entry.desired_retention,
+            config.desired_retention,
+            0.7,
+            0.99,
+        );
+    }
     ensure_f32_valid(&mut config.sm2_retention, default.sm2_retention, 0.7, 0.97)
 }
 
@@ -18,6 +18,8 @@ use serde_tuple::Serialize_tuple;
 use super::DeckConfig;
 use super::DeckConfigId;
 use super::DeckConfigInner;
+use super::DesiredRetentionOverride;
+use super::FsrsWeightsOverride;
 use super::NewCardInsertOrder;
 use super::INITIAL_EASE_FACTOR_THOUSANDS;
 use crate::serde::default_on_invalid;
@@ -68,12 +70,30 @@ pub struct DeckConfSchema11 {
     new_gather_priority: i32,
     #[serde(default)]
     bury_interday_learning: bool,
+    #[serde(default)]
+    sibling_dispersal_days: u32,
+    #[serde(default)]
+    leech_repeat_interval: u32,
+    #[serde(default)]
+    leech_deck: String,
+    #[serde(default)]
+    leech_reset_to_new: bool,
+    #[serde(default)]
+    leech_flag: u32,
+    #[serde(default)]
+    leech_tag_lapses: bool,
+    #[serde(default)]
+    adaptive_learning_steps: bool,
+    #[serde(default)]
+    interleave_review_decks: bool,
 
     #[serde(default)]
     fsrs_weights: Vec<f32>,
     #[serde(default)]
     desired_retention: f32,
     #[serde(default)]
+    desired_retention_overrides: Vec<DesiredRetentionOverrideSchema11>,
+    #[serde(default)]
     stop_timer_on_answer: bool,
     #[serde(default)]
     seconds_to_show_question: f32,
@@ -87,6 +107,10 @@ pub struct DeckConfSchema11 {
     sm2_retention: f32,
     #[serde(default)]
     weight_search: String,
+    #[serde(default)]
+    fsrs_weights_grouping: i32,
+    #[serde(default)]
+    fsrs_weights_overrides: Vec<FsrsWeightsOverrideSchema11>,
 
     #[serde(flatten)]
     other: HashMap<String, Value>,
@@ -104,6 +128,64 @@ pub enum AnswerAction {
     ShowReminder = 4,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DesiredRetentionOverrideSchema11 {
+    #[serde(default)]
+    search: String,
+    #[serde(default)]
+    desired_retention: f32,
+}
+
+impl From<DesiredRetentionOverrideSchema11> for DesiredRetentionOverride {
+    fn from(o: DesiredRetentionOverrideSchema11) -> Self {
+        DesiredRetentionOverride {
+            search: o.search,
+            desired_retention: o.desired_retention,
+        }
+    }
+}
+
+impl From<DesiredRetentionOverride> for DesiredRetentionOverrideSchema11 {
+    fn from(o: DesiredRetentionOverride) -> Self {
+        DesiredRetentionOverrideSchema11 {
+            search: o.search,
+            desired_retention: o.desired_retention,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FsrsWeightsOverrideSchema11 {
+    #[serde(default)]
+    notetype_id: i64,
+    #[serde(default)]
+    template_idx: Option<u32>,
+    #[serde(default)]
+    weights: Vec<f32>,
+}
+
+impl From<FsrsWeightsOverrideSchema11> for FsrsWeightsOverride {
+    fn from(o: FsrsWeightsOverrideSchema11) -> Self {
+        FsrsWeightsOverride {
+            notetype_id: o.notetype_id,
+            template_idx: o.template_idx,
+            weights: o.weights,
+        }
+    }
+}
+
+impl From<FsrsWeightsOverride> for FsrsWeightsOverrideSchema11 {
+    fn from(o: FsrsWeightsOverride) -> Self {
+        FsrsWeightsOverrideSchema11 {
+            notetype_id: o.notetype_id,
+            template_idx: o.template_idx,
+            weights: o.weights,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct NewConfSchema11 {
@@ -290,10 +372,21 @@ impl Default for DeckConfSchema11 {
             new_sort_order: 0,
             new_gather_priority: 0,
             bury_interday_learning: false,
+            sibling_dispersal_days: 0,
+            leech_repeat_interval: 0,
+            leech_deck: "".to_string(),
+            leech_reset_to_new: false,
+            leech_flag: 0,
+            leech_tag_lapses: false,
+            adaptive_learning_steps: false,
+            interleave_review_decks: false,
             fsrs_weights: vec![],
             desired_retention: 0.9,
+            desired_retention_overrides: vec![],
             sm2_retention: 0.9,
             weight_search: "".to_string(),
+            fsrs_weights_grouping: 0,
+            fsrs_weights_overrides: vec![],
         }
     }
 }
@@ -367,10 +460,29 @@ impl From<DeckConfSchema11> for DeckConfig {
                 bury_new: c.new.bury,
                 bury_reviews: c.rev.bury,
                 bury_interday_learning: c.bury_interday_learning,
+                sibling_dispersal_days: c.sibling_dispersal_days,
+                leech_repeat_interval: c.leech_repeat_interval,
+                leech_deck: c.leech_deck,
+                leech_reset_to_new: c.leech_reset_to_new,
+                leech_flag: c.leech_flag,
+                leech_tag_lapses: c.leech_tag_lapses,
+                adaptive_learning_steps: c.adaptive_learning_steps,
+                interleave_review_decks: c.interleave_review_decks,
                 fsrs_weights: c.fsrs_weights,
                 desired_retention: c.desired_retention,
+                desired_retention_overrides: c
+                    .desired_retention_overrides
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
                 sm2_retention: c.sm2_retention,
                 weight_search: c.weight_search,
+                fsrs_weights_grouping: c.fsrs_weights_grouping,
+                fsrs_weights_overrides: c
+                    .fsrs_weights_overrides
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
                 other: other_bytes,
             },
         }
@@ -473,10 +585,29 @@ impl From<DeckConfig> for DeckConfSchema11 {
             new_sort_order: i.new_card_sort_order,
             new_gather_priority: i.new_card_gather_priority,
             bury_interday_learning: i.bury_interday_learning,
+            sibling_dispersal_days: i.sibling_dispersal_days,
+            leech_repeat_interval: i.leech_repeat_interval,
+            leech_deck: i.leech_deck,
+            leech_reset_to_new: i.leech_reset_to_new,
+            leech_flag: i.leech_flag,
+            leech_tag_lapses: i.leech_tag_lapses,
+            adaptive_learning_steps: i.adaptive_learning_steps,
+            interleave_review_decks: i.interleave_review_decks,
             fsrs_weights: i.fsrs_weights,
             desired_retention: i.desired_retention,
+            desired_retention_overrides: i
+                .desired_retention_overrides
+                .into_iter()
+                .map(Into::into)
+                .collect(),
             sm2_retention: i.sm2_retention,
             weight_search: i.weight_search,
+            fsrs_weights_grouping: i.fsrs_weights_grouping,
+            fsrs_weights_overrides: i
+                .fsrs_weights_overrides
+                .into_iter()
+                .map(Into::into)
+                .collect(),
         }
     }
 }
@@ -500,6 +631,7 @@ static RESERVED_DECKCONF_KEYS: Set<&'static str> = phf_set! {
     "newGatherPriority",
     "fsrsWeights",
     "desiredRetention",
+    "desiredRetentionOverrides",
     "stopTimerOnAnswer",
     "secondsToShowQuestion",
     "secondsToShowAnswer",
@@ -507,6 +639,16 @@ static RESERVED_DECKCONF_KEYS: Set<&'static str> = phf_set! {
     "waitForAudio",
     "sm2Retention",
     "weightSearch",
+    "siblingDispersalDays",
+    "leechRepeatInterval",
+    "leechDeck",
+    "leechResetToNew",
+    "leechFlag",
+    "leechTagLapses",
+    "fsrsWeightsGrouping",
+    "fsrsWeightsOverrides",
+    "adaptiveLearningSteps",
+    "interleaveReviewDecks",
 };
 
 static RESERVED_DECKCONF_NEW_KEYS: Set<&'static str> = phf_set! {
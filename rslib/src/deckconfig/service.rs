@@ -78,6 +78,13 @@ impl crate::services::DeckConfigService for Collection {
     ) -> Result<anki_proto::collection::OpChanges> {
         self.update_deck_configs(input.into()).map(Into::into)
     }
+
+    fn preview_update_deck_configs(
+        &mut self,
+        input: anki_proto::deck_config::UpdateDeckConfigsRequest,
+    ) -> Result<anki_proto::deck_config::UpdateDeckConfigsPreview> {
+        self.preview_update_deck_configs(input.into())
+    }
 }
 
 impl From<DeckConfig> for anki_proto::deck_config::DeckConfig {
@@ -101,6 +108,7 @@ impl From<anki_proto::deck_config::UpdateDeckConfigsRequest> for UpdateDeckConfi
             removed_config_ids: c.removed_config_ids.into_iter().map(Into::into).collect(),
             mode,
             card_state_customizer: c.card_state_customizer,
+            card_state_script: c.card_state_script,
             limits: c.limits.unwrap_or_default(),
             new_cards_ignore_review_limit: c.new_cards_ignore_review_limit,
             apply_all_parent_limits: c.apply_all_parent_limits,
@@ -10,18 +10,28 @@ use std::iter;
 use anki_proto::deck_config::deck_configs_for_update::current_deck::Limits;
 use anki_proto::deck_config::deck_configs_for_update::ConfigWithExtra;
 use anki_proto::deck_config::deck_configs_for_update::CurrentDeck;
+use anki_proto::deck_config::update_deck_configs_preview::Deck as DeckPreview;
 use anki_proto::deck_config::UpdateDeckConfigsMode;
+use anki_proto::deck_config::UpdateDeckConfigsPreview;
 use anki_proto::decks::deck::normal::DayLimit;
 use fsrs::DEFAULT_WEIGHTS;
 
 use crate::config::StringKey;
 use crate::decks::NormalDeck;
 use crate::prelude::*;
+use crate::scheduler::customizer::CardStateScript;
 use crate::scheduler::fsrs::memory_state::UpdateMemoryStateRequest;
+use crate::scheduler::fsrs::retention_overrides::validate_desired_retention_overrides;
+use crate::scheduler::fsrs::simulator::WorkloadSimulationConfig;
+use crate::scheduler::fsrs::weights_overrides::validate_weights_overrides;
 use crate::search::JoinSearches;
 use crate::search::SearchNode;
+use crate::search::StateKind;
 use crate::storage::comma_separated_ids;
 
+/// The number of days of expected workload included in a preview.
+const PREVIEW_WORKLOAD_DAYS: u32 = 30;
+
 #[derive(Debug, Clone)]
 pub struct UpdateDeckConfigsRequest {
     pub target_deck_id: DeckId,
@@ -30,6 +40,7 @@ pub struct UpdateDeckConfigsRequest {
     pub removed_config_ids: Vec<DeckConfigId>,
     pub mode: UpdateDeckConfigsMode,
     pub card_state_customizer: String,
+    pub card_state_script: String,
     pub limits: Limits,
     pub new_cards_ignore_review_limit: bool,
     pub apply_all_parent_limits: bool,
@@ -54,6 +65,7 @@ impl Collection {
                 .get_collection_timestamps()?
                 .schema_changed_since_sync(),
             card_state_customizer: self.get_config_string(StringKey::CardStateCustomizer),
+            card_state_script: self.get_config_string(StringKey::CardStateScript),
             new_cards_ignore_review_limit: self.get_config_bool(BoolKey::NewCardsIgnoreReviewLimit),
             apply_all_parent_limits: self.get_config_bool(BoolKey::ApplyAllParentLimits),
             fsrs: self.get_config_bool(BoolKey::Fsrs),
@@ -66,6 +78,15 @@ impl Collection {
             col.update_deck_configs_inner(input)
         })
     }
+
+    /// Report how applying the provided changes would affect each deck,
+    /// without modifying the collection.
+    pub fn preview_update_deck_configs(
+        &mut self,
+        input: UpdateDeckConfigsRequest,
+    ) -> Result<UpdateDeckConfigsPreview> {
+        self.transact_dry_run(|col| col.preview_update_deck_configs_inner(input))
+    }
 }
 
 impl Collection {
@@ -129,6 +150,9 @@ impl Collection {
 
     fn update_deck_configs_inner(&mut self, mut req: UpdateDeckConfigsRequest) -> Result<()> {
         require!(!req.configs.is_empty(), "config not provided");
+        if !req.card_state_script.trim().is_empty() {
+            CardStateScript::parse(&req.card_state_script)?;
+        }
         let configs_before_update = self.storage.get_deck_config_map()?;
         let mut configs_after_update = configs_before_update.clone();
 
@@ -148,6 +172,8 @@ impl Collection {
             if weight_len != 0 && weight_len != 17 {
                 return Err(AnkiError::FsrsWeightsInvalid);
             }
+            validate_desired_retention_overrides(&conf.inner.desired_retention_overrides)?;
+            validate_weights_overrides(&conf.inner.fsrs_weights_overrides)?;
             self.add_or_update_deck_config(conf)?;
             configs_after_update.insert(conf.id, conf.clone());
         }
@@ -188,8 +214,14 @@ impl Collection {
                 let previous_order = previous_config
                     .map(|c| c.inner.new_card_insert_order())
                     .unwrap_or_default();
-                let previous_weights = previous_config.map(|c| &c.inner.fsrs_weights);
-                let previous_retention = previous_config.map(|c| c.inner.desired_retention);
+                let previous_weights = previous_config
+                    .map(|c| (&c.inner.fsrs_weights, &c.inner.fsrs_weights_overrides));
+                let previous_retention = previous_config.map(|c| {
+                    (
+                        c.inner.desired_retention,
+                        &c.inner.desired_retention_overrides,
+                    )
+                });
 
                 // if a selected (sub)deck, or its old config was removed, update deck to point
                 // to new config
@@ -215,8 +247,14 @@ impl Collection {
                 }
 
                 // if weights differ, memory state needs to be recomputed
-                let current_weights = current_config.map(|c| &c.inner.fsrs_weights);
-                let current_retention = current_config.map(|c| c.inner.desired_retention);
+                let current_weights = current_config
+                    .map(|c| (&c.inner.fsrs_weights, &c.inner.fsrs_weights_overrides));
+                let current_retention = current_config.map(|c| {
+                    (
+                        c.inner.desired_retention,
+                        &c.inner.desired_retention_overrides,
+                    )
+                });
                 if fsrs_toggled
                     || previous_weights != current_weights
                     || previous_retention != current_retention
@@ -238,13 +276,10 @@ impl Collection {
                     .map(|(conf_id, search)| {
                         let weights = configs_after_update.get(&conf_id).and_then(|c| {
                             if req.fsrs {
-                                Some(UpdateMemoryStateRequest {
-                                    weights: c.inner.fsrs_weights.clone(),
-                                    desired_retention: c.inner.desired_retention,
-                                    max_interval: c.inner.maximum_review_interval,
-                                    reschedule: req.fsrs_reschedule,
-                                    sm2_retention: c.inner.sm2_retention,
-                                })
+                                Some(UpdateMemoryStateRequest::from_config(
+                                    c,
+                                    req.fsrs_reschedule,
+                                ))
                             } else {
                                 None
                             }
@@ -259,6 +294,7 @@ impl Collection {
         }
 
         self.set_config_string_inner(StringKey::CardStateCustomizer, &req.card_state_customizer)?;
+        self.set_config_string_inner(StringKey::CardStateScript, &req.card_state_script)?;
         self.set_config_bool_inner(
             BoolKey::NewCardsIgnoreReviewLimit,
             req.new_cards_ignore_review_limit,
@@ -268,6 +304,75 @@ impl Collection {
         Ok(())
     }
 
+    /// Must be called inside a transaction that is rolled back afterwards.
+    fn preview_update_deck_configs_inner(
+        &mut self,
+        req: UpdateDeckConfigsRequest,
+    ) -> Result<UpdateDeckConfigsPreview> {
+        let configs_before = self.storage.get_deck_config_map()?;
+        let config_ids_before = self.deck_config_ids_by_deck()?;
+        let due_before: HashMap<CardId, i32> = self
+            .all_cards_for_search(SearchNode::State(StateKind::Review))?
+            .into_iter()
+            .map(|card| (card.id, card.original_or_current_due()))
+            .collect();
+
+        self.update_deck_configs_inner(req)?;
+
+        let configs_after = self.storage.get_deck_config_map()?;
+        let mut due_shifts: HashMap<DeckId, HashMap<i32, u32>> = HashMap::new();
+        for card in self.all_cards_for_search(SearchNode::State(StateKind::Review))? {
+            if let Some(before) = due_before.get(&card.id) {
+                let shift = card.original_or_current_due() - before;
+                if shift != 0 {
+                    *due_shifts
+                        .entry(card.original_or_current_deck_id())
+                        .or_default()
+                        .entry(shift)
+                        .or_default() += 1;
+                }
+            }
+        }
+
+        let mut decks = vec![];
+        for deck in self.storage.get_all_decks()? {
+            let Some(config_id) = deck.config_id() else {
+                continue;
+            };
+            let Some(config) = configs_after.get(&config_id) else {
+                continue;
+            };
+            let config_changed = config_ids_before.get(&deck.id) != Some(&config_id)
+                || configs_before.get(&config_id).map(|c| &c.inner) != Some(&config.inner);
+            let due_shifts = due_shifts.remove(&deck.id).unwrap_or_default();
+            if !config_changed && due_shifts.is_empty() {
+                continue;
+            }
+            let workload = self.simulate_workload_for_search(
+                SearchNode::from_deck_id(deck.id, false),
+                &WorkloadSimulationConfig::from_deck_config(config, PREVIEW_WORKLOAD_DAYS),
+            )?;
+            decks.push(DeckPreview {
+                deck_id: deck.id.0,
+                name: deck.human_name(),
+                changed_cards: due_shifts.values().sum(),
+                due_shifts,
+                workload,
+            });
+        }
+
+        Ok(UpdateDeckConfigsPreview { decks })
+    }
+
+    fn deck_config_ids_by_deck(&self) -> Result<HashMap<DeckId, DeckConfigId>> {
+        Ok(self
+            .storage
+            .get_all_decks()?
+            .into_iter()
+            .filter_map(|deck| deck.config_id().map(|config_id| (deck.id, config_id)))
+            .collect())
+    }
+
     /// Adjust the remaining steps of cards in the given deck according to the
     /// config change.
     pub(crate) fn adjust_remaining_steps_in_deck(
@@ -326,11 +431,13 @@ impl Collection {
             } else {
                 config.inner.weight_search.clone()
             };
-            match self.compute_weights(&search, idx as u32 + 1, config_len) {
+            let grouping = config.inner.fsrs_weights_grouping();
+            match self.compute_weights(&search, grouping, idx as u32 + 1, config_len) {
                 Ok(weights) => {
                     if weights.fsrs_items >= 1000 {
                         println!("{}: {:?}", config.name, weights.weights);
                         config.inner.fsrs_weights = weights.weights;
+                        config.inner.fsrs_weights_overrides = weights.weights_overrides;
                     }
                 }
                 Err(AnkiError::Interrupted) => return Err(AnkiError::Interrupted),
@@ -431,6 +538,7 @@ mod test {
             removed_config_ids: vec![],
             mode: UpdateDeckConfigsMode::Normal,
             card_state_customizer: "".to_string(),
+            card_state_script: "".to_string(),
             limits: Limits::default(),
             new_cards_ignore_review_limit: false,
             apply_all_parent_limits: false,
@@ -484,6 +592,47 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn previewing_does_not_modify_collection() -> Result<()> {
+        let mut col = Collection::new();
+        CardAdder::new().due_dates(["5"]).add(&mut col);
+        let output = col.get_deck_configs_for_update(DeckId(1))?;
+        let mut input = UpdateDeckConfigsRequest {
+            target_deck_id: DeckId(1),
+            configs: output
+                .all_config
+                .into_iter()
+                .map(|c| c.config.unwrap().into())
+                .collect(),
+            removed_config_ids: vec![],
+            mode: UpdateDeckConfigsMode::Normal,
+            card_state_customizer: "".to_string(),
+            card_state_script: "".to_string(),
+            limits: Limits::default(),
+            new_cards_ignore_review_limit: false,
+            apply_all_parent_limits: false,
+            fsrs: false,
+            fsrs_reschedule: false,
+        };
+        input.configs[0].inner.new_per_day += 1;
+        let original_config = col.get_deck_config(DeckConfigId(1), false)?.unwrap();
+
+        let preview = col.preview_update_deck_configs(input)?;
+        assert_eq!(preview.decks.len(), 1);
+        assert_eq!(preview.decks[0].deck_id, 1);
+        assert_eq!(preview.decks[0].changed_cards, 0);
+        assert_eq!(
+            preview.decks[0].workload.len(),
+            PREVIEW_WORKLOAD_DAYS as usize
+        );
+        assert_eq!(
+            col.get_deck_config(DeckConfigId(1), false)?.unwrap(),
+            original_config
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn should_increase_remaining_learning_steps_if_unpassed_learning_step_added() {
         let mut col = open_test_collection_with_learning_card();
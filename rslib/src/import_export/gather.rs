@@ -32,16 +32,19 @@ pub(super) struct ExchangeData {
 }
 
 impl ExchangeData {
+    /// If changed_since is set, only notes modified since then, or with cards
+    /// modified since then, are gathered.
     pub(super) fn gather_data(
         &mut self,
         col: &mut Collection,
         search: impl TryIntoSearch,
         with_scheduling: bool,
         with_deck_configs: bool,
+        changed_since: Option<TimestampSecs>,
     ) -> Result<()> {
         self.days_elapsed = col.timing_today()?.days_elapsed;
         self.creation_utc_offset = col.get_creation_utc_offset();
-        let (notes, guard) = col.gather_notes(search)?;
+        let (notes, guard) = col.gather_notes(search, changed_since)?;
         self.notes = notes;
         let (cards, guard) = guard.col.gather_cards()?;
         self.cards = cards;
@@ -67,25 +70,118 @@ impl ExchangeData {
         self.check_ids()
     }
 
-    pub(super) fn gather_media_names(
+    /// Like [ExchangeData::gather_data], but notes, cards and revlog entries
+    /// are written to `target` one at a time instead of being held in memory.
+    /// Media names are gathered if `with_media` is set. Returns the number of
+    /// notes written.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn stream_data(
         &mut self,
+        col: &mut Collection,
+        target: &mut Collection,
+        search: impl TryIntoSearch,
+        with_scheduling: bool,
+        with_deck_configs: bool,
+        with_media: bool,
+        changed_since: Option<TimestampSecs>,
         progress: &mut ThrottlingProgressHandler<ExportProgress>,
-    ) -> Result<()> {
+    ) -> Result<usize> {
+        self.days_elapsed = col.timing_today()?.days_elapsed;
+        self.creation_utc_offset = col.get_creation_utc_offset();
+        let guard = col.search_notes_into_table(search)?;
+        if let Some(since) = changed_since {
+            guard
+                .col
+                .storage
+                .retain_searched_notes_changed_since(since)?;
+        }
+        let guard = guard.col.search_cards_of_notes_into_table()?;
+        let col = &mut *guard.col;
+        self.decks = col.gather_decks(with_scheduling, !with_scheduling)?;
+        self.notetypes = col.gather_notetypes()?;
+
+        let allow_filtered = self.searched_cards_enable_filtered_decks(col)?;
+
+        if with_deck_configs {
+            self.deck_configs = col.gather_deck_configs(&self.decks)?;
+        }
+        self.reset_decks(!with_deck_configs, !with_scheduling, allow_filtered);
+
+        target.transact_no_undo(|target| {
+            let note_count =
+                self.stream_notes(col, target, !with_scheduling, with_media, progress)?;
+            self.stream_cards(col, target, with_scheduling, allow_filtered)?;
+            if with_scheduling {
+                stream_revlog(col, target)?;
+            }
+            Ok(note_count)
+        })
+    }
+
+    fn stream_notes(
+        &mut self,
+        col: &Collection,
+        target: &Collection,
+        remove_system_tags: bool,
+        with_media: bool,
+        progress: &mut ThrottlingProgressHandler<ExportProgress>,
+    ) -> Result<usize> {
+        let max_id = max_valid_id();
+        let svg_getter = svg_getter(&self.notetypes);
+        let media_filenames = &mut self.media_filenames;
         let mut inserter = |name: String| {
             if filename_is_safe(&name) {
-                self.media_filenames.insert(name);
+                media_filenames.insert(name);
             }
         };
-        let mut progress = progress.incrementor(ExportProgress::Notes);
-        let svg_getter = svg_getter(&self.notetypes);
-        for note in self.notes.iter() {
-            progress.increment()?;
-            gather_media_names_from_note(note, &mut inserter, &svg_getter);
-        }
-        for notetype in self.notetypes.iter() {
-            notetype.gather_media_names(&mut inserter);
+        let mut incrementor = progress.incrementor(ExportProgress::Notes);
+        col.storage.for_each_note_in_search(|mut note| {
+            incrementor.increment()?;
+            check_id(note.id.0, max_id)?;
+            if remove_system_tags {
+                remove_system_tags_from_note(&mut note);
+            }
+            if with_media {
+                gather_media_names_from_note(&note, &mut inserter, &svg_getter);
+            }
+            target.storage.add_or_update_note(&note)
+        })?;
+        if with_media {
+            for notetype in self.notetypes.iter() {
+                notetype.gather_media_names(&mut inserter);
+            }
         }
-        Ok(())
+        Ok(incrementor.count())
+    }
+
+    fn stream_cards(
+        &self,
+        col: &Collection,
+        target: &Collection,
+        with_scheduling: bool,
+        allow_filtered: bool,
+    ) -> Result<()> {
+        let max_id = max_valid_id();
+        let mut position = col.get_next_card_position();
+        col.storage.for_each_card_in_search(|mut card| {
+            check_id(card.id.0, max_id)?;
+            if !with_scheduling {
+                reset_card(&mut card, &mut position);
+            } else if !allow_filtered {
+                restore_card_from_filtered_deck(&mut card);
+            }
+            target.storage.add_or_update_card(&card)
+        })
+    }
+
+    /// Streaming equivalent of [ExchangeData::enables_filtered_decks].
+    fn searched_cards_enable_filtered_decks(&self, col: &Collection) -> Result<bool> {
+        let mut enabled = true;
+        col.storage.for_each_card_in_search(|card| {
+            enabled &= self.card_enables_filtered_decks(&card);
+            Ok(())
+        })?;
+        Ok(enabled)
     }
 
     fn reset_cards_and_notes(&mut self, col: &Collection) {
@@ -94,12 +190,8 @@ impl ExchangeData {
     }
 
     fn remove_system_tags(&mut self) {
-        const SYSTEM_TAGS: [&str; 2] = ["marked", "leech"];
         for note in self.notes.iter_mut() {
-            note.tags = std::mem::take(&mut note.tags)
-                .into_iter()
-                .filter(|tag| !SYSTEM_TAGS.iter().any(|s| tag.eq_ignore_ascii_case(s)))
-                .collect();
+            remove_system_tags_from_note(note);
         }
     }
 
@@ -148,7 +240,11 @@ impl ExchangeData {
     fn enables_filtered_decks(&self) -> bool {
         self.cards
             .iter()
-            .all(|c| self.card_and_its_deck_are_normal(c) || self.original_deck_exists(c))
+            .all(|c| self.card_enables_filtered_decks(c))
+    }
+
+    fn card_enables_filtered_decks(&self, card: &Card) -> bool {
+        self.card_and_its_deck_are_normal(card) || self.original_deck_exists(card)
     }
 
     fn card_and_its_deck_are_normal(&self, card: &Card) -> bool {
@@ -168,42 +264,75 @@ impl ExchangeData {
     fn reset_cards(&mut self, col: &Collection) {
         let mut position = col.get_next_card_position();
         for card in self.cards.iter_mut() {
-            // schedule_as_new() removes cards from filtered decks, but we want to
-            // leave cards in their current deck, which gets converted to a regular one
-            let deck_id = card.deck_id;
-            if card.schedule_as_new(position, true, true) {
-                position += 1;
-            }
-            card.flags = 0;
-            card.deck_id = deck_id;
+            reset_card(card, &mut position);
         }
     }
 
     fn restore_cards_from_filtered_decks(&mut self) {
         for card in self.cards.iter_mut() {
-            if card.is_filtered() {
-                // instead of moving between decks, the deck is converted to a regular one
-                card.original_deck_id = card.deck_id;
-                card.remove_from_filtered_deck_restoring_queue();
-            }
+            restore_card_from_filtered_deck(card);
         }
     }
 
     fn check_ids(&self) -> Result<()> {
-        let tomorrow = TimestampMillis::now().adding_secs(86_400).0;
-        if self
-            .cards
+        let max_id = max_valid_id();
+        self.cards
             .iter()
             .map(|card| card.id.0)
             .chain(self.notes.iter().map(|note| note.id.0))
             .chain(self.revlog.iter().map(|entry| entry.id.0))
-            .any(|timestamp| timestamp > tomorrow)
-        {
-            Err(AnkiError::InvalidId)
-        } else {
-            Ok(())
-        }
+            .try_for_each(|id| check_id(id, max_id))
+    }
+}
+
+fn remove_system_tags_from_note(note: &mut Note) {
+    const SYSTEM_TAGS: [&str; 2] = ["marked", "leech"];
+    note.tags = std::mem::take(&mut note.tags)
+        .into_iter()
+        .filter(|tag| !SYSTEM_TAGS.iter().any(|s| tag.eq_ignore_ascii_case(s)))
+        .collect();
+}
+
+fn reset_card(card: &mut Card, position: &mut u32) {
+    // schedule_as_new() removes cards from filtered decks, but we want to
+    // leave cards in their current deck, which gets converted to a regular one
+    let deck_id = card.deck_id;
+    if card.schedule_as_new(*position, true, true) {
+        *position += 1;
     }
+    card.flags = 0;
+    card.deck_id = deck_id;
+}
+
+fn restore_card_from_filtered_deck(card: &mut Card) {
+    if card.is_filtered() {
+        // instead of moving between decks, the deck is converted to a regular one
+        card.original_deck_id = card.deck_id;
+        card.remove_from_filtered_deck_restoring_queue();
+    }
+}
+
+/// Ids are millisecond timestamps, so ids after tomorrow are invalid.
+fn max_valid_id() -> i64 {
+    TimestampMillis::now().adding_secs(86_400).0
+}
+
+fn check_id(id: i64, max_id: i64) -> Result<()> {
+    if id > max_id {
+        Err(AnkiError::InvalidId)
+    } else {
+        Ok(())
+    }
+}
+
+fn stream_revlog(col: &Collection, target: &Collection) -> Result<()> {
+    let max_id = max_valid_id();
+    col.storage
+        .for_each_revlog_entry_of_searched_cards(|entry| {
+            check_id(entry.id.0, max_id)?;
+            target.storage.add_revlog_entry(&entry, false)?;
+            Ok(())
+        })
 }
 
 fn gather_media_names_from_note(
@@ -231,8 +360,18 @@ fn svg_getter(notetypes: &[Notetype]) -> impl Fn(NotetypeId) -> bool {
 }
 
 impl Collection {
-    fn gather_notes(&mut self, search: impl TryIntoSearch) -> Result<(Vec<Note>, NoteTableGuard)> {
+    fn gather_notes(
+        &mut self,
+        search: impl TryIntoSearch,
+        changed_since: Option<TimestampSecs>,
+    ) -> Result<(Vec<Note>, NoteTableGuard)> {
         let guard = self.search_notes_into_table(search)?;
+        if let Some(since) = changed_since {
+            guard
+                .col
+                .storage
+                .retain_searched_notes_changed_since(since)?;
+        }
         guard
             .col
             .storage
@@ -329,7 +468,7 @@ mod test {
         let mut col = Collection::new();
 
         let note = NoteAdder::basic(&mut col).add(&mut col);
-        data.gather_data(&mut col, SearchNode::WholeCollection, true, true)
+        data.gather_data(&mut col, SearchNode::WholeCollection, true, true, None)
             .unwrap();
 
         assert_eq!(data.notes, [note]);
@@ -346,7 +485,52 @@ mod test {
         col.add_note_only_with_id_undoable(&mut note).unwrap();
 
         assert!(data
-            .gather_data(&mut col, SearchNode::WholeCollection, true, true)
+            .gather_data(&mut col, SearchNode::WholeCollection, true, true, None)
             .is_err());
     }
+    #[test]
+    fn streamed_data_should_match_gathered_data() -> Result<()> {
+        let mut col = Collection::new();
+        let mut note = NoteAdder::basic(&mut col).add(&mut col);
+        note.tags = vec!["marked".into(), "foo".into()];
+        col.update_note(&mut note)?;
+        col.answer_good();
+
+        for with_scheduling in [true, false] {
+            let mut gathered = ExchangeData::default();
+            gathered.gather_data(
+                &mut col,
+                SearchNode::WholeCollection,
+                with_scheduling,
+                true,
+                None,
+            )?;
+
+            let mut streamed = ExchangeData::default();
+            let mut target = Collection::new();
+            let mut progress = col.new_progress_handler();
+            let note_count = streamed.stream_data(
+                &mut col,
+                &mut target,
+                SearchNode::WholeCollection,
+                with_scheduling,
+                true,
+                true,
+                None,
+                &mut progress,
+            )?;
+
+            assert_eq!(note_count, 1);
+            assert_eq!(target.storage.get_all_notes(), gathered.notes);
+            assert_eq!(target.storage.get_all_cards(), gathered.cards);
+            assert_eq!(
+                target.storage.get_all_revlog_entries(TimestampSecs(0))?,
+                gathered.revlog
+            );
+            assert_eq!(streamed.decks, gathered.decks);
+            assert_eq!(streamed.notetypes, gathered.notetypes);
+            assert_eq!(streamed.deck_configs, gathered.deck_configs);
+        }
+        Ok(())
+    }
 }
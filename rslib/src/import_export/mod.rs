@@ -4,6 +4,7 @@
 mod gather;
 mod insert;
 pub mod package;
+mod preview;
 mod service;
 pub mod text;
 
@@ -65,6 +66,8 @@ pub enum ImportError {
     MediaImportFailed { info: String },
     NoFieldColumn,
     EmptyFile,
+    PasswordRequired,
+    WrongPassword,
 }
 
 impl ImportError {
@@ -77,6 +80,8 @@ impl ImportError {
             }
             ImportError::NoFieldColumn => tr.importing_file_must_contain_field_column(),
             ImportError::EmptyFile => tr.importing_file_empty(),
+            ImportError::PasswordRequired => tr.importing_package_password_required(),
+            ImportError::WrongPassword => tr.importing_package_wrong_password(),
         }
         .into()
     }
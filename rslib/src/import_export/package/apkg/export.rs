@@ -13,6 +13,7 @@ use super::super::meta::MetaExt;
 use crate::collection::CollectionBuilder;
 use crate::import_export::gather::ExchangeData;
 use crate::import_export::package::colpkg::export::export_collection;
+use crate::import_export::package::encryption::maybe_encrypt_package;
 use crate::import_export::package::media::MediaIter;
 use crate::import_export::package::ExportAnkiPackageOptions;
 use crate::import_export::package::Meta;
@@ -41,7 +42,8 @@ impl Collection {
         } else {
             Meta::new()
         };
-        let data =
+        let password = options.password.clone();
+        let (data, note_count) =
             self.export_into_collection_file(&meta, temp_col_path, options, search, &mut progress)?;
 
         progress.set(ExportProgress::File)?;
@@ -61,10 +63,14 @@ impl Collection {
             &self.tr,
             &mut progress,
         )?;
+        let temp_apkg = maybe_encrypt_package(temp_apkg, password.as_deref())?;
         atomic_rename(temp_apkg, out_path.as_ref(), true)?;
-        Ok(data.notes.len())
+        Ok(note_count)
     }
 
+    /// Notes, cards and revlog entries are copied into the new collection one
+    /// at a time, so memory use does not grow with the size of the export.
+    /// Returns the gathered remaining data and the number of exported notes.
     fn export_into_collection_file(
         &mut self,
         meta: &Meta,
@@ -72,27 +78,28 @@ impl Collection {
         options: ExportAnkiPackageOptions,
         search: impl TryIntoSearch,
         progress: &mut ThrottlingProgressHandler<ExportProgress>,
-    ) -> Result<ExchangeData> {
+    ) -> Result<(ExchangeData, usize)> {
         let mut data = ExchangeData::default();
+        let mut temp_col = Collection::new_minimal(path)?;
         progress.set(ExportProgress::Gathering)?;
-        data.gather_data(
+        let note_count = data.stream_data(
             self,
+            &mut temp_col,
             search,
             options.with_scheduling,
             options.with_deck_configs,
+            options.with_media,
+            options.changed_since.map(TimestampSecs),
+            progress,
         )?;
-        if options.with_media {
-            data.gather_media_names(progress)?;
-        }
 
-        let mut temp_col = Collection::new_minimal(path)?;
         progress.set(ExportProgress::File)?;
         temp_col.insert_data(&data)?;
         temp_col.set_creation_stamp(self.storage.creation_stamp()?)?;
         temp_col.set_creation_utc_offset(data.creation_utc_offset)?;
         temp_col.close(Some(meta.schema_version()))?;
 
-        Ok(data)
+        Ok((data, note_count))
     }
 
     fn new_minimal(path: impl Into<PathBuf>) -> Result<Self> {
@@ -10,9 +10,9 @@ use std::fs::File;
 use std::path::Path;
 
 use anki_io::new_tempfile;
-use anki_io::open_file;
 use anki_io::FileIoSnafu;
 use anki_io::FileOp;
+use anki_proto::import_export::ImportPreview;
 pub(crate) use notes::NoteMeta;
 use rusqlite::OptionalExtension;
 use tempfile::NamedTempFile;
@@ -22,6 +22,7 @@ use super::super::meta::MetaExt;
 use crate::collection::CollectionBuilder;
 use crate::config::ConfigKey;
 use crate::import_export::gather::ExchangeData;
+use crate::import_export::package::encryption::open_package;
 use crate::import_export::package::ImportAnkiPackageOptions;
 use crate::import_export::package::Meta;
 use crate::import_export::package::UpdateCondition;
@@ -54,20 +55,44 @@ impl Collection {
         path: impl AsRef<Path>,
         options: ImportAnkiPackageOptions,
     ) -> Result<OpOutput<NoteLog>> {
-        let file = open_file(path)?;
+        let file = open_package(path, options.password.as_deref())?;
         let archive = ZipArchive::new(file)?;
         let progress = self.new_progress_handler();
 
         self.transact(Op::Import, |col| {
-            col.set_config(BoolKey::MergeNotetypes, &options.merge_notetypes)?;
-            col.set_config(BoolKey::WithScheduling, &options.with_scheduling)?;
-            col.set_config(BoolKey::WithDeckConfigs, &options.with_deck_configs)?;
-            col.set_config(ConfigKey::UpdateNotes, &options.update_notes())?;
-            col.set_config(ConfigKey::UpdateNotetypes, &options.update_notetypes())?;
-            let mut ctx = Context::new(archive, col, options, progress)?;
-            ctx.import()
+            col.import_apkg_inner(archive, options, progress, true)
         })
     }
+
+    /// Report what importing the package would change, without modifying the
+    /// collection or the media folder.
+    pub fn preview_import_apkg(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: ImportAnkiPackageOptions,
+    ) -> Result<ImportPreview> {
+        let file = open_package(path, options.password.as_deref())?;
+        let archive = ZipArchive::new(file)?;
+        let progress = self.new_progress_handler();
+
+        self.preview_import(|col| col.import_apkg_inner(archive, options, progress, false))
+    }
+
+    fn import_apkg_inner(
+        &mut self,
+        archive: ZipArchive<File>,
+        options: ImportAnkiPackageOptions,
+        progress: ThrottlingProgressHandler<ImportProgress>,
+        with_media: bool,
+    ) -> Result<NoteLog> {
+        self.set_config(BoolKey::MergeNotetypes, &options.merge_notetypes)?;
+        self.set_config(BoolKey::WithScheduling, &options.with_scheduling)?;
+        self.set_config(BoolKey::WithDeckConfigs, &options.with_deck_configs)?;
+        self.set_config(ConfigKey::UpdateNotes, &options.update_notes())?;
+        self.set_config(ConfigKey::UpdateNotetypes, &options.update_notetypes())?;
+        let mut ctx = Context::new(archive, self, options, progress)?;
+        ctx.import(with_media)
+    }
 }
 
 impl<'a> Context<'a> {
@@ -102,7 +127,7 @@ impl<'a> Context<'a> {
         })
     }
 
-    fn import(&mut self) -> Result<NoteLog> {
+    fn import(&mut self, with_media: bool) -> Result<NoteLog> {
         let notetypes = self
             .data
             .notes
@@ -118,7 +143,9 @@ impl<'a> Context<'a> {
             &note_imports.remapped_templates,
             &imported_decks,
         )?;
-        self.copy_media(&mut media_map)?;
+        if with_media {
+            self.copy_media(&mut media_map)?;
+        }
         Ok(note_imports.log)
     }
 }
@@ -139,7 +166,7 @@ impl ExchangeData {
 
         progress.set(ImportProgress::Gathering)?;
         let mut data = ExchangeData::default();
-        data.gather_data(&mut col, search, with_scheduling, with_deck_configs)?;
+        data.gather_data(&mut col, search, with_scheduling, with_deck_configs, None)?;
 
         Ok(data)
     }
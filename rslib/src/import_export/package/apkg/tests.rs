@@ -10,12 +10,14 @@ use std::io::Write;
 use anki_io::read_file;
 use anki_proto::import_export::ImportAnkiPackageOptions;
 
+use crate::collection::CollectionBuilder;
 use crate::import_export::package::ExportAnkiPackageOptions;
 use crate::media::files::sha1_of_data;
 use crate::media::MediaManager;
 use crate::prelude::*;
 use crate::search::SearchNode;
 use crate::tests::open_fs_test_collection;
+use crate::tests::NoteAdder;
 
 const SAMPLE_JPG: &str = "sample.jpg";
 const SAMPLE_MP3: &str = "sample.mp3";
@@ -50,6 +52,8 @@ fn roundtrip_inner(legacy: bool) {
                 with_deck_configs: true,
                 with_media: true,
                 legacy,
+                changed_since: None,
+                password: None,
             },
             SearchNode::from_deck_name("parent::sample"),
             None,
@@ -67,6 +71,51 @@ fn roundtrip_inner(legacy: bool) {
     target_col.assert_empty();
 }
 
+#[test]
+fn delta_export_should_only_contain_changed_notes() -> Result<()> {
+    let mut src = Collection::new();
+    NoteAdder::basic(&mut src)
+        .fields(&["unchanged", ""])
+        .add(&mut src);
+    let mut edited = NoteAdder::basic(&mut src)
+        .fields(&["edited", ""])
+        .add(&mut src);
+    let flagged = NoteAdder::basic(&mut src)
+        .fields(&["flagged", ""])
+        .add(&mut src);
+    src.storage
+        .db
+        .execute_batch("UPDATE notes SET mod = 0; UPDATE cards SET mod = 0")?;
+
+    let since = TimestampSecs::now();
+    edited.fields_mut()[1] = "new back".into();
+    src.update_note(&mut edited)?;
+    let card_id = src.storage.card_ids_of_notes(&[flagged.id])?[0];
+    src.set_card_flag(&[card_id], 1)?;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("delta.apkg");
+    let options = ExportAnkiPackageOptions {
+        changed_since: Some(since.0),
+        ..Default::default()
+    };
+    assert_eq!(src.export_apkg(&path, options, "", None)?, 2);
+
+    let mut dst = CollectionBuilder::new(dir.path().join("dst.anki2"))
+        .with_desktop_media_paths()
+        .build()?;
+    dst.import_apkg(&path, ImportAnkiPackageOptions::default())?;
+    let mut sort_fields: Vec<_> = dst
+        .storage
+        .get_all_notes()
+        .into_iter()
+        .map(|note| note.fields()[0].clone())
+        .collect();
+    sort_fields.sort();
+    assert_eq!(sort_fields, ["edited", "flagged"]);
+    Ok(())
+}
+
 impl Collection {
     fn add_sample_decks(&mut self) -> (Deck, Deck) {
         let sample = self.add_named_deck("parent\x1fsample");
@@ -29,6 +29,7 @@ use super::super::MediaEntry;
 use super::super::Meta;
 use super::super::Version;
 use crate::collection::CollectionBuilder;
+use crate::import_export::package::encryption::maybe_encrypt_package;
 use crate::import_export::package::media::new_media_entry;
 use crate::import_export::package::media::MediaCopier;
 use crate::import_export::package::media::MediaIter;
@@ -43,11 +44,13 @@ use crate::storage::SchemaVersion;
 const MULTITHREAD_MIN_BYTES: usize = 10 * 1024 * 1024;
 
 impl Collection {
+    /// If a non-empty password is provided, the package is encrypted with it.
     pub fn export_colpkg(
         self,
         out_path: impl AsRef<Path>,
         include_media: bool,
         legacy: bool,
+        password: Option<&str>,
     ) -> Result<()> {
         let mut progress = self.new_progress_handler();
         let colpkg_name = out_path.as_ref();
@@ -73,6 +76,7 @@ impl Collection {
             &tr,
             &mut progress,
         )?;
+        let temp_colpkg = maybe_encrypt_package(temp_colpkg, password)?;
         atomic_rename(temp_colpkg, colpkg_name, true)?;
 
         Ok(())
@@ -103,23 +107,22 @@ fn export_collection_file(
     export_collection(meta, out_path, &mut col_file, col_size, media, tr, progress)
 }
 
-/// Write copied collection data without any media.
-pub(crate) fn export_colpkg_from_data(
+/// Write a copied collection file without any media, encrypting the package
+/// if a non-empty password is provided. The file is streamed into the package
+/// rather than read into memory.
+pub(crate) fn export_colpkg_from_file(
     out_path: impl AsRef<Path>,
-    mut col_data: &[u8],
+    col_path: impl AsRef<Path>,
+    password: Option<&str>,
     tr: &I18n,
 ) -> Result<()> {
-    let col_size = col_data.len();
     let mut progress = ThrottlingProgressHandler::new(Default::default());
-    export_collection(
-        Meta::new(),
-        out_path,
-        &mut col_data,
-        col_size,
-        MediaIter::empty(),
-        tr,
-        &mut progress,
-    )
+    let temp_colpkg = new_tempfile_in_parent_of(out_path.as_ref())?;
+    export_collection_file(temp_colpkg.path(), col_path, None, false, tr, &mut progress)?;
+    let temp_colpkg = maybe_encrypt_package(temp_colpkg, password)?;
+    atomic_rename(temp_colpkg, out_path.as_ref(), true)?;
+
+    Ok(())
 }
 
 pub(crate) fn export_collection(
@@ -10,7 +10,6 @@ use std::path::PathBuf;
 use anki_io::atomic_rename;
 use anki_io::create_dir_all;
 use anki_io::new_tempfile_in_parent_of;
-use anki_io::open_file;
 use anki_io::FileIoSnafu;
 use anki_io::FileOp;
 use zip::read::ZipFile;
@@ -19,6 +18,7 @@ use zstd::stream::copy_decode;
 
 use super::super::meta::MetaExt;
 use crate::collection::CollectionBuilder;
+use crate::import_export::package::encryption::open_package;
 use crate::import_export::package::media::extract_media_entries;
 use crate::import_export::package::media::SafeMediaEntry;
 use crate::import_export::package::Meta;
@@ -33,12 +33,13 @@ pub fn import_colpkg(
     target_col_path: &str,
     target_media_folder: &Path,
     media_db: &Path,
+    password: Option<&str>,
     mut progress: ThrottlingProgressHandler<ImportProgress>,
 ) -> Result<()> {
     let col_path = PathBuf::from(target_col_path);
     let mut tempfile = new_tempfile_in_parent_of(&col_path)?;
 
-    let backup_file = open_file(colpkg_path)?;
+    let backup_file = open_package(colpkg_path, password)?;
     let mut archive = ZipArchive::new(backup_file)?;
     let meta = Meta::from_archive(&mut archive)?;
 
@@ -11,8 +11,10 @@ use tempfile::tempdir;
 
 use crate::collection::CollectionBuilder;
 use crate::import_export::package::import_colpkg;
+use crate::import_export::ImportError;
 use crate::media::MediaManager;
 use crate::prelude::*;
+use crate::progress::ThrottlingProgressHandler;
 
 fn collection_with_media(dir: &Path, name: &str) -> Result<Collection> {
     let name = format!("{name}_src");
@@ -41,7 +43,7 @@ fn roundtrip() -> Result<()> {
         let col = collection_with_media(dir, name)?;
         let colpkg_name = dir.join(format!("{name}.colpkg"));
         let progress = col.new_progress_handler();
-        col.export_colpkg(&colpkg_name, true, legacy)?;
+        col.export_colpkg(&colpkg_name, true, legacy, None)?;
 
         // import into a new collection
         let anki2_name = dir
@@ -57,6 +59,7 @@ fn roundtrip() -> Result<()> {
             &anki2_name,
             &import_media_dir,
             &import_media_db,
+            None,
             progress,
         )?;
 
@@ -90,7 +93,8 @@ fn normalization_check_on_export() -> Result<()> {
     // manually write a file in the wrong encoding.
     write_file(col.media_folder.join("ぱぱ.jpg"), "nfd encoding")?;
     assert_eq!(
-        col.export_colpkg(&colpkg_name, true, false,).unwrap_err(),
+        col.export_colpkg(&colpkg_name, true, false, None)
+            .unwrap_err(),
         AnkiError::MediaCheckRequired
     );
     // file should have been cleaned up
@@ -98,3 +102,47 @@ fn normalization_check_on_export() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn encrypted_package_requires_its_password() -> Result<()> {
+    let _dir = tempdir()?;
+    let dir = _dir.path();
+
+    let col = collection_with_media(dir, "encrypted")?;
+    let colpkg_name = dir.join("encrypted.colpkg");
+    col.export_colpkg(&colpkg_name, true, false, Some("secret"))?;
+
+    let anki2_name = dir.join("encrypted.anki2").to_string_lossy().into_owned();
+    let import_media_dir = dir.join("encrypted.media");
+    create_dir_all(&import_media_dir)?;
+    let import_media_db = dir.join("encrypted.mdb");
+    MediaManager::new(&import_media_dir, &import_media_db)?;
+    let import = |password| {
+        import_colpkg(
+            &colpkg_name.to_string_lossy(),
+            &anki2_name,
+            &import_media_dir,
+            &import_media_db,
+            password,
+            ThrottlingProgressHandler::new(Default::default()),
+        )
+    };
+
+    assert_eq!(
+        import(None).unwrap_err(),
+        AnkiError::from(ImportError::PasswordRequired)
+    );
+    assert_eq!(
+        import(Some("wrong")).unwrap_err(),
+        AnkiError::from(ImportError::WrongPassword)
+    );
+    import(Some("secret"))?;
+    let col = CollectionBuilder::new(&anki2_name).build()?;
+    assert_eq!(
+        col.storage.db_scalar::<i32>("select count() from notes")?,
+        1
+    );
+    assert_eq!(read_file(import_media_dir.join("1"))?, b"1");
+
+    Ok(())
+}
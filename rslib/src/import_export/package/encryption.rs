@@ -0,0 +1,272 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Password-based encryption of packages.
+//!
+//! An encrypted package starts with a header holding a magic number, the salt
+//! and iteration count used to derive a key with PBKDF2-HMAC-SHA256, and a
+//! nonce prefix. The original package follows in chunks sealed with
+//! AES-256-GCM, so packages of any size can be processed without reading them
+//! into memory. A chunk's nonce includes its index and whether it is the last
+//! chunk, so chunks cannot be reordered or truncated without detection.
+
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use anki_io::new_tempfile_in_parent_of;
+use anki_io::open_file;
+use rand::Rng;
+use ring::aead::Aad;
+use ring::aead::LessSafeKey;
+use ring::aead::Nonce;
+use ring::aead::UnboundKey;
+use ring::aead::AES_256_GCM;
+use ring::aead::NONCE_LEN;
+use ring::pbkdf2;
+use tempfile::NamedTempFile;
+
+use crate::import_export::ImportError;
+use crate::prelude::*;
+
+const MAGIC: &[u8; 8] = b"ANKIENC1";
+const SALT_LEN: usize = 16;
+const NONCE_PREFIX_LEN: usize = 7;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + 4 + NONCE_PREFIX_LEN;
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const CHUNK_LEN: usize = 64 * 1024;
+/// Headers requesting more iterations are rejected, so a crafted file cannot
+/// stall the importer.
+const MAX_ITERATIONS: u32 = 10_000_000;
+#[cfg(not(test))]
+const ITERATIONS: u32 = 600_000;
+#[cfg(test)]
+const ITERATIONS: u32 = 1_000;
+
+/// If a non-empty password is provided, the package is replaced by an
+/// encrypted copy next to it.
+pub(crate) fn maybe_encrypt_package(
+    package: NamedTempFile,
+    password: Option<&str>,
+) -> Result<NamedTempFile> {
+    let Some(password) = password.filter(|p| !p.is_empty()) else {
+        return Ok(package);
+    };
+    let mut encrypted = new_tempfile_in_parent_of(package.path())?;
+    encrypt(&mut package.reopen()?, encrypted.as_file_mut(), password)?;
+    Ok(encrypted)
+}
+
+/// Opens the package at `path`. If it is encrypted, it is decrypted into an
+/// anonymous temp file, which is returned instead.
+pub(crate) fn open_package(path: impl AsRef<Path>, password: Option<&str>) -> Result<File> {
+    let mut file = open_file(path)?;
+    if !is_encrypted(&mut file)? {
+        return Ok(file);
+    }
+    let password = password
+        .filter(|p| !p.is_empty())
+        .ok_or(ImportError::PasswordRequired)?;
+    let mut decrypted = tempfile::tempfile()?;
+    decrypt(&mut file, &mut decrypted, password)?;
+    decrypted.rewind()?;
+    Ok(decrypted)
+}
+
+fn is_encrypted(file: &mut (impl Read + Seek)) -> Result<bool> {
+    let mut magic = [0; MAGIC.len()];
+    let encrypted = file.read_exact(&mut magic).is_ok() && &magic == MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(encrypted)
+}
+
+fn encrypt(src: &mut impl Read, dst: &mut impl Write, password: &str) -> Result<()> {
+    let header = Header::new_random();
+    let header_bytes = header.to_bytes();
+    let key = header.key(password);
+    dst.write_all(&header_bytes)?;
+
+    let mut buf = Vec::with_capacity(CHUNK_LEN + TAG_LEN);
+    for index in 0.. {
+        buf.clear();
+        (&mut *src).take(CHUNK_LEN as u64).read_to_end(&mut buf)?;
+        // a full chunk is never the last one; if the input ends on a chunk
+        // boundary, an empty last chunk follows
+        let last = buf.len() < CHUNK_LEN;
+        key.seal_in_place_append_tag(
+            header.nonce(index, last),
+            Aad::from(&header_bytes),
+            &mut buf,
+        )
+        .ok()
+        .or_invalid("package too large to encrypt")?;
+        dst.write_all(&buf)?;
+        if last {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// As a wrong password cannot be told apart from a corrupt first chunk, a
+/// failure to open that chunk is reported as the former.
+fn decrypt(src: &mut impl Read, dst: &mut impl Write, password: &str) -> Result<()> {
+    let mut header_bytes = [0; HEADER_LEN];
+    src.read_exact(&mut header_bytes)
+        .map_err(|_| ImportError::Corrupt)?;
+    let header = Header::from_bytes(&header_bytes)?;
+    let key = header.key(password);
+
+    let mut buf = Vec::with_capacity(CHUNK_LEN + TAG_LEN);
+    for index in 0.. {
+        buf.clear();
+        (&mut *src)
+            .take((CHUNK_LEN + TAG_LEN) as u64)
+            .read_to_end(&mut buf)?;
+        let last = buf.len() < CHUNK_LEN + TAG_LEN;
+        let plaintext = key
+            .open_in_place(
+                header.nonce(index, last),
+                Aad::from(&header_bytes),
+                &mut buf,
+            )
+            .map_err(|_| {
+                if index == 0 {
+                    ImportError::WrongPassword
+                } else {
+                    ImportError::Corrupt
+                }
+            })?;
+        dst.write_all(plaintext)?;
+        if last {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+struct Header {
+    salt: [u8; SALT_LEN],
+    iterations: NonZeroU32,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+}
+
+impl Header {
+    fn new_random() -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            salt: rng.gen(),
+            iterations: NonZeroU32::new(ITERATIONS).unwrap(),
+            nonce_prefix: rng.gen(),
+        }
+    }
+
+    fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        let (magic, rest) = bytes.split_at_mut(MAGIC.len());
+        let (salt, rest) = rest.split_at_mut(SALT_LEN);
+        let (iterations, nonce_prefix) = rest.split_at_mut(4);
+        magic.copy_from_slice(MAGIC);
+        salt.copy_from_slice(&self.salt);
+        iterations.copy_from_slice(&self.iterations.get().to_le_bytes());
+        nonce_prefix.copy_from_slice(&self.nonce_prefix);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; HEADER_LEN]) -> Result<Self> {
+        let rest = bytes.strip_prefix(MAGIC).ok_or(ImportError::Corrupt)?;
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (iterations, nonce_prefix) = rest.split_at(4);
+        let iterations = u32::from_le_bytes(iterations.try_into().unwrap());
+        Ok(Self {
+            salt: salt.try_into().unwrap(),
+            iterations: NonZeroU32::new(iterations)
+                .filter(|n| n.get() <= MAX_ITERATIONS)
+                .ok_or(ImportError::Corrupt)?,
+            nonce_prefix: nonce_prefix.try_into().unwrap(),
+        })
+    }
+
+    fn key(&self, password: &str) -> LessSafeKey {
+        let mut key = [0; KEY_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            self.iterations,
+            &self.salt,
+            password.as_bytes(),
+            &mut key,
+        );
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).unwrap())
+    }
+
+    /// The prefix, followed by the big-endian chunk index and a flag marking
+    /// the last chunk.
+    fn nonce(&self, index: u32, last: bool) -> Nonce {
+        let mut nonce = [0; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&index.to_be_bytes());
+        nonce[NONCE_LEN - 1] = last as u8;
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn roundtrip(len: usize) -> Result<()> {
+        let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let mut encrypted = Cursor::new(vec![]);
+        encrypt(&mut plaintext.as_slice(), &mut encrypted, "secret")?;
+        assert!(is_encrypted(&mut encrypted)?);
+
+        let mut decrypted = vec![];
+        decrypt(&mut encrypted, &mut decrypted, "secret")?;
+        assert_eq!(decrypted, plaintext);
+        Ok(())
+    }
+
+    #[test]
+    fn should_roundtrip_any_length() -> Result<()> {
+        for len in [0, 1, CHUNK_LEN - 1, CHUNK_LEN, 2 * CHUNK_LEN + 5] {
+            roundtrip(len)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_wrong_password_and_tampering() -> Result<()> {
+        let plaintext = vec![0; 2 * CHUNK_LEN];
+        let mut encrypted = vec![];
+        encrypt(&mut plaintext.as_slice(), &mut encrypted, "secret")?;
+        let decrypt_err =
+            |bytes: &[u8], password| decrypt(&mut &bytes[..], &mut vec![], password).unwrap_err();
+
+        assert_eq!(
+            decrypt_err(&encrypted, "wrong"),
+            AnkiError::from(ImportError::WrongPassword)
+        );
+        // dropping the final, empty chunk
+        assert_eq!(
+            decrypt_err(&encrypted[..encrypted.len() - TAG_LEN], "secret"),
+            AnkiError::from(ImportError::Corrupt)
+        );
+        // altering a later chunk
+        let last = encrypted.len() - 1;
+        encrypted[last - TAG_LEN] ^= 1;
+        assert_eq!(
+            decrypt_err(&encrypted, "secret"),
+            AnkiError::from(ImportError::Corrupt)
+        );
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 
 mod apkg;
 mod colpkg;
+mod encryption;
 mod media;
 mod meta;
 
@@ -12,7 +13,7 @@ pub use anki_proto::import_export::ImportAnkiPackageOptions;
 pub use anki_proto::import_export::ImportAnkiPackageUpdateCondition as UpdateCondition;
 use anki_proto::import_export::MediaEntries;
 pub(crate) use apkg::NoteMeta;
-pub(crate) use colpkg::export::export_colpkg_from_data;
+pub(crate) use colpkg::export::export_colpkg_from_file;
 pub use colpkg::import::import_colpkg;
 pub use media::MediaIter;
 pub use media::MediaIterEntry;
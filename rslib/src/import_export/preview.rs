@@ -0,0 +1,217 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anki_proto::import_export::import_preview::FieldChange;
+use anki_proto::import_export::import_preview::UpdatedNote;
+use anki_proto::import_export::ImportPreview;
+
+use crate::import_export::NoteLog;
+use crate::prelude::*;
+
+/// The state of the objects an import may have modified, gathered before the
+/// import is rolled back.
+struct ImportedState {
+    /// Notes the import may have updated, with the field names of their
+    /// notetype.
+    notes: Vec<(Note, Vec<String>)>,
+    notetypes: Vec<Arc<Notetype>>,
+    deck_configs: Vec<DeckConfig>,
+}
+
+impl Collection {
+    /// Run the provided import, then roll it back and report what it would
+    /// have changed.
+    pub(crate) fn preview_import(
+        &mut self,
+        import: impl FnOnce(&mut Collection) -> Result<NoteLog>,
+    ) -> Result<ImportPreview> {
+        let (log, imported) = self.transact_dry_run(|col| {
+            let log = import(col)?;
+            let imported = col.gather_imported_state(&log)?;
+            Ok((log, imported))
+        })?;
+        self.compare_with_imported_state(log, imported)
+    }
+
+    fn gather_imported_state(&mut self, log: &NoteLog) -> Result<ImportedState> {
+        let mut notes = vec![];
+        let mut field_names = HashMap::new();
+        // notes matching by first field may also have been updated
+        for log_note in log.updated.iter().chain(&log.first_field_match) {
+            let nid = NoteId(log_note.id.as_ref().map(|id| id.nid).unwrap_or_default());
+            if let Some(note) = self.storage.get_note(nid)? {
+                let names = match field_names.get(&note.notetype_id) {
+                    Some(names) => names.clone(),
+                    None => {
+                        let notetype = self
+                            .get_notetype(note.notetype_id)?
+                            .or_not_found(note.notetype_id)?;
+                        let names: Vec<String> =
+                            notetype.fields.iter().map(|f| f.name.clone()).collect();
+                        field_names.insert(note.notetype_id, names.clone());
+                        names
+                    }
+                };
+                notes.push((note, names));
+            }
+        }
+        Ok(ImportedState {
+            notes,
+            notetypes: self.get_all_notetypes()?,
+            deck_configs: self.storage.all_deck_config()?,
+        })
+    }
+
+    fn compare_with_imported_state(
+        &mut self,
+        log: NoteLog,
+        imported: ImportedState,
+    ) -> Result<ImportPreview> {
+        let mut preview = ImportPreview {
+            log: Some(log),
+            ..Default::default()
+        };
+        for (note, field_names) in imported.notes {
+            // notes that did not exist before were added, not updated
+            if let Some(original) = self.storage.get_note(note.id)? {
+                if let Some(updated) = note_diff(&original, &note, &field_names) {
+                    preview.updated_notes.push(updated);
+                }
+            }
+        }
+        for notetype in imported.notetypes {
+            match self.storage.get_notetype(notetype.id)? {
+                None => preview.new_notetypes.push(notetype.name.clone()),
+                Some(original) if notetype_changed(&original, &notetype) => {
+                    preview.changed_notetypes.push(notetype.name.clone())
+                }
+                _ => (),
+            }
+        }
+        for config in imported.deck_configs {
+            match self.storage.get_deck_config(config.id)? {
+                None => preview.new_deck_configs.push(config.name),
+                Some(original) if deck_config_changed(&original, &config) => {
+                    preview.changed_deck_configs.push(config.name)
+                }
+                _ => (),
+            }
+        }
+        Ok(preview)
+    }
+}
+
+/// None if neither fields nor tags differ.
+fn note_diff(original: &Note, updated: &Note, field_names: &[String]) -> Option<UpdatedNote> {
+    let fields: Vec<_> = updated
+        .fields()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, new_value)| {
+            let old_value = original.fields().get(idx).map(String::as_str);
+            (old_value != Some(new_value.as_str())).then(|| FieldChange {
+                field: field_names.get(idx).cloned().unwrap_or_default(),
+                old_value: old_value.unwrap_or_default().to_string(),
+                new_value: new_value.clone(),
+            })
+        })
+        .collect();
+    let original_tags: HashSet<&String> = original.tags.iter().collect();
+    let updated_tags: HashSet<&String> = updated.tags.iter().collect();
+    let added_tags: Vec<String> = updated
+        .tags
+        .iter()
+        .filter(|tag| !original_tags.contains(tag))
+        .cloned()
+        .collect();
+    let removed_tags: Vec<String> = original
+        .tags
+        .iter()
+        .filter(|tag| !updated_tags.contains(tag))
+        .cloned()
+        .collect();
+    if fields.is_empty() && added_tags.is_empty() && removed_tags.is_empty() {
+        return None;
+    }
+    Some(UpdatedNote {
+        id: Some(updated.id.into()),
+        fields,
+        added_tags,
+        removed_tags,
+    })
+}
+
+/// Modification times and USNs are ignored.
+fn notetype_changed(original: &Notetype, updated: &Notetype) -> bool {
+    original.name != updated.name
+        || original.fields != updated.fields
+        || original.config != updated.config
+        || original.templates.len() != updated.templates.len()
+        || original
+            .templates
+            .iter()
+            .zip(&updated.templates)
+            .any(|(a, b)| a.ord != b.ord || a.name != b.name || a.config != b.config)
+}
+
+/// Modification times and USNs are ignored.
+fn deck_config_changed(original: &DeckConfig, updated: &DeckConfig) -> bool {
+    *original
+        != DeckConfig {
+            mtime_secs: original.mtime_secs,
+            usn: original.usn,
+            ..updated.clone()
+        }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::NoteAdder;
+
+    #[test]
+    fn preview_reports_changes_without_modifying_collection() -> Result<()> {
+        let mut col = Collection::new();
+        let note = NoteAdder::basic(&mut col)
+            .fields(&["front", "old"])
+            .add(&mut col);
+        let json = r#"{
+            "default_notetype": "Basic",
+            "default_deck": 1,
+            "notes": [
+                {"fields": ["front", "new"], "tags": ["added"]},
+                {"fields": ["other", "back"]}
+            ],
+            "notetypes": [{
+                "name": "Custom",
+                "fields": ["Front"],
+                "templates": [{"name": "Card 1", "qfmt": "{{Front}}", "afmt": ""}]
+            }]
+        }"#;
+        let preview = col.preview_import_json_string(json)?;
+        let log = preview.log.unwrap();
+        assert_eq!((log.new.len(), log.first_field_match.len()), (1, 1));
+        assert_eq!(preview.updated_notes.len(), 1);
+        let updated = &preview.updated_notes[0];
+        assert_eq!(
+            updated.fields,
+            [FieldChange {
+                field: "Back".to_string(),
+                old_value: "old".to_string(),
+                new_value: "new".to_string(),
+            }]
+        );
+        assert_eq!(updated.added_tags, ["added"]);
+        assert_eq!(preview.new_notetypes, ["Custom"]);
+
+        // nothing was imported
+        assert_eq!(col.storage.notes_table_len(), 1);
+        assert_eq!(col.storage.get_note(note.id)?.unwrap().fields()[1], "old");
+        assert!(col.get_notetype_by_name("Custom")?.is_none());
+        Ok(())
+    }
+}
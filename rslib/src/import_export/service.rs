@@ -25,6 +25,7 @@ impl crate::services::ImportExportService for Collection {
             with_deck_configs: self.get_config_bool(BoolKey::WithDeckConfigs),
             update_notes: self.get_update_notes() as i32,
             update_notetypes: self.get_update_notetypes() as i32,
+            password: None,
         })
     }
 
@@ -83,6 +84,25 @@ impl crate::services::ImportExportService for Collection {
         .map(Into::into)
     }
 
+    fn export_revlog_csv(
+        &mut self,
+        input: anki_proto::import_export::ExportRevlogCsvRequest,
+    ) -> Result<generic::UInt32> {
+        self.export_revlog_csv(
+            &input.out_path,
+            SearchNode::from(input.limit.unwrap_or_default()),
+            input.card_stats_out_path.as_deref(),
+        )
+        .map(Into::into)
+    }
+
+    fn export_json(
+        &mut self,
+        input: anki_proto::import_export::ExportJsonRequest,
+    ) -> Result<generic::UInt32> {
+        self.export_json(input).map(Into::into)
+    }
+
     fn import_json_file(
         &mut self,
         input: generic::String,
@@ -96,6 +116,50 @@ impl crate::services::ImportExportService for Collection {
     ) -> Result<anki_proto::import_export::ImportResponse> {
         self.import_json_string(&input.val).map(Into::into)
     }
+
+    fn import_mnemosyne(
+        &mut self,
+        input: anki_proto::import_export::ImportForeignFileRequest,
+    ) -> Result<anki_proto::import_export::ImportResponse> {
+        self.import_mnemosyne(&input.path, DeckId(input.deck_id))
+            .map(Into::into)
+    }
+
+    fn import_supermemo(
+        &mut self,
+        input: anki_proto::import_export::ImportForeignFileRequest,
+    ) -> Result<anki_proto::import_export::ImportResponse> {
+        self.import_supermemo(&input.path, DeckId(input.deck_id))
+            .map(Into::into)
+    }
+
+    fn preview_import_anki_package(
+        &mut self,
+        input: anki_proto::import_export::ImportAnkiPackageRequest,
+    ) -> Result<anki_proto::import_export::ImportPreview> {
+        self.preview_import_apkg(&input.package_path, input.options.unwrap_or_default())
+    }
+
+    fn preview_import_csv(
+        &mut self,
+        input: anki_proto::import_export::ImportCsvRequest,
+    ) -> Result<anki_proto::import_export::ImportPreview> {
+        self.preview_import_csv(&input.path, input.metadata.unwrap_or_default())
+    }
+
+    fn preview_import_json_file(
+        &mut self,
+        input: generic::String,
+    ) -> Result<anki_proto::import_export::ImportPreview> {
+        self.preview_import_json_file(&input.val)
+    }
+
+    fn preview_import_json_string(
+        &mut self,
+        input: generic::String,
+    ) -> Result<anki_proto::import_export::ImportPreview> {
+        self.preview_import_json_string(&input.val)
+    }
 }
 
 impl From<OpOutput<NoteLog>> for anki_proto::import_export::ImportResponse {
@@ -8,6 +8,7 @@ use std::io::Write;
 use std::sync::Arc;
 
 use anki_proto::import_export::ExportNoteCsvRequest;
+use anki_proto::stats::CardStatsResponse;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -17,6 +18,8 @@ use crate::import_export::text::csv::metadata::DelimeterExt;
 use crate::import_export::ExportProgress;
 use crate::notetype::RenderCardOutput;
 use crate::prelude::*;
+use crate::revlog::RevlogEntry;
+use crate::revlog::RevlogReviewKind;
 use crate::search::SearchNode;
 use crate::search::SortMode;
 use crate::template::RenderedNode;
@@ -68,6 +71,50 @@ impl Collection {
         Ok(incrementor.count())
     }
 
+    /// Writes the review history of the matched cards, and optionally their
+    /// statistics as shown in the card info screen, to separate files.
+    /// Returns the number of reviews written.
+    pub fn export_revlog_csv(
+        &mut self,
+        path: &str,
+        search: impl TryIntoSearch,
+        card_stats_path: Option<&str>,
+    ) -> Result<usize> {
+        let mut progress = self.new_progress_handler::<ExportProgress>();
+        let mut incrementor = progress.incrementor(ExportProgress::Cards);
+
+        let mut writer = file_writer_with_column_names(path, &REVLOG_COLUMNS)?;
+        let mut stats_writer = card_stats_path
+            .map(|path| file_writer_with_column_names(path, &CARD_STATS_COLUMNS))
+            .transpose()?;
+        let mut cards = self.search_cards(search, SortMode::NoOrder)?;
+        cards.sort_unstable();
+        let mut review_count = 0;
+        for &card_id in &cards {
+            incrementor.increment()?;
+            let card = self.storage.get_card(card_id)?.or_not_found(card_id)?;
+            let deck = self.get_deck(card.deck_id)?.or_not_found(card.deck_id)?;
+            let deck_name = deck.human_name();
+            for entry in self.storage.get_revlog_entries_for_card(card_id)? {
+                writer
+                    .write_record(revlog_record(&card, &deck_name, &entry))
+                    .or_invalid("invalid csv")?;
+                review_count += 1;
+            }
+            if let Some(stats_writer) = &mut stats_writer {
+                stats_writer
+                    .write_record(card_stats_record(self.card_stats(card_id)?))
+                    .or_invalid("invalid csv")?;
+            }
+        }
+        writer.flush()?;
+        if let Some(mut stats_writer) = stats_writer {
+            stats_writer.flush()?;
+        }
+
+        Ok(review_count)
+    }
+
     fn card_record(&mut self, card: CardId, with_html: bool) -> Result<[String; 2]> {
         let RenderCardOutput { qnodes, anodes, .. } =
             self.render_existing_card(card, false, false)?;
@@ -87,6 +134,105 @@ fn file_writer_with_header(path: &str, with_html: bool) -> Result<csv::Writer<Fi
         .from_writer(file))
 }
 
+/// Unlike the other exports, which are meant to be imported again, these files
+/// start with a row of column names for external tools.
+fn file_writer_with_column_names(path: &str, columns: &[&str]) -> Result<csv::Writer<File>> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(DELIMITER.byte())
+        .from_writer(File::create(path)?);
+    writer.write_record(columns).or_invalid("invalid csv")?;
+    Ok(writer)
+}
+
+const REVLOG_COLUMNS: [&str; 10] = [
+    "card_id",
+    "note_id",
+    "deck",
+    "timestamp",
+    "rating",
+    "last_interval",
+    "interval",
+    "ease",
+    "taken_millis",
+    "review_kind",
+];
+
+/// Intervals are in days if positive, and in seconds if negative.
+fn revlog_record(card: &Card, deck_name: &str, entry: &RevlogEntry) -> [String; 10] {
+    [
+        card.id.to_string(),
+        card.note_id.to_string(),
+        deck_name.to_string(),
+        entry.id.as_secs().to_string(),
+        entry.button_chosen.to_string(),
+        entry.last_interval.to_string(),
+        entry.interval.to_string(),
+        entry.ease_factor.to_string(),
+        entry.taken_millis.to_string(),
+        review_kind_name(entry.review_kind).to_string(),
+    ]
+}
+
+fn review_kind_name(kind: RevlogReviewKind) -> &'static str {
+    match kind {
+        RevlogReviewKind::Learning => "learning",
+        RevlogReviewKind::Review => "review",
+        RevlogReviewKind::Relearning => "relearning",
+        RevlogReviewKind::Filtered => "filtered",
+        RevlogReviewKind::Manual => "manual",
+    }
+}
+
+const CARD_STATS_COLUMNS: [&str; 19] = [
+    "card_id",
+    "note_id",
+    "deck",
+    "notetype",
+    "card_type",
+    "added",
+    "first_review",
+    "latest_review",
+    "due_date",
+    "due_position",
+    "interval",
+    "ease",
+    "reviews",
+    "lapses",
+    "average_secs",
+    "total_secs",
+    "stability",
+    "difficulty",
+    "retrievability",
+];
+
+/// Missing values are left empty.
+fn card_stats_record(stats: CardStatsResponse) -> [String; 19] {
+    fn optional(value: Option<impl ToString>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+    [
+        stats.card_id.to_string(),
+        stats.note_id.to_string(),
+        stats.deck,
+        stats.notetype,
+        stats.card_type,
+        stats.added.to_string(),
+        optional(stats.first_review),
+        optional(stats.latest_review),
+        optional(stats.due_date),
+        optional(stats.due_position),
+        stats.interval.to_string(),
+        stats.ease.to_string(),
+        stats.reviews.to_string(),
+        stats.lapses.to_string(),
+        stats.average_secs.to_string(),
+        stats.total_secs.to_string(),
+        optional(stats.memory_state.as_ref().map(|state| state.stability)),
+        optional(stats.memory_state.as_ref().map(|state| state.difficulty)),
+        optional(stats.fsrs_retrievability),
+    ]
+}
+
 fn write_file_header(writer: &mut impl Write, with_html: bool) -> Result<()> {
     writeln!(writer, "#separator:{}", DELIMITER.name())?;
     writeln!(writer, "#html:{with_html}")?;
@@ -282,3 +428,49 @@ impl From<&mut ExportNoteCsvRequest> for SearchNode {
         SearchNode::from(req.limit.take().unwrap_or_default())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::NoteAdder;
+
+    #[test]
+    fn should_export_revlog_and_card_stats() -> Result<()> {
+        let mut col = Collection::new();
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+        col.answer_again();
+        col.answer_good();
+
+        let dir = tempfile::tempdir()?;
+        let revlog_path = dir.path().join("revlog.txt");
+        let stats_path = dir.path().join("cards.txt");
+        let count = col.export_revlog_csv(
+            revlog_path.to_str().unwrap(),
+            "",
+            Some(stats_path.to_str().unwrap()),
+        )?;
+        assert_eq!(count, 2);
+
+        let revlog = anki_io::read_to_string(&revlog_path)?;
+        let rows: Vec<Vec<&str>> = revlog
+            .lines()
+            .map(|line| line.split('\t').collect())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], REVLOG_COLUMNS);
+        assert_eq!(rows[1][1], note.id.to_string());
+        assert_eq!(rows[1][2], "Default");
+        assert_eq!((rows[1][4], rows[1][9]), ("1", "learning"));
+        assert_eq!((rows[2][4], rows[2][9]), ("3", "learning"));
+
+        let stats = anki_io::read_to_string(&stats_path)?;
+        let rows: Vec<Vec<&str>> = stats
+            .lines()
+            .map(|line| line.split('\t').collect())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], CARD_STATS_COLUMNS);
+        assert_eq!(rows[1][12], "2");
+        Ok(())
+    }
+}
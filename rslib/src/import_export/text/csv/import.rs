@@ -8,6 +8,10 @@ use std::io::Seek;
 use std::io::SeekFrom;
 
 use anki_io::open_file;
+use anki_proto::import_export::ImportPreview;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
 
 use crate::import_export::text::csv::metadata::CsvDeck;
 use crate::import_export::text::csv::metadata::CsvMetadata;
@@ -15,6 +19,9 @@ use crate::import_export::text::csv::metadata::CsvMetadataHelpers;
 use crate::import_export::text::csv::metadata::CsvNotetype;
 use crate::import_export::text::csv::metadata::DelimeterExt;
 use crate::import_export::text::csv::metadata::Delimiter;
+use crate::import_export::text::csv::metadata::SchedulingColumns;
+use crate::import_export::text::csv::metadata::SchedulingColumnsExt;
+use crate::import_export::text::ForeignCard;
 use crate::import_export::text::ForeignData;
 use crate::import_export::text::ForeignNote;
 use crate::import_export::text::NameOrId;
@@ -32,6 +39,20 @@ impl Collection {
         data.notes = notes;
         data.import(self, progress)
     }
+
+    pub fn preview_import_csv(
+        &mut self,
+        path: &str,
+        metadata: CsvMetadata,
+    ) -> Result<ImportPreview> {
+        let progress = self.new_progress_handler();
+        let file = open_file(path)?;
+        let mut ctx = ColumnContext::new(&metadata)?;
+        let notes = ctx.deserialize_csv(file, metadata.delimiter())?;
+        let mut data = ForeignData::from(metadata);
+        data.notes = notes;
+        data.preview_import(self, progress)
+    }
 }
 
 impl From<CsvMetadata> for ForeignData {
@@ -100,8 +121,10 @@ pub(super) type FieldSourceColumns = Vec<Option<usize>>;
 struct ColumnContext {
     tags_column: Option<usize>,
     guid_column: Option<usize>,
+    mtime_column: Option<usize>,
     deck_column: Option<usize>,
     notetype_column: Option<usize>,
+    scheduling_columns: Option<SchedulingColumns>,
     /// Source column indices for the fields of a notetype
     field_source_columns: FieldSourceColumns,
     /// How fields are converted to strings. Used for escaping HTML if
@@ -114,8 +137,10 @@ impl ColumnContext {
         Ok(Self {
             tags_column: (metadata.tags_column > 0).then_some(metadata.tags_column as usize),
             guid_column: (metadata.guid_column > 0).then_some(metadata.guid_column as usize),
+            mtime_column: (metadata.mtime_column > 0).then_some(metadata.mtime_column as usize),
             deck_column: metadata.deck()?.column(),
             notetype_column: metadata.notetype()?.column(),
+            scheduling_columns: metadata.scheduling_columns.clone(),
             field_source_columns: metadata.field_source_columns()?,
             stringify: stringify_fn(metadata.is_html),
         })
@@ -150,6 +175,8 @@ impl ColumnContext {
             tags: self.gather_tags(record),
             deck: name_or_id_from_record_column(self.deck_column, record),
             guid: str_from_record_column(self.guid_column, record),
+            mtime: self.gather_mtime(record),
+            cards: self.gather_card(record).into_iter().collect(),
             ..Default::default()
         }
     }
@@ -163,6 +190,46 @@ impl ColumnContext {
         })
     }
 
+    fn gather_mtime(&self, record: &csv::StringRecord) -> Option<i64> {
+        self.mtime_column
+            .and_then(|i| record.get(i - 1))
+            .and_then(|s| s.trim().parse().ok())
+    }
+
+    /// None if the row has no scheduling information.
+    fn gather_card(&self, record: &csv::StringRecord) -> Option<ForeignCard> {
+        let columns = self.scheduling_columns.as_ref()?;
+        let get = |column: u32| {
+            (column > 0)
+                .then(|| record.get(column as usize - 1))
+                .flatten()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        if columns
+            .all()
+            .into_iter()
+            .all(|column| get(column).is_none())
+        {
+            return None;
+        }
+        // empty or invalid cells leave the existing card's value unchanged
+        Some(ForeignCard {
+            due: get(columns.due).and_then(parse_due),
+            interval: get(columns.interval).and_then(|s| s.parse().ok()),
+            ease_factor: get(columns.ease).and_then(parse_ease),
+            reps: get(columns.reps).and_then(|s| s.parse().ok()),
+            lapses: get(columns.lapses).and_then(|s| s.parse().ok()),
+            suspended: get(columns.suspended).map(parse_suspended),
+            flags: get(columns.flag)
+                .and_then(|s| s.parse().ok())
+                .filter(|&flag| flag <= 7),
+            stability: get(columns.stability).and_then(|s| s.parse().ok()),
+            difficulty: get(columns.difficulty).and_then(|s| s.parse().ok()),
+            ..Default::default()
+        })
+    }
+
     fn gather_note_fields(&self, record: &csv::StringRecord) -> Vec<Option<String>> {
         let stringify = self.stringify;
         self.field_source_columns
@@ -179,6 +246,36 @@ fn str_from_record_column(column: Option<usize>, record: &csv::StringRecord) ->
         .to_string()
 }
 
+/// A Unix timestamp in seconds, or a YYYY-MM-DD date, which is mapped to noon
+/// so that it falls on the same day regardless of the rollover hour.
+fn parse_due(text: &str) -> Option<i32> {
+    if let Ok(secs) = text.parse() {
+        return Some(secs);
+    }
+    let noon = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(12, 0, 0)?;
+    Local
+        .from_local_datetime(&noon)
+        .single()
+        .map(|datetime| datetime.timestamp() as i32)
+}
+
+/// A decimal fraction like 2.5, or a percentage like 250%.
+fn parse_ease(text: &str) -> Option<f32> {
+    match text.strip_suffix('%') {
+        Some(percentage) => percentage.trim().parse::<f32>().ok().map(|p| p / 100.),
+        None => text.parse().ok(),
+    }
+}
+
+fn parse_suspended(text: &str) -> bool {
+    matches!(
+        text.to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "suspended"
+    )
+}
+
 fn name_or_id_from_record_column(column: Option<usize>, record: &csv::StringRecord) -> NameOrId {
     NameOrId::parse(column.and_then(|i| record.get(i - 1)).unwrap_or_default())
 }
@@ -348,6 +445,47 @@ mod test {
         assert_eq!(notes[1].notetype, NameOrId::Name(String::from("Cloze")));
     }
 
+    #[test]
+    fn should_parse_scheduling_columns() {
+        let mut metadata = CsvMetadata::defaults_for_testing();
+        metadata.scheduling_columns = Some(SchedulingColumns {
+            interval: 3,
+            ease: 4,
+            suspended: 5,
+            flag: 6,
+            ..Default::default()
+        });
+        let notes = import!(
+            metadata,
+            "front,back,10,250%,yes,2
+foo,bar,,,,
+baz,qux,,,no,
+"
+        );
+        assert_eq!(
+            notes[0].cards,
+            [ForeignCard {
+                interval: Some(10),
+                ease_factor: Some(2.5),
+                suspended: Some(true),
+                flags: Some(2),
+                ..Default::default()
+            }]
+        );
+        // no scheduling, so imported as a new card
+        assert!(notes[1].cards.is_empty());
+        // only the provided values are set
+        assert_eq!(
+            notes[2].cards,
+            [ForeignCard {
+                suspended: Some(false),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(parse_ease("2.5"), Some(2.5));
+        assert!(parse_due("2024-05-01").is_some());
+    }
+
     #[test]
     fn should_ignore_bom() {
         let metadata = CsvMetadata::defaults_for_testing();
@@ -17,6 +17,7 @@ pub use anki_proto::import_export::csv_metadata::DupeResolution;
 pub use anki_proto::import_export::csv_metadata::MappedNotetype;
 pub use anki_proto::import_export::csv_metadata::MatchScope;
 pub use anki_proto::import_export::csv_metadata::Notetype as CsvNotetype;
+pub use anki_proto::import_export::csv_metadata::SchedulingColumns;
 pub use anki_proto::import_export::CsvMetadata;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
@@ -170,6 +171,11 @@ impl Collection {
                     metadata.guid_column = n;
                 }
             }
+            "mtime column" => {
+                if let Ok(n) = value.trim().parse() {
+                    metadata.mtime_column = n;
+                }
+            }
             "match scope" => {
                 if let Some(scope) = MatchScope::from_text(value) {
                     metadata.match_scope = scope as i32;
@@ -180,7 +186,14 @@ impl Collection {
                     metadata.dupe_resolution = resolution as i32;
                 }
             }
-            _ => (),
+            other => {
+                if let (Some(name), Ok(n)) = (other.strip_suffix(" column"), value.trim().parse()) {
+                    let mut columns = metadata.scheduling_columns.clone().unwrap_or_default();
+                    if columns.set_by_name(name, n) {
+                        metadata.scheduling_columns = Some(columns);
+                    }
+                }
+            }
         }
     }
 
@@ -297,6 +310,18 @@ impl CsvMetadataHelpers for CsvMetadata {
         if self.guid_column > 0 {
             columns.insert(self.guid_column as usize);
         }
+        if self.mtime_column > 0 {
+            columns.insert(self.mtime_column as usize);
+        }
+        if let Some(scheduling) = &self.scheduling_columns {
+            columns.extend(
+                scheduling
+                    .all()
+                    .into_iter()
+                    .filter(|&n| n > 0)
+                    .map(|n| n as usize),
+            );
+        }
         columns
     }
 }
@@ -314,6 +339,7 @@ impl DupeResolutionExt for DupeResolution {
     fn from_text(text: &str) -> Option<Self> {
         match text {
             "update current" => Some(Self::Update),
+            "update if newer" => Some(Self::UpdateIfNewer),
             "keep current" => Some(Self::Preserve),
             "keep both" => Some(Self::Duplicate),
             _ => None,
@@ -321,6 +347,46 @@ impl DupeResolutionExt for DupeResolution {
     }
 }
 
+pub(super) trait SchedulingColumnsExt {
+    /// Sets the column with the given header name, e.g. "due" for the
+    /// "due column" header. False if there is no such column.
+    fn set_by_name(&mut self, name: &str, column: u32) -> bool;
+    fn all(&self) -> [u32; 9];
+}
+
+impl SchedulingColumnsExt for SchedulingColumns {
+    fn set_by_name(&mut self, name: &str, column: u32) -> bool {
+        let field = match name {
+            "due" => &mut self.due,
+            "interval" => &mut self.interval,
+            "ease" => &mut self.ease,
+            "reps" => &mut self.reps,
+            "lapses" => &mut self.lapses,
+            "suspended" => &mut self.suspended,
+            "flag" => &mut self.flag,
+            "stability" => &mut self.stability,
+            "difficulty" => &mut self.difficulty,
+            _ => return false,
+        };
+        *field = column;
+        true
+    }
+
+    fn all(&self) -> [u32; 9] {
+        [
+            self.due,
+            self.interval,
+            self.ease,
+            self.reps,
+            self.lapses,
+            self.suspended,
+            self.flag,
+            self.stability,
+            self.difficulty,
+        ]
+    }
+}
+
 pub(super) trait MatchScopeExt: Sized {
     fn from_config(col: &Collection) -> Self;
     fn from_text(text: &str) -> Option<Self>;
@@ -624,6 +690,8 @@ pub(in crate::import_export) mod test {
                 force_is_html: false,
                 tags_column: 0,
                 guid_column: 0,
+                mtime_column: 0,
+                scheduling_columns: None,
                 global_tags: Vec::new(),
                 updated_tags: Vec::new(),
                 column_labels: vec!["".to_string(); 2],
@@ -834,6 +902,15 @@ pub(in crate::import_export) mod test {
         assert_eq!(meta.preview[1].vals, ["baz", ""]);
     }
 
+    #[test]
+    fn should_parse_scheduling_columns_and_exclude_them_from_field_mapping() {
+        let mut col = Collection::new();
+        let meta = metadata!(col, "#interval column:1\n#ease column:2\nfoo,bar,baz\n");
+        let scheduling = meta.scheduling_columns.as_ref().unwrap();
+        assert_eq!((scheduling.interval, scheduling.ease), (1, 2));
+        assert_eq!(meta.unwrap_notetype_map(), &[3, 0]);
+    }
+
     #[test]
     fn should_parse_first_first_line_despite_bom() {
         let mut col = Collection::new();
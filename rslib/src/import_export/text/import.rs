@@ -6,15 +6,20 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 
+use anki_proto::import_export::ImportPreview;
+
 use super::NameOrId;
 use crate::card::CardQueue;
 use crate::card::CardType;
+use crate::card::FsrsMemoryState;
 use crate::config::I32ConfigKey;
 use crate::import_export::text::DupeResolution;
 use crate::import_export::text::ForeignCard;
 use crate::import_export::text::ForeignData;
+use crate::import_export::text::ForeignFieldOptions;
 use crate::import_export::text::ForeignNote;
 use crate::import_export::text::ForeignNotetype;
+use crate::import_export::text::ForeignReview;
 use crate::import_export::text::ForeignTemplate;
 use crate::import_export::text::MatchScope;
 use crate::import_export::ImportProgress;
@@ -26,6 +31,8 @@ use crate::notetype::CardTemplate;
 use crate::notetype::NoteField;
 use crate::prelude::*;
 use crate::progress::ThrottlingProgressHandler;
+use crate::scheduler::states::review::INITIAL_EASE_FACTOR;
+use crate::scheduler::timing::SchedTimingToday;
 use crate::text::strip_html_preserving_media_filenames;
 
 impl ForeignData {
@@ -35,17 +42,35 @@ impl ForeignData {
         mut progress: ThrottlingProgressHandler<ImportProgress>,
     ) -> Result<OpOutput<NoteLog>> {
         progress.set(ImportProgress::File)?;
-        col.transact(Op::Import, |col| {
-            self.update_config(col)?;
-            let mut ctx = Context::new(&self, col)?;
-            ctx.import_foreign_notetypes(self.notetypes)?;
-            ctx.import_foreign_notes(
-                self.notes,
-                &self.global_tags,
-                &self.updated_tags,
-                &mut progress,
-            )
-        })
+        col.transact(Op::Import, |col| self.import_inner(col, progress))
+    }
+
+    /// Report what importing would change, without modifying the collection.
+    pub fn preview_import(
+        self,
+        col: &mut Collection,
+        mut progress: ThrottlingProgressHandler<ImportProgress>,
+    ) -> Result<ImportPreview> {
+        progress.set(ImportProgress::File)?;
+        col.preview_import(|col| self.import_inner(col, progress))
+    }
+
+    fn import_inner(
+        self,
+        col: &mut Collection,
+        mut progress: ThrottlingProgressHandler<ImportProgress>,
+    ) -> Result<NoteLog> {
+        self.update_config(col)?;
+        let mut ctx = Context::new(&self, col)?;
+        ctx.import_foreign_notetypes(self.notetypes)?;
+        let log = ctx.import_foreign_notes(
+            self.notes,
+            &self.global_tags,
+            &self.updated_tags,
+            &mut progress,
+        )?;
+        ctx.update_memory_states()?;
+        Ok(log)
     }
 
     fn update_config(&self, col: &mut Collection) -> Result<()> {
@@ -73,11 +98,14 @@ struct Context<'a> {
     deck_ids: DeckIdsByNameOrId,
     usn: Usn,
     normalize_notes: bool,
-    today: u32,
+    timing: SchedTimingToday,
     dupe_resolution: DupeResolution,
     card_gen_ctxs: HashMap<(NotetypeId, DeckId), CardGenContext<Arc<Notetype>>>,
     existing_checksums: ExistingChecksums,
     existing_guids: HashMap<String, NoteId>,
+    /// Cards that reviews were imported for, whose memory state must be
+    /// updated.
+    reviewed_cards: Vec<CardId>,
 }
 
 struct DeckIdsByNameOrId {
@@ -179,7 +207,7 @@ impl<'a> Context<'a> {
     fn new(data: &ForeignData, col: &'a mut Collection) -> Result<Self> {
         let usn = col.usn()?;
         let normalize_notes = col.get_config_bool(BoolKey::NormalizeNoteText);
-        let today = col.timing_today()?.days_elapsed;
+        let timing = col.timing_today()?;
         let mut notetypes = HashMap::new();
         notetypes.insert(
             NameOrId::default(),
@@ -193,19 +221,28 @@ impl<'a> Context<'a> {
             col,
             usn,
             normalize_notes,
-            today,
+            timing,
             dupe_resolution: data.dupe_resolution,
             notetypes,
             deck_ids,
             card_gen_ctxs: HashMap::new(),
             existing_checksums,
             existing_guids,
+            reviewed_cards: Vec::new(),
         })
     }
 
+    /// A notetype that would be added identical to an existing one of the same
+    /// name is skipped, so that importing an export again doesn't duplicate
+    /// its notetypes. Otherwise, notes would be matched against a new copy.
     fn import_foreign_notetypes(&mut self, notetypes: Vec<ForeignNotetype>) -> Result<()> {
         for foreign in notetypes {
             let mut notetype = foreign.into_native();
+            if let Some(existing) = self.col.get_notetype_by_name(&notetype.name)? {
+                if ForeignNotetype::from(existing.as_ref()) == ForeignNotetype::from(&notetype) {
+                    continue;
+                }
+            }
             notetype.usn = self.usn;
             self.col
                 .add_notetype_inner(&mut notetype, self.usn, false)?;
@@ -343,7 +380,9 @@ impl<'a> Context<'a> {
                 log.empty_first_field.push(ctx.note.into_log_note())
             }
             DupeResolution::Duplicate => self.add_note(ctx, log)?,
-            DupeResolution::Update => self.update_with_note(ctx, log)?,
+            DupeResolution::Update | DupeResolution::UpdateIfNewer => {
+                self.update_with_note(ctx, log)?
+            }
             DupeResolution::Preserve => log.first_field_match.push(ctx.note.into_log_note()),
         }
         Ok(())
@@ -351,12 +390,10 @@ impl<'a> Context<'a> {
 
     fn add_note(&mut self, ctx: NoteContext, log: &mut NoteLog) -> Result<()> {
         let mut note = Note::new(&ctx.notetype);
-        let mut cards = ctx
-            .note
-            .into_native(&mut note, ctx.deck_id, self.today, ctx.global_tags);
+        let cards = ctx.note.into_native(&mut note, ctx.global_tags);
         self.prepare_note(&mut note, &ctx.notetype)?;
         self.col.add_note_only_undoable(&mut note)?;
-        self.add_cards(&mut cards, &note, ctx.deck_id, ctx.notetype)?;
+        self.add_cards(&cards, &note, ctx.deck_id, ctx.notetype)?;
 
         if ctx.dupes.is_empty() {
             log.new.push(note.into_log_note());
@@ -369,12 +406,12 @@ impl<'a> Context<'a> {
 
     fn add_cards(
         &mut self,
-        cards: &mut [Card],
+        cards: &[ForeignCard],
         note: &Note,
         deck_id: DeckId,
         notetype: Arc<Notetype>,
     ) -> Result<()> {
-        self.import_cards(cards, note.id)?;
+        self.import_cards(cards, note.id, deck_id)?;
         self.generate_missing_cards(notetype, deck_id, note)
     }
 
@@ -387,21 +424,24 @@ impl<'a> Context<'a> {
             }
 
             let mut note = dupe.note.clone();
-            let mut cards = ctx.note.clone().into_native(
+            let cards = ctx.note.clone().into_native(
                 &mut note,
-                ctx.deck_id,
-                self.today,
                 ctx.global_tags.iter().chain(ctx.updated_tags.iter()),
             );
 
             if dupe.identical {
                 update_result.update(DuplicateUpdateResult::Identical(dupe));
+            } else if self.dupe_resolution == DupeResolution::UpdateIfNewer
+                && !ctx.note.is_newer_than(&dupe.note)
+            {
+                update_result.update(DuplicateUpdateResult::Outdated(dupe));
+                continue;
             } else {
                 self.prepare_note(&mut note, &ctx.notetype)?;
                 self.col.update_note_undoable(&note, &dupe.note)?;
                 update_result.update(DuplicateUpdateResult::Update(dupe));
             }
-            self.add_cards(&mut cards, &note, ctx.deck_id, ctx.notetype.clone())?;
+            self.add_cards(&cards, &note, ctx.deck_id, ctx.notetype.clone())?;
         }
         update_result.log(log);
 
@@ -415,14 +455,63 @@ impl<'a> Context<'a> {
         Ok(())
     }
 
-    fn import_cards(&mut self, cards: &mut [Card], note_id: NoteId) -> Result<()> {
-        for card in cards {
-            card.note_id = note_id;
-            self.col.add_card(card)?;
+    /// Existing cards of the same template are updated with the imported
+    /// scheduling, and missing ones are added.
+    fn import_cards(
+        &mut self,
+        cards: &[ForeignCard],
+        note_id: NoteId,
+        deck_id: DeckId,
+    ) -> Result<()> {
+        let existing = self.col.storage.all_cards_of_note(note_id)?;
+        for foreign in cards {
+            let template_idx = foreign.template_index.unwrap_or_default();
+            let original = existing.iter().find(|c| c.template_idx == template_idx);
+            let mut card = original
+                .cloned()
+                .unwrap_or_else(|| Card::new(note_id, template_idx, deck_id, 0));
+            foreign.apply_to(&mut card, self.timing);
+            if card.ctype == CardType::New
+                && foreign.position.is_none()
+                && original.map_or(true, |c| c.ctype != CardType::New)
+            {
+                card.due = self.col.get_and_update_next_card_position()? as i32;
+            }
+            if let Some(original) = original {
+                if card != *original {
+                    self.col
+                        .update_card_inner(&mut card, original.clone(), self.usn)?;
+                }
+            } else {
+                self.col.add_card(&mut card)?;
+            }
+            self.import_reviews(card.id, &foreign.reviews)?;
         }
         Ok(())
     }
 
+    /// Like in the apkg importer, reviews are identified by their timestamp,
+    /// and skipped if their id is already taken. Changing the id of a
+    /// colliding review would make it a duplicate on the next import.
+    fn import_reviews(&mut self, card_id: CardId, reviews: &[ForeignReview]) -> Result<()> {
+        if reviews.is_empty() {
+            return Ok(());
+        }
+        for review in reviews {
+            self.col
+                .add_revlog_entry_if_unique_undoable(review.into_native(card_id, self.usn))?;
+        }
+        self.reviewed_cards.push(card_id);
+        Ok(())
+    }
+
+    fn update_memory_states(&mut self) -> Result<()> {
+        if self.reviewed_cards.is_empty() || !self.col.get_config_bool(BoolKey::Fsrs) {
+            return Ok(());
+        }
+        self.col.update_memory_state_of_cards(&self.reviewed_cards)
+    }
+
     fn generate_missing_cards(
         &mut self,
         notetype: Arc<Notetype>,
@@ -443,6 +532,7 @@ impl<'a> Context<'a> {
 enum DuplicateUpdateResult {
     None,
     Conflicting(Duplicate),
+    Outdated(Duplicate),
     Identical(Duplicate),
     Update(Duplicate),
 }
@@ -452,8 +542,9 @@ impl DuplicateUpdateResult {
         match self {
             DuplicateUpdateResult::None => 0,
             DuplicateUpdateResult::Conflicting(_) => 1,
-            DuplicateUpdateResult::Identical(_) => 2,
-            DuplicateUpdateResult::Update(_) => 3,
+            DuplicateUpdateResult::Outdated(_) => 2,
+            DuplicateUpdateResult::Identical(_) => 3,
+            DuplicateUpdateResult::Update(_) => 4,
         }
     }
 
@@ -469,6 +560,7 @@ impl DuplicateUpdateResult {
             DuplicateUpdateResult::Conflicting(dupe) => {
                 log.conflicting.push(dupe.note.into_log_note())
             }
+            DuplicateUpdateResult::Outdated(dupe) => log.outdated.push(dupe.note.into_log_note()),
             DuplicateUpdateResult::Identical(dupe) => log.duplicate.push(dupe.note.into_log_note()),
             DuplicateUpdateResult::Update(dupe) if dupe.first_field_match => {
                 log.first_field_match.push(dupe.note.into_log_note())
@@ -540,14 +632,13 @@ impl Collection {
 }
 
 impl ForeignNote {
-    /// Updates a native note with the foreign data and returns its new cards.
+    /// Updates a native note with the foreign data and returns its cards, with
+    /// their template indices set.
     fn into_native<'tags>(
         self,
         note: &mut Note,
-        deck_id: DeckId,
-        today: u32,
         extra_tags: impl IntoIterator<Item = &'tags String>,
-    ) -> Vec<Card> {
+    ) -> Vec<ForeignCard> {
         // TODO: Handle new and learning cards
         if !self.guid.is_empty() {
             note.guid = self.guid;
@@ -567,10 +658,18 @@ impl ForeignNote {
         self.cards
             .into_iter()
             .enumerate()
-            .map(|(idx, c)| c.into_native(NoteId(0), idx as u16, deck_id, today))
+            .map(|(idx, card)| ForeignCard {
+                template_index: Some(card.template_index.unwrap_or(idx as u16)),
+                ..card
+            })
             .collect()
     }
 
+    /// Notes without a modification time are never considered newer.
+    fn is_newer_than(&self, note: &Note) -> bool {
+        self.mtime.map_or(false, |mtime| mtime > note.mtime.0)
+    }
+
     fn first_field_is_the_empty_string(&self) -> bool {
         matches!(self.fields.get(0), Some(Some(s)) if s.is_empty())
     }
@@ -611,58 +710,198 @@ impl ForeignNote {
 }
 
 impl ForeignCard {
-    fn into_native(self, note_id: NoteId, template_idx: u16, deck_id: DeckId, today: u32) -> Card {
-        Card {
-            note_id,
-            template_idx,
-            deck_id,
-            due: self.native_due(today),
-            interval: self.interval,
-            ease_factor: (self.ease_factor * 1000.).round() as u16,
-            reps: self.reps,
-            lapses: self.lapses,
-            ctype: CardType::Review,
-            queue: CardQueue::Review,
-            ..Default::default()
+    /// Overwrites the card's scheduling with the values that are set. The card
+    /// is only reset to new if an interval of 0 is set, and only moved into
+    /// (re)learning if remaining steps are set.
+    fn apply_to(&self, card: &mut Card, timing: SchedTimingToday) {
+        let original_type = card.ctype;
+        let was_suspended = card.queue == CardQueue::Suspended;
+        match self.interval {
+            Some(0) => {
+                card.ctype = CardType::New;
+                card.interval = 0;
+                card.ease_factor = 0;
+                card.remaining_steps = 0;
+                card.memory_state = None;
+            }
+            Some(interval) => {
+                if card.ctype != CardType::Review {
+                    card.due = timing.days_elapsed as i32;
+                }
+                if card.ease_factor == 0 {
+                    card.ease_factor = (INITIAL_EASE_FACTOR * 1000.0).round() as u16;
+                }
+                card.ctype = CardType::Review;
+                card.interval = interval;
+            }
+            None => (),
+        }
+        if let Some(remaining_steps) = self.remaining_steps {
+            if matches!(original_type, CardType::New | CardType::Review) {
+                card.due = TimestampSecs::now().0 as i32;
+            }
+            card.ctype = if card.interval > 0 {
+                CardType::Relearn
+            } else {
+                CardType::Learn
+            };
+            card.remaining_steps = remaining_steps;
+            if let Some(due) = self.due {
+                card.due = native_learning_due(due, timing);
+            }
+        }
+        match card.ctype {
+            CardType::New => {
+                if let Some(position) = self.position {
+                    card.due = position as i32;
+                }
+            }
+            CardType::Learn => {
+                if let Some(position) = self.position {
+                    card.original_position = Some(position);
+                }
+            }
+            CardType::Review => {
+                if let Some(due) = self.due {
+                    card.due = native_due(due, timing);
+                }
+            }
+            CardType::Relearn => (),
+        }
+        if matches!(card.ctype, CardType::Review | CardType::Relearn) {
+            if let Some(ease_factor) = self.ease_factor {
+                card.ease_factor = (ease_factor * 1000.).round() as u16;
+            }
+        }
+        if let Some(reps) = self.reps {
+            card.reps = reps;
+        }
+        if let Some(lapses) = self.lapses {
+            card.lapses = lapses;
+        }
+        if let Some(flags) = self.flags {
+            card.flags = flags;
+        }
+        if let Some(memory_state) = self.memory_state().filter(|_| card.ctype != CardType::New) {
+            card.memory_state = Some(memory_state);
+        }
+        if card.ctype != original_type || self.suspended.is_some() || self.remaining_steps.is_some()
+        {
+            if self.suspended.unwrap_or(was_suspended) {
+                card.queue = CardQueue::Suspended;
+            } else {
+                card.restore_queue_from_type();
+            }
         }
     }
 
-    fn native_due(self, today: u32) -> i32 {
-        let remaining_secs = self.interval as i64 - TimestampSecs::now().0;
-        let remaining_days = remaining_secs / (60 * 60 * 24);
-        0.max(remaining_days as i32 + today as i32)
+    /// None unless both values are set and in FSRS's range.
+    fn memory_state(&self) -> Option<FsrsMemoryState> {
+        Some(FsrsMemoryState {
+            stability: self
+                .stability
+                .filter(|&stability| stability.is_finite() && stability > 0.0)?,
+            difficulty: self
+                .difficulty
+                .filter(|difficulty| (1.0..=10.0).contains(difficulty))?,
+        })
+    }
+}
+
+/// Converts a Unix timestamp into a day number.
+fn native_due(due: i32, timing: SchedTimingToday) -> i32 {
+    let day_start = timing.next_day_at.adding_secs(-86_400);
+    let remaining_days = (due as i64 - day_start.0).div_euclid(86_400);
+    0.max(remaining_days as i32 + timing.days_elapsed as i32)
+}
+
+/// Learning steps due today keep their exact time, later ones are converted
+/// into a day number.
+fn native_learning_due(due: i32, timing: SchedTimingToday) -> i32 {
+    if (due as i64) < timing.next_day_at.0 {
+        due
+    } else {
+        native_due(due, timing)
     }
 }
 
 impl ForeignNotetype {
     fn into_native(self) -> Notetype {
+        let mut config = if self.is_cloze {
+            Notetype::new_cloze_config()
+        } else {
+            Notetype::new_config()
+        };
+        if let Some(css) = self.css {
+            config.css = css;
+        }
+        if let Some(latex_pre) = self.latex_pre {
+            config.latex_pre = latex_pre;
+        }
+        if let Some(latex_post) = self.latex_post {
+            config.latex_post = latex_post;
+        }
+        if let Some(latex_svg) = self.latex_svg {
+            config.latex_svg = latex_svg;
+        }
+        if let Some(sort_field_index) = self.sort_field_index {
+            config.sort_field_idx = sort_field_index;
+        }
+        let mut field_options = self.field_options.into_iter();
         Notetype {
             name: self.name,
-            fields: self.fields.into_iter().map(NoteField::new).collect(),
+            fields: self
+                .fields
+                .into_iter()
+                .map(|name| {
+                    let mut field = NoteField::new(name);
+                    if let Some(options) = field_options.next() {
+                        options.apply_to(&mut field);
+                    }
+                    field
+                })
+                .collect(),
             templates: self
                 .templates
                 .into_iter()
                 .map(ForeignTemplate::into_native)
                 .collect(),
-            config: if self.is_cloze {
-                Notetype::new_cloze_config()
-            } else {
-                Notetype::new_config()
-            },
+            config,
             ..Notetype::default()
         }
     }
 }
 
+impl ForeignFieldOptions {
+    fn apply_to(self, field: &mut NoteField) {
+        if let Some(font_name) = self.font_name {
+            field.config.font_name = font_name;
+        }
+        if let Some(font_size) = self.font_size {
+            field.config.font_size = font_size;
+        }
+        field.config.description = self.description;
+        field.config.sticky = self.sticky;
+        field.config.rtl = self.rtl;
+        field.config.plain_text = self.plain_text;
+        field.config.collapsed = self.collapsed;
+        field.config.exclude_from_search = self.exclude_from_search;
+    }
+}
+
 impl ForeignTemplate {
     fn into_native(self) -> CardTemplate {
-        CardTemplate::new(self.name, self.qfmt, self.afmt)
+        let mut template = CardTemplate::new(self.name, self.qfmt, self.afmt);
+        template.config.q_format_browser = self.browser_qfmt;
+        template.config.a_format_browser = self.browser_afmt;
+        template
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::revlog::RevlogReviewKind;
     use crate::tests::DeckAdder;
     use crate::tests::NoteAdder;
 
@@ -731,6 +970,33 @@ mod test {
         assert_eq!(col.storage.get_all_notes()[0].fields()[1], "new");
     }
 
+    #[test]
+    fn should_only_update_note_if_newer_if_dupe_mode_is_update_if_newer() {
+        let mut col = Collection::new();
+        let mut data = ForeignData::with_defaults();
+        data.add_note(&["same", "old"]);
+        data.dupe_resolution = DupeResolution::UpdateIfNewer;
+        let progress = col.new_progress_handler();
+        data.clone().import(&mut col, progress).unwrap();
+        let mtime = col.storage.get_all_notes()[0].mtime.0;
+
+        // no or an older mtime, so the existing note is kept
+        data.notes[0].fields[1].replace("new".to_string());
+        for incoming in [None, Some(mtime - 1)] {
+            data.notes[0].mtime = incoming;
+            let progress = col.new_progress_handler();
+            let log = data.clone().import(&mut col, progress).unwrap().output;
+            assert_eq!(log.outdated.len(), 1);
+            assert_eq!(col.storage.get_all_notes()[0].fields()[1], "old");
+        }
+
+        data.notes[0].mtime = Some(mtime + 1);
+        let progress = col.new_progress_handler();
+        let log = data.import(&mut col, progress).unwrap().output;
+        assert_eq!(log.updated.len(), 1);
+        assert_eq!(col.storage.get_all_notes()[0].fields()[1], "new");
+    }
+
     #[test]
     fn should_keep_old_field_content_if_no_new_one_is_supplied() {
         let mut col = Collection::new();
@@ -820,4 +1086,145 @@ mod test {
         // other deck, should be unchanged
         assert_eq!(notes[1].fields()[1], "old");
     }
+
+    #[test]
+    fn should_import_reviews_and_compute_memory_state() {
+        let mut col = Collection::new();
+        col.set_config_bool(BoolKey::Fsrs, true, false).unwrap();
+        let mut data = ForeignData::with_defaults();
+        data.dupe_resolution = DupeResolution::Update;
+        data.add_note(&["foo", "bar"]);
+        let day = 86_400_000;
+        let now = TimestampMillis::now().0;
+        let review = |days_ago: i64, interval: i32, kind: RevlogReviewKind| ForeignReview {
+            time: now - days_ago * day,
+            rating: 3,
+            interval,
+            kind,
+            ..Default::default()
+        };
+        data.notes[0].cards.push(ForeignCard {
+            due: Some(TimestampSecs::now().0 as i32),
+            interval: Some(3),
+            ease_factor: Some(2.5),
+            reviews: vec![
+                review(4, 1, RevlogReviewKind::Learning),
+                review(3, 3, RevlogReviewKind::Review),
+            ],
+            ..Default::default()
+        });
+        let progress = col.new_progress_handler();
+        data.clone().import(&mut col, progress).unwrap();
+        let card = col.storage.get_all_cards().remove(0);
+        let revlog = col.storage.get_revlog_entries_for_card(card.id).unwrap();
+        assert_eq!(revlog.len(), 2);
+        assert_eq!(revlog[1].review_kind, RevlogReviewKind::Review);
+        assert!(card.memory_state.is_some());
+
+        // importing again updates the card without duplicating its reviews
+        let progress = col.new_progress_handler();
+        data.import(&mut col, progress).unwrap();
+        assert_eq!(col.storage.get_all_cards().len(), 1);
+        let revlog = col.storage.get_revlog_entries_for_card(card.id).unwrap();
+        assert_eq!(revlog.len(), 2);
+    }
+
+    #[test]
+    fn should_not_duplicate_reviews_with_colliding_ids() {
+        let mut col = Collection::new();
+        let mut data = ForeignData::with_defaults();
+        data.dupe_resolution = DupeResolution::Update;
+        let review = ForeignReview {
+            time: TimestampMillis::now().0,
+            rating: 3,
+            interval: 1,
+            ..Default::default()
+        };
+        for field in ["foo", "bar"] {
+            data.add_note(&[field, ""]);
+            data.notes.last_mut().unwrap().cards.push(ForeignCard {
+                reviews: vec![review],
+                ..Default::default()
+            });
+        }
+        for _ in 0..2 {
+            let progress = col.new_progress_handler();
+            data.clone().import(&mut col, progress).unwrap();
+            let revlog = col.storage.get_all_revlog_entries_in_card_order().unwrap();
+            assert_eq!(revlog.len(), 1);
+            assert_eq!(revlog[0].id, RevlogId(review.time));
+        }
+    }
+
+    #[test]
+    fn should_convert_due_timestamps_into_days() {
+        let timing = Collection::new().timing_today().unwrap();
+        let today = timing.days_elapsed as i32;
+        let day_start = timing.next_day_at.adding_secs(-86_400).0 as i32;
+        assert_eq!(native_due(day_start, timing), today);
+        assert_eq!(native_due(day_start + 3 * 86_400 - 1, timing), today + 2);
+        assert_eq!(native_due(0, timing), 0);
+        // learning steps keep their exact time if due today
+        assert_eq!(native_learning_due(day_start + 10, timing), day_start + 10);
+        assert_eq!(
+            native_learning_due(timing.next_day_at.0 as i32, timing),
+            today + 1
+        );
+    }
+
+    #[test]
+    fn should_only_update_provided_scheduling() {
+        let mut col = Collection::new();
+        let mut data = ForeignData::with_defaults();
+        data.dupe_resolution = DupeResolution::Update;
+        data.add_note(&["foo", "bar"]);
+        data.notes[0].cards.push(ForeignCard {
+            interval: Some(3),
+            ease_factor: Some(2.0),
+            suspended: Some(true),
+            flags: Some(2),
+            stability: Some(3.0),
+            difficulty: Some(5.0),
+            ..Default::default()
+        });
+        let progress = col.new_progress_handler();
+        data.clone().import(&mut col, progress).unwrap();
+        let imported = col.storage.get_all_cards().remove(0);
+        assert_eq!(imported.ctype, CardType::Review);
+        assert_eq!(imported.queue, CardQueue::Suspended);
+
+        // only a flag and an out-of-range memory state, so the card stays a
+        // suspended review card
+        data.notes[0].fields[1] = Some("baz".into());
+        data.notes[0].cards[0] = ForeignCard {
+            flags: Some(1),
+            stability: Some(3.0),
+            difficulty: Some(11.0),
+            ..Default::default()
+        };
+        let progress = col.new_progress_handler();
+        data.clone().import(&mut col, progress).unwrap();
+        let card = col.storage.get_all_cards().remove(0);
+        assert_eq!(
+            card,
+            Card {
+                flags: 1,
+                mtime: card.mtime,
+                usn: card.usn,
+                ..imported
+            }
+        );
+
+        // an interval of 0 resets the card
+        data.notes[0].cards[0] = ForeignCard {
+            interval: Some(0),
+            suspended: Some(false),
+            ..Default::default()
+        };
+        let progress = col.new_progress_handler();
+        data.import(&mut col, progress).unwrap();
+        let card = col.storage.get_all_cards().remove(0);
+        assert_eq!((card.ctype, card.queue), (CardType::New, CardQueue::New));
+        assert_eq!(card.memory_state, None);
+    }
 }
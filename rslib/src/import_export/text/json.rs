@@ -1,11 +1,27 @@
 // Copyright: Ankitects Pty Ltd and contributors
 // License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
 
+use std::collections::HashMap;
+
 use anki_io::read_file;
+use anki_io::write_file;
+use anki_proto::import_export::ExportJsonRequest;
+use anki_proto::import_export::ImportPreview;
 
+use super::DupeResolution;
+use super::ForeignCard;
+use super::ForeignNote;
+use super::MatchScope;
+use super::NameOrId;
+use crate::card::CardQueue;
+use crate::card::CardType;
 use crate::import_export::text::ForeignData;
+use crate::import_export::ExportProgress;
 use crate::import_export::NoteLog;
 use crate::prelude::*;
+use crate::scheduler::timing::is_unix_epoch_timestamp;
+use crate::scheduler::timing::SchedTimingToday;
+use crate::search::SearchNode;
 
 impl Collection {
     pub fn import_json_file(&mut self, path: &str) -> Result<OpOutput<NoteLog>> {
@@ -20,4 +36,287 @@ impl Collection {
         let data: ForeignData = serde_json::from_str(json)?;
         data.import(self, progress)
     }
+
+    pub fn preview_import_json_file(&mut self, path: &str) -> Result<ImportPreview> {
+        let progress = self.new_progress_handler();
+        let slice = read_file(path)?;
+        let data: ForeignData = serde_json::from_slice(&slice)?;
+        data.preview_import(self, progress)
+    }
+
+    pub fn preview_import_json_string(&mut self, json: &str) -> Result<ImportPreview> {
+        let progress = self.new_progress_handler();
+        let data: ForeignData = serde_json::from_str(json)?;
+        data.preview_import(self, progress)
+    }
+
+    /// Notes are exported to be updated in place when the file is imported
+    /// again.
+    pub fn export_json(&mut self, mut request: ExportJsonRequest) -> Result<usize> {
+        let mut progress = self.new_progress_handler::<ExportProgress>();
+        let mut incrementor = progress.incrementor(ExportProgress::Notes);
+
+        let timing = self.timing_today()?;
+        let guard = self
+            .search_notes_into_table(SearchNode::from(request.limit.take().unwrap_or_default()))?;
+        let notetypes = guard.col.get_all_notetypes_of_search_notes()?;
+        let deck_ids = guard.col.storage.all_decks_of_search_notes()?;
+        let deck_names: HashMap<DeckId, String> =
+            HashMap::from_iter(guard.col.storage.get_all_deck_names()?);
+        let mut notes = vec![];
+        guard.col.storage.for_each_note_in_search(|note| {
+            incrementor.increment()?;
+            let mut cards = vec![];
+            if request.with_scheduling {
+                for card in guard.col.storage.all_cards_of_note(note.id)? {
+                    let mut foreign = ForeignCard::from_card(&card, timing);
+                    foreign.reviews = guard
+                        .col
+                        .storage
+                        .get_revlog_entries_for_card(card.id)?
+                        .iter()
+                        .map(Into::into)
+                        .collect();
+                    cards.push(foreign);
+                }
+            }
+            notes.push(ForeignNote {
+                fields: note.fields().iter().cloned().map(Some).collect(),
+                tags: Some(note.tags.clone()),
+                notetype: notetypes
+                    .get(&note.notetype_id)
+                    .map(|nt| NameOrId::Name(nt.name.clone()))
+                    .unwrap_or_default(),
+                deck: deck_ids
+                    .get(&note.id)
+                    .and_then(|did| deck_names.get(did))
+                    .map(|name| NameOrId::Name(name.clone()))
+                    .unwrap_or_default(),
+                guid: note.guid,
+                cards,
+                mtime: Some(note.mtime.0),
+            });
+            Ok(())
+        })?;
+        let data = ForeignData {
+            dupe_resolution: DupeResolution::Update,
+            match_scope: MatchScope::Notetype,
+            notes,
+            notetypes: notetypes.values().map(|nt| nt.as_ref().into()).collect(),
+            ..Default::default()
+        };
+        write_file(&request.out_path, serde_json::to_vec(&data)?)?;
+
+        Ok(incrementor.count())
+    }
+}
+
+impl ForeignCard {
+    /// Cards in filtered decks are exported with the scheduling they have in
+    /// their home deck. Burying is not exported, as it's lifted on the next day
+    /// anyway.
+    fn from_card(card: &Card, timing: SchedTimingToday) -> Self {
+        let mut card = card.clone();
+        card.remove_from_filtered_deck_restoring_queue();
+        let day_start = timing.next_day_at.adding_secs(-86_400);
+        let day_to_secs =
+            |day: i32| (day_start.0 + (day as i64 - timing.days_elapsed as i64) * 86_400) as i32;
+        let (due, position, remaining_steps) = match card.ctype {
+            CardType::New => (None, Some(card.due as u32), None),
+            CardType::Learn | CardType::Relearn => {
+                let due = if is_unix_epoch_timestamp(card.due) {
+                    card.due
+                } else {
+                    day_to_secs(card.due)
+                };
+                (
+                    Some(due),
+                    card.original_position,
+                    Some(card.remaining_steps),
+                )
+            }
+            CardType::Review => (Some(day_to_secs(card.due)), None, None),
+        };
+        ForeignCard {
+            template_index: Some(card.template_idx),
+            due,
+            interval: Some(card.interval),
+            position,
+            remaining_steps,
+            ease_factor: (card.ease_factor != 0).then(|| card.ease_factor as f32 / 1000.),
+            reps: Some(card.reps),
+            lapses: Some(card.lapses),
+            suspended: Some(card.queue == CardQueue::Suspended),
+            flags: Some(card.flags),
+            stability: card.memory_state.map(|state| state.stability),
+            difficulty: card.memory_state.map(|state| state.difficulty),
+            reviews: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::import_export::text::ForeignNotetype;
+    use crate::tests::DeckAdder;
+    use crate::tests::NoteAdder;
+
+    #[test]
+    fn exported_notes_round_trip() -> Result<()> {
+        let mut col = Collection::new();
+        let deck = DeckAdder::new("parent::child").add(&mut col);
+        let note = NoteAdder::basic(&mut col)
+            .fields(&["front", "back"])
+            .deck(deck.id)
+            .add(&mut col);
+        let cids = col.storage.card_ids_of_notes(&[note.id])?;
+        col.set_due_date(&cids, "5", None)?;
+        let card = col.storage.all_cards_of_note(note.id)?.remove(0);
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notes.json");
+        let request = ExportJsonRequest {
+            out_path: path.to_string_lossy().into(),
+            with_scheduling: true,
+            limit: None,
+        };
+        assert_eq!(col.export_json(request.clone())?, 1);
+
+        // into a new collection
+        let mut new_col = Collection::new();
+        new_col.import_json_file(&request.out_path)?;
+        let imported = new_col.storage.get_all_notes();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].guid, note.guid);
+        assert_eq!(imported[0].fields(), note.fields());
+        let imported_card = new_col.storage.all_cards_of_note(imported[0].id)?.remove(0);
+        assert_eq!(imported_card.due, card.due);
+        assert_eq!(imported_card.interval, card.interval);
+        assert_eq!(imported_card.ease_factor, card.ease_factor);
+        assert_eq!(
+            new_col
+                .get_deck(imported_card.deck_id)?
+                .unwrap()
+                .human_name(),
+            "parent::child"
+        );
+
+        // back into the same collection, updating the note and card in place
+        let json = anki_io::read_to_string(&path)?.replace("back", "edited");
+        col.set_due_date(&[card.id], "20", None)?;
+        let notetypes = col.storage.get_all_notetype_names()?.len();
+        col.import_json_string(&json)?;
+        assert_eq!(col.storage.get_all_notetype_names()?.len(), notetypes);
+        let notes = col.storage.get_all_notes();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].fields()[1], "edited");
+        let cards = col.storage.all_cards_of_note(note.id)?;
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].due, card.due);
+
+        Ok(())
+    }
+
+    #[test]
+    fn all_card_types_and_notetype_options_round_trip() -> Result<()> {
+        let mut col = Collection::new();
+        let mut notetype = (*col.get_notetype_by_name("Basic")?.unwrap()).clone();
+        notetype.name = "Exported".into();
+        notetype.config.css = ".card { color: red; }".into();
+        notetype.config.latex_pre = "pre".into();
+        notetype.config.sort_field_idx = 1;
+        notetype.fields[1].config.font_size = 30;
+        notetype.fields[1].config.sticky = true;
+        notetype.templates[0].config.q_format_browser = "{{Back}}".into();
+        col.update_notetype(&mut notetype, false)?;
+        let timing = col.timing_today()?;
+        let now = TimestampSecs::now().0 as i32;
+        let mut cards = vec![];
+        let states = [
+            (CardType::New, CardQueue::New, 42, 0, 0, None),
+            (CardType::Learn, CardQueue::Learn, now - 60, 0, 2, Some(7)),
+            (
+                CardType::Relearn,
+                CardQueue::DayLearn,
+                timing.days_elapsed as i32 + 1,
+                5,
+                1,
+                None,
+            ),
+            (
+                CardType::Review,
+                CardQueue::Suspended,
+                timing.days_elapsed as i32 + 3,
+                10,
+                0,
+                None,
+            ),
+        ];
+        for (idx, (ctype, queue, due, interval, remaining_steps, original_position)) in
+            states.into_iter().enumerate()
+        {
+            let note = NoteAdder::new(&notetype)
+                .fields(&[&idx.to_string(), "back"])
+                .add(&mut col);
+            let mut card = col.storage.all_cards_of_note(note.id)?.remove(0);
+            card.ctype = ctype;
+            card.queue = queue;
+            card.due = due;
+            card.interval = interval;
+            card.ease_factor = if interval > 0 { 2300 } else { 0 };
+            card.remaining_steps = remaining_steps;
+            card.original_position = original_position;
+            card.reps = 3;
+            card.lapses = 1;
+            card.flags = 2;
+            col.storage.update_card(&card)?;
+            cards.push((note.guid, card));
+        }
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("notes.json");
+        col.export_json(ExportJsonRequest {
+            out_path: path.to_string_lossy().into(),
+            with_scheduling: true,
+            limit: None,
+        })?;
+        let mut new_col = Collection::new();
+        new_col.import_json_file(&path.to_string_lossy())?;
+
+        let imported = new_col.get_notetype_by_name("Exported")?.unwrap();
+        assert_eq!(
+            ForeignNotetype::from(imported.as_ref()),
+            ForeignNotetype::from(&notetype)
+        );
+        for (guid, card) in cards {
+            let note = new_col
+                .storage
+                .get_all_notes()
+                .into_iter()
+                .find(|note| note.guid == guid)
+                .unwrap();
+            let imported = new_col.storage.all_cards_of_note(note.id)?.remove(0);
+            let scheduling = |card: &Card| {
+                (
+                    card.ctype,
+                    card.queue,
+                    card.due,
+                    card.interval,
+                    card.ease_factor,
+                    card.remaining_steps,
+                    card.original_position,
+                    (card.reps, card.lapses, card.flags),
+                )
+            };
+            assert_eq!(scheduling(&imported), scheduling(&card));
+        }
+
+        // importing again doesn't duplicate the notetype
+        let notetypes = new_col.storage.get_all_notetype_names()?.len();
+        new_col.import_json_file(&path.to_string_lossy())?;
+        assert_eq!(new_col.storage.get_all_notetype_names()?.len(), notetypes);
+
+        Ok(())
+    }
 }
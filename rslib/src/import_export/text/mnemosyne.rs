@@ -0,0 +1,493 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Importing of Mnemosyne collections.
+//!
+//! Some notes about their structure:
+//! https://github.com/mnemosyne-proj/mnemosyne/blob/master/mnemosyne/libmnemosyne/docs/source/index.rst
+//!
+//! Anki      | Mnemosyne
+//! ----------+-----------
+//! Note      | Fact
+//! Card Type | Fact View
+//! Card      | Card
+//! Notetype  | Card Type
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Captures;
+use regex::Regex;
+use rusqlite::Connection;
+use rusqlite::OpenFlags;
+
+use super::ForeignCard;
+use super::ForeignData;
+use super::ForeignNote;
+use super::ForeignNotetype;
+use super::ForeignReview;
+use super::ForeignTemplate;
+use super::NameOrId;
+use crate::import_export::ImportError;
+use crate::import_export::NoteLog;
+use crate::prelude::*;
+use crate::revlog::RevlogReviewKind;
+
+/// The log event of a card being graded.
+const REPETITION_EVENT: i64 = 9;
+
+impl Collection {
+    /// Notes are added to the provided deck.
+    pub fn import_mnemosyne(&mut self, path: &str, deck_id: DeckId) -> Result<OpOutput<NoteLog>> {
+        let progress = self.new_progress_handler();
+        let data = ForeignData::from_mnemosyne(Path::new(path), deck_id)?;
+        data.import(self, progress)
+    }
+}
+
+/// The data [Collection::import_mnemosyne] would import, as JSON for
+/// [Collection::import_json_string].
+pub fn mnemosyne_foreign_data_json(path: &str, deck_id: DeckId) -> Result<String> {
+    let data = ForeignData::from_mnemosyne(Path::new(path), deck_id)?;
+    Ok(serde_json::to_string(&data)?)
+}
+
+impl ForeignData {
+    fn from_mnemosyne(path: &Path, deck_id: DeckId) -> Result<Self> {
+        let db = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        // any Mnemosyne database has this table
+        db.query_row(
+            "SELECT value FROM global_variables WHERE key = 'version'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .map_err(|_| AnkiError::from(ImportError::Corrupt))?;
+
+        let mut facts = gather_facts(&db)?;
+        gather_cards_into_facts(&db, &mut facts)?;
+        gather_reviews_into_cards(&db, &mut facts)?;
+
+        let mut notes = vec![];
+        let mut fact_views = vec![];
+        for fact in facts.into_values() {
+            if let Some(fact_view) = fact.fact_view() {
+                if !fact_views.contains(&fact_view) {
+                    fact_views.push(fact_view);
+                }
+                notes.push(fact.into_foreign_note(fact_view));
+            } else {
+                notes.push(fact.into_unknown_note());
+            }
+        }
+        Ok(ForeignData {
+            default_deck: NameOrId::Id(deck_id.0),
+            notes,
+            notetypes: fact_views
+                .into_iter()
+                .map(FactView::foreign_notetype)
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FactView {
+    FrontOnly,
+    FrontBack,
+    Vocabulary,
+    Cloze,
+}
+
+impl FactView {
+    fn from_id(id: &str) -> Option<Self> {
+        let starts_with = |prefix: &str| {
+            id.starts_with(&format!("{prefix}.")) || id.starts_with(&format!("{prefix}::"))
+        };
+        if starts_with("1") {
+            Some(Self::FrontOnly)
+        } else if starts_with("2") {
+            Some(Self::FrontBack)
+        } else if starts_with("3") {
+            Some(Self::Vocabulary)
+        } else if id.starts_with("5.1") {
+            Some(Self::Cloze)
+        } else {
+            None
+        }
+    }
+
+    fn notetype_name(self) -> &'static str {
+        match self {
+            Self::FrontOnly => "Mnemosyne-FrontOnly",
+            Self::FrontBack => "Mnemosyne-FrontBack",
+            Self::Vocabulary => "Mnemosyne-Vocabulary",
+            Self::Cloze => "Mnemosyne-Cloze",
+        }
+    }
+
+    fn field_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::FrontOnly | Self::FrontBack => &["f", "b"],
+            Self::Vocabulary => &["f", "p_1", "m_1", "n"],
+            Self::Cloze => &["text"],
+        }
+    }
+
+    fn foreign_notetype(self) -> ForeignNotetype {
+        let name = self.notetype_name();
+        match self {
+            Self::FrontOnly => ForeignNotetype::basic(name),
+            Self::FrontBack => ForeignNotetype::basic_reverse(name),
+            Self::Vocabulary => ForeignNotetype {
+                fields: ["Expression", "Pronunciation", "Meaning", "Notes"]
+                    .map(ToString::to_string)
+                    .to_vec(),
+                templates: vec![
+                    ForeignTemplate::new(
+                        "Recognition",
+                        "{{Expression}}",
+                        "{{Expression}}\n\n<hr id=answer>\n\n{{Pronunciation}}<br>\n{{Meaning}}<br>\n{{Notes}}",
+                    ),
+                    ForeignTemplate::new(
+                        "Production",
+                        "{{Meaning}}",
+                        "{{Meaning}}\n\n<hr id=answer>\n\n{{Expression}}<br>\n{{Pronunciation}}<br>\n{{Notes}}",
+                    ),
+                ],
+                ..ForeignNotetype::basic(name)
+            },
+            Self::Cloze => ForeignNotetype::cloze(name),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Fact {
+    fields: HashMap<String, String>,
+    cards: Vec<MnemoCard>,
+}
+
+#[derive(Debug)]
+struct MnemoCard {
+    id: String,
+    fact_view_id: String,
+    tags: String,
+    next_rep: i64,
+    last_rep: i64,
+    easiness: f32,
+    reps: u32,
+    lapses: u32,
+    reviews: Vec<ForeignReview>,
+}
+
+impl Fact {
+    /// None if the fact has no cards, or they have an unknown fact view.
+    fn fact_view(&self) -> Option<FactView> {
+        self.cards
+            .first()
+            .and_then(|card| FactView::from_id(&card.fact_view_id))
+    }
+
+    fn into_foreign_note(mut self, fact_view: FactView) -> ForeignNote {
+        self.cards.sort_by_key(MnemoCard::card_ord);
+        let fields = fact_view
+            .field_keys()
+            .iter()
+            .map(|key| {
+                let field = self
+                    .fields
+                    .get(*key)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                if fact_view == FactView::Cloze {
+                    munge_field(&clozes_from_brackets(field))
+                } else {
+                    munge_field(field)
+                }
+            })
+            .map(Some)
+            .collect();
+        let tags = self
+            .cards
+            .iter()
+            .flat_map(|card| card.anki_tags())
+            .unique()
+            .collect();
+        ForeignNote {
+            fields,
+            tags: Some(tags),
+            notetype: NameOrId::Name(fact_view.notetype_name().to_string()),
+            cards: self
+                .cards
+                .into_iter()
+                .enumerate()
+                .filter(|(_, card)| !card.is_new())
+                .map(|(idx, card)| {
+                    // cloze cards share a fact view, so are assumed to be in the
+                    // order of their clozes
+                    let template_index = if fact_view == FactView::Cloze {
+                        idx as u16
+                    } else {
+                        card.card_ord()
+                    };
+                    card.into_foreign_card(template_index)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Fact {
+    /// A note without a notetype, so that the fact is reported as having a
+    /// missing notetype instead of being silently dropped.
+    fn into_unknown_note(self) -> ForeignNote {
+        ForeignNote {
+            fields: self
+                .fields
+                .into_iter()
+                .sorted()
+                .map(|(_, field)| Some(munge_field(&field)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl MnemoCard {
+    /// The number after the last separator of the fact view id, minus one.
+    fn card_ord(&self) -> u16 {
+        self.fact_view_id
+            .rsplit(['.', ':'])
+            .next()
+            .and_then(|ord| ord.parse::<u16>().ok())
+            .map(|ord| ord.saturating_sub(1))
+            .unwrap_or_default()
+    }
+
+    fn is_new(&self) -> bool {
+        self.last_rep == -1
+    }
+
+    fn anki_tags(&self) -> impl Iterator<Item = String> + '_ {
+        self.tags
+            .split(", ")
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| tag.replace([' ', '\u{3000}'], "_"))
+    }
+
+    fn into_foreign_card(self, template_index: u16) -> ForeignCard {
+        ForeignCard {
+            template_index: Some(template_index),
+            due: Some(self.next_rep as i32),
+            interval: Some(1.max((self.next_rep - self.last_rep) / 86_400) as u32),
+            ease_factor: Some(self.easiness),
+            reps: Some(self.reps),
+            lapses: Some(self.lapses),
+            reviews: self.reviews,
+            ..Default::default()
+        }
+    }
+}
+
+fn gather_facts(db: &Connection) -> Result<BTreeMap<i64, Fact>> {
+    let mut facts: BTreeMap<i64, Fact> = BTreeMap::new();
+    let mut stmt = db.prepare(
+        "SELECT _id, key, value FROM facts, data_for_fact WHERE facts._id = data_for_fact._fact_id",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        facts
+            .entry(row.get(0)?)
+            .or_default()
+            .fields
+            .insert(row.get(1)?, row.get(2)?);
+    }
+    Ok(facts)
+}
+
+fn gather_cards_into_facts(db: &Connection, facts: &mut BTreeMap<i64, Fact>) -> Result<()> {
+    let mut stmt = db.prepare(
+        "SELECT _fact_id, id, fact_view_id, tags, next_rep, last_rep, easiness,
+            acq_reps + ret_reps, lapses FROM cards",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        if let Some(fact) = facts.get_mut(&row.get::<_, i64>(0)?) {
+            fact.cards.push(MnemoCard {
+                id: row.get(1)?,
+                fact_view_id: row.get(2)?,
+                tags: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                next_rep: row.get(4)?,
+                last_rep: row.get(5)?,
+                easiness: row.get(6)?,
+                reps: row.get(7)?,
+                lapses: row.get(8)?,
+                reviews: vec![],
+            });
+        }
+    }
+    Ok(())
+}
+
+fn gather_reviews_into_cards(db: &Connection, facts: &mut BTreeMap<i64, Fact>) -> Result<()> {
+    let mut cards: HashMap<String, &mut MnemoCard> = facts
+        .values_mut()
+        .flat_map(|fact| fact.cards.iter_mut())
+        .map(|card| (card.id.clone(), card))
+        .collect();
+    let mut stmt = db.prepare(
+        "SELECT object_id, timestamp, grade, next_rep, thinking_time FROM log
+        WHERE event_type = ? ORDER BY timestamp",
+    )?;
+    let mut rows = stmt.query([REPETITION_EVENT])?;
+    let mut memorised: HashMap<String, bool> = HashMap::new();
+    let mut lapsed: HashMap<String, bool> = HashMap::new();
+    while let Some(row) = rows.next()? {
+        let card_id: String = row.get(0)?;
+        let Some(card) = cards.get_mut(card_id.as_str()) else {
+            continue;
+        };
+        let time: i64 = row.get(1)?;
+        let grade: u8 = row.get(2)?;
+        let next_rep: i64 = row.get(3)?;
+        let thinking_secs: f64 = row.get::<_, Option<f64>>(4)?.unwrap_or_default();
+
+        let was_memorised = memorised.get(&card_id).copied().unwrap_or_default();
+        let has_lapsed = lapsed.get(&card_id).copied().unwrap_or_default();
+        let kind = match (was_memorised, has_lapsed) {
+            (true, _) => RevlogReviewKind::Review,
+            (false, true) => RevlogReviewKind::Relearning,
+            (false, false) => RevlogReviewKind::Learning,
+        };
+        // grades of 2 and above count as remembered
+        let passed = grade >= 2;
+        if was_memorised && !passed {
+            lapsed.insert(card_id.clone(), true);
+        }
+        memorised.insert(card_id, passed);
+
+        let interval_secs = next_rep - time;
+        card.reviews.push(ForeignReview {
+            time: time * 1000,
+            rating: match grade {
+                0 | 1 => 1,
+                2 => 2,
+                3 | 4 => 3,
+                _ => 4,
+            },
+            interval: if interval_secs >= 86_400 {
+                (interval_secs / 86_400) as i32
+            } else {
+                -(interval_secs.max(0) as i32)
+            },
+            taken_millis: (thinking_secs * 1000.0) as u32,
+            kind,
+            ..Default::default()
+        });
+    }
+    Ok(())
+}
+
+/// Converts Mnemosyne's cloze syntax of `[hidden]` into numbered clozes.
+fn clozes_from_brackets(text: &str) -> String {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"\[(.+?)\]").unwrap();
+    }
+    let mut ord = 0;
+    RE.replace_all(text, |caps: &Captures| {
+        ord += 1;
+        format!("{{{{c{ord}::{}}}}}", &caps[1])
+    })
+    .into_owned()
+}
+
+fn munge_field(field: &str) -> String {
+    lazy_static! {
+        static ref NEWLINE: Regex = Regex::new(r"\r?\n").unwrap();
+        static ref LATEX: Regex = Regex::new(r"(?i)<(/?(\$|\$\$|latex))>").unwrap();
+        static ref AUDIO: Regex = Regex::new(r#"<audio src="(.+?)">(</audio>)?"#).unwrap();
+    }
+    let field = NEWLINE.replace_all(field, "<br>");
+    let field = LATEX.replace_all(&field, "[$1]");
+    AUDIO.replace_all(&field, "[sound:$1]").into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_import_facts_with_their_cards_and_reviews() -> Result<()> {
+        let mut col = Collection::new();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../pylib/tests/support/mnemo.db");
+        col.import_mnemosyne(path.to_str().unwrap(), DeckId(1))?;
+
+        let cards = col.storage.get_all_cards();
+        assert_eq!(cards.len(), 7);
+        assert_eq!(
+            cards
+                .iter()
+                .filter(|card| card.ctype == CardType::New)
+                .count(),
+            1
+        );
+        assert!(col
+            .storage
+            .all_tags()?
+            .iter()
+            .any(|tag| tag.name == "a_longer_tag"));
+        let reviewed = cards.iter().find(|card| card.reps == 1).unwrap();
+        let revlog = col.storage.get_revlog_entries_for_card(reviewed.id)?;
+        assert_eq!(revlog.len(), 1);
+        assert_eq!(revlog[0].review_kind, RevlogReviewKind::Learning);
+        Ok(())
+    }
+
+    #[test]
+    fn foreign_data_json_can_be_imported() -> Result<()> {
+        let mut col = Collection::new();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../pylib/tests/support/mnemo.db");
+        let json = mnemosyne_foreign_data_json(path.to_str().unwrap(), DeckId(1))?;
+        col.import_json_string(&json)?;
+        assert_eq!(col.storage.get_all_cards().len(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_facts_without_known_fact_view() -> Result<()> {
+        let mut col = Collection::new();
+        let fact = Fact {
+            fields: HashMap::from([("f".to_string(), "foo".to_string())]),
+            cards: vec![],
+        };
+        assert_eq!(fact.fact_view(), None);
+        let data = ForeignData {
+            default_deck: NameOrId::Id(1),
+            notes: vec![fact.into_unknown_note()],
+            ..Default::default()
+        };
+        let progress = col.new_progress_handler();
+        let log = data.import(&mut col, progress)?.output;
+        assert_eq!(log.missing_notetype.len(), 1);
+        assert_eq!(log.missing_notetype[0].fields, ["foo"]);
+        assert_eq!(col.storage.get_all_notes().len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn should_munge_fields() {
+        assert_eq!(
+            munge_field("a\nb<$>x</$><audio src=\"foo.mp3\">"),
+            "a<br>b[$]x[/$][sound:foo.mp3]"
+        );
+        assert_eq!(
+            clozes_from_brackets("[foo] and [bar]"),
+            "{{c1::foo}} and {{c2::bar}}"
+        );
+    }
+}
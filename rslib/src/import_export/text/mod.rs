@@ -4,6 +4,10 @@
 pub mod csv;
 mod import;
 mod json;
+mod mnemosyne;
+mod supermemo;
+
+pub use mnemosyne::mnemosyne_foreign_data_json;
 
 use anki_proto::import_export::csv_metadata::DupeResolution;
 use anki_proto::import_export::csv_metadata::MatchScope;
@@ -11,6 +15,11 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use super::LogNote;
+use crate::notetype::CardTemplate;
+use crate::notetype::NoteField;
+use crate::prelude::*;
+use crate::revlog::RevlogEntry;
+use crate::revlog::RevlogReviewKind;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
@@ -34,18 +43,60 @@ pub struct ForeignNote {
     notetype: NameOrId,
     deck: NameOrId,
     cards: Vec<ForeignCard>,
+    /// Unix timestamp in seconds of the last modification, which existing
+    /// notes are compared with when updating only if newer.
+    mtime: Option<i64>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ForeignCard {
-    pub due: i32,
-    pub interval: u32,
-    pub ease_factor: f32,
-    pub reps: u32,
-    pub lapses: u32,
+    /// The card's position in the note's list of cards if not set.
+    pub template_index: Option<u16>,
+    /// Unset values are left unchanged on existing cards. Added cards are new
+    /// unless an interval is set.
+    ///
+    /// Unix timestamp. Ignored for new cards.
+    pub due: Option<i32>,
+    /// In days. An interval of 0 resets the card to new.
+    pub interval: Option<u32>,
+    /// The position of a new card, or the position a learning card had when it
+    /// was new. New cards are added to the end of the queue if not set.
+    pub position: Option<u32>,
+    /// Makes the card a learning card, or a relearning card if it has an
+    /// interval, that is due at the exact time in `due`.
+    pub remaining_steps: Option<u32>,
+    pub ease_factor: Option<f32>,
+    pub reps: Option<u32>,
+    pub lapses: Option<u32>,
+    pub suspended: Option<bool>,
+    pub flags: Option<u8>,
+    /// FSRS memory state, only used if both are set and in range.
+    pub stability: Option<f32>,
+    pub difficulty: Option<f32>,
+    /// Added to the review history of the card. Reviews at the time of an
+    /// existing review of the card are skipped.
+    pub reviews: Vec<ForeignReview>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForeignReview {
+    /// Unix timestamp in milliseconds
+    pub time: i64,
+    /// 1-4, or 0 for manual rescheduling
+    pub rating: u8,
+    /// Positive values are in days, negative values in seconds.
+    pub interval: i32,
+    /// The interval before the review, in the same units.
+    pub last_interval: i32,
+    /// The ease factor after the review, eg 2500 for 250%.
+    pub ease_factor: u32,
+    pub taken_millis: u32,
+    pub kind: RevlogReviewKind,
 }
 
+/// Unset options keep the defaults of a new notetype.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ForeignNotetype {
     name: String,
@@ -53,6 +104,32 @@ pub struct ForeignNotetype {
     templates: Vec<ForeignTemplate>,
     #[serde(default)]
     is_cloze: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    css: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latex_pre: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latex_post: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latex_svg: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sort_field_index: Option<u32>,
+    /// The options of the fields at the same positions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    field_options: Vec<ForeignFieldOptions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForeignFieldOptions {
+    font_name: Option<String>,
+    font_size: Option<u32>,
+    description: String,
+    sticky: bool,
+    rtl: bool,
+    plain_text: bool,
+    collapsed: bool,
+    exclude_from_search: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -60,6 +137,10 @@ pub struct ForeignTemplate {
     name: String,
     qfmt: String,
     afmt: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    browser_qfmt: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    browser_afmt: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -81,6 +162,146 @@ impl From<String> for NameOrId {
     }
 }
 
+impl ForeignNotetype {
+    fn basic(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: vec!["Front".to_string(), "Back".to_string()],
+            templates: vec![ForeignTemplate::front_back()],
+            is_cloze: false,
+            css: None,
+            latex_pre: None,
+            latex_post: None,
+            latex_svg: None,
+            sort_field_index: None,
+            field_options: vec![],
+        }
+    }
+
+    fn basic_reverse(name: &str) -> Self {
+        Self {
+            templates: vec![ForeignTemplate::front_back(), ForeignTemplate::back_front()],
+            ..Self::basic(name)
+        }
+    }
+
+    fn cloze(name: &str) -> Self {
+        Self {
+            fields: vec!["Text".to_string(), "Back Extra".to_string()],
+            templates: vec![ForeignTemplate::new(
+                "Cloze",
+                "{{cloze:Text}}",
+                "{{cloze:Text}}<br>\n{{Back Extra}}",
+            )],
+            is_cloze: true,
+            ..Self::basic(name)
+        }
+    }
+}
+
+impl ForeignTemplate {
+    fn new(name: &str, qfmt: &str, afmt: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            qfmt: qfmt.to_string(),
+            afmt: afmt.to_string(),
+            browser_qfmt: String::new(),
+            browser_afmt: String::new(),
+        }
+    }
+
+    fn front_back() -> Self {
+        Self::new(
+            "Card 1",
+            "{{Front}}",
+            "{{FrontSide}}\n\n<hr id=answer>\n\n{{Back}}",
+        )
+    }
+
+    fn back_front() -> Self {
+        Self::new(
+            "Card 2",
+            "{{Back}}",
+            "{{FrontSide}}\n\n<hr id=answer>\n\n{{Front}}",
+        )
+    }
+}
+
+impl From<&Notetype> for ForeignNotetype {
+    fn from(notetype: &Notetype) -> Self {
+        Self {
+            name: notetype.name.clone(),
+            fields: notetype.fields.iter().map(|f| f.name.clone()).collect(),
+            templates: notetype.templates.iter().map(Into::into).collect(),
+            is_cloze: notetype.is_cloze(),
+            css: Some(notetype.config.css.clone()),
+            latex_pre: Some(notetype.config.latex_pre.clone()),
+            latex_post: Some(notetype.config.latex_post.clone()),
+            latex_svg: Some(notetype.config.latex_svg),
+            sort_field_index: Some(notetype.config.sort_field_idx),
+            field_options: notetype.fields.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&NoteField> for ForeignFieldOptions {
+    fn from(field: &NoteField) -> Self {
+        Self {
+            font_name: Some(field.config.font_name.clone()),
+            font_size: Some(field.config.font_size),
+            description: field.config.description.clone(),
+            sticky: field.config.sticky,
+            rtl: field.config.rtl,
+            plain_text: field.config.plain_text,
+            collapsed: field.config.collapsed,
+            exclude_from_search: field.config.exclude_from_search,
+        }
+    }
+}
+
+impl From<&CardTemplate> for ForeignTemplate {
+    fn from(template: &CardTemplate) -> Self {
+        Self {
+            name: template.name.clone(),
+            qfmt: template.config.q_format.clone(),
+            afmt: template.config.a_format.clone(),
+            browser_qfmt: template.config.q_format_browser.clone(),
+            browser_afmt: template.config.a_format_browser.clone(),
+        }
+    }
+}
+
+impl From<&RevlogEntry> for ForeignReview {
+    fn from(entry: &RevlogEntry) -> Self {
+        Self {
+            time: entry.id.0,
+            rating: entry.button_chosen,
+            interval: entry.interval,
+            last_interval: entry.last_interval,
+            ease_factor: entry.ease_factor,
+            taken_millis: entry.taken_millis,
+            kind: entry.review_kind,
+        }
+    }
+}
+
+impl ForeignReview {
+    fn into_native(self, card_id: CardId, usn: Usn) -> RevlogEntry {
+        RevlogEntry {
+            id: RevlogId(self.time),
+            cid: card_id,
+            usn,
+            button_chosen: self.rating,
+            interval: self.interval,
+            last_interval: self.last_interval,
+            ease_factor: self.ease_factor,
+            taken_millis: self.taken_millis,
+            review_kind: self.kind,
+            ..Default::default()
+        }
+    }
+}
+
 impl ForeignNote {
     pub(crate) fn into_log_note(self) -> LogNote {
         LogNote {
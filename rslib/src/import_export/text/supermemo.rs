@@ -0,0 +1,340 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Importing of SuperMemo XML exports.
+//!
+//! Elements with a title are topics, which are turned into tags of the items
+//! they contain. Elements without a title, but with a question and an answer,
+//! are items, which are imported as basic notes.
+
+use anki_io::read_file;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::Regex;
+
+use super::ForeignCard;
+use super::ForeignData;
+use super::ForeignNote;
+use super::ForeignNotetype;
+use super::NameOrId;
+use crate::import_export::ImportError;
+use crate::import_export::NoteLog;
+use crate::prelude::*;
+use crate::text::without_combining;
+
+const NOTETYPE_NAME: &str = "Supermemo";
+
+impl Collection {
+    /// Notes are added to the provided deck.
+    pub fn import_supermemo(&mut self, path: &str, deck_id: DeckId) -> Result<OpOutput<NoteLog>> {
+        let progress = self.new_progress_handler();
+        let slice = read_file(path)?;
+        let xml = String::from_utf8_lossy(&slice);
+        let data = ForeignData::from_supermemo(&xml, deck_id)?;
+        data.import(self, progress)
+    }
+}
+
+impl ForeignData {
+    fn from_supermemo(xml: &str, deck_id: DeckId) -> Result<Self> {
+        Ok(ForeignData {
+            default_deck: NameOrId::Id(deck_id.0),
+            default_notetype: NameOrId::Name(NOTETYPE_NAME.to_string()),
+            notes: parse_items(xml)?
+                .into_iter()
+                .map(SuperMemoItem::into_foreign_note)
+                .collect(),
+            notetypes: vec![ForeignNotetype::basic(NOTETYPE_NAME)],
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct SuperMemoElement {
+    title: Option<String>,
+    question: Option<String>,
+    answer: Option<String>,
+    interval: Option<String>,
+    repetitions: Option<String>,
+    lapses: Option<String>,
+    last_repetition: Option<String>,
+    afactor: Option<String>,
+}
+
+#[derive(Debug)]
+struct SuperMemoItem {
+    element: SuperMemoElement,
+    /// The titles of the topics containing the item.
+    topics: Vec<String>,
+}
+
+impl SuperMemoElement {
+    fn field_mut(&mut self, tag: &[u8]) -> Option<&mut Option<String>> {
+        Some(match tag {
+            b"Title" => &mut self.title,
+            b"Question" => &mut self.question,
+            b"Answer" => &mut self.answer,
+            b"Interval" => &mut self.interval,
+            b"Repetitions" => &mut self.repetitions,
+            b"Lapses" => &mut self.lapses,
+            b"LastRepetition" => &mut self.last_repetition,
+            b"AFactor" => &mut self.afactor,
+            _ => return None,
+        })
+    }
+
+    fn is_item(&self) -> bool {
+        let not_empty =
+            |text: &Option<String>| text.as_deref().is_some_and(|t| !t.trim().is_empty());
+        self.title.is_none() && not_empty(&self.question) && not_empty(&self.answer)
+    }
+}
+
+fn parse_items(xml: &str) -> Result<Vec<SuperMemoItem>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut elements: Vec<SuperMemoElement> = vec![];
+    let mut open_tags: Vec<Vec<u8>> = vec![];
+    let mut items = vec![];
+    loop {
+        let text = match reader.read_event().map_err(|_| ImportError::Corrupt)? {
+            Event::Start(start) => {
+                let name = start.name().as_ref().to_vec();
+                if name == b"SuperMemoElement" {
+                    elements.push(SuperMemoElement::default());
+                }
+                open_tags.push(name);
+                continue;
+            }
+            Event::End(end) => {
+                open_tags.pop();
+                if end.name().as_ref() == b"SuperMemoElement" {
+                    let element = elements.pop().ok_or(ImportError::Corrupt)?;
+                    if element.is_item() {
+                        items.push(SuperMemoItem {
+                            element,
+                            topics: elements.iter().filter_map(|e| e.title.clone()).collect(),
+                        });
+                    }
+                }
+                continue;
+            }
+            Event::Text(text) => text
+                .unescape()
+                .map_err(|_| ImportError::Corrupt)?
+                .into_owned(),
+            Event::CData(data) => String::from_utf8_lossy(&data.into_inner()).into_owned(),
+            Event::Eof => break,
+            _ => continue,
+        };
+        if let (Some(tag), Some(element)) = (open_tags.last(), elements.last_mut()) {
+            if let Some(field) = element.field_mut(tag) {
+                field.get_or_insert_with(String::new).push_str(&text);
+            }
+        }
+    }
+    Ok(items)
+}
+
+impl SuperMemoItem {
+    fn into_foreign_note(self) -> ForeignNote {
+        let interval = parse_number::<u32>(&self.element.interval).unwrap_or_default();
+        let mut tags = topic_tags(&self.topics);
+        if interval > 0 {
+            tags.push("Memorized".to_string());
+        }
+        ForeignNote {
+            fields: vec![
+                self.element.question.as_deref().map(fudge_text),
+                self.element.answer.as_deref().map(fudge_text),
+            ],
+            tags: Some(tags),
+            cards: self.element.foreign_card(interval).into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl SuperMemoElement {
+    /// None if the item has not been learned yet.
+    fn foreign_card(&self, interval: u32) -> Option<ForeignCard> {
+        if interval == 0 {
+            return None;
+        }
+        let last_review = parse_date(self.last_repetition.as_deref()?)?;
+        let lapses = parse_number(&self.lapses).unwrap_or_default();
+        Some(ForeignCard {
+            due: Some(last_review + interval as i32 * 86_400),
+            interval: Some(interval),
+            ease_factor: Some(
+                parse_number::<f32>(&self.afactor)
+                    .map(afactor_to_efactor)
+                    .unwrap_or(2.5),
+            ),
+            reps: Some(parse_number::<u32>(&self.repetitions).unwrap_or_default() + lapses),
+            lapses: Some(lapses),
+            ..Default::default()
+        })
+    }
+}
+
+/// Numbers may use a decimal comma.
+fn parse_number<T: std::str::FromStr>(text: &Option<String>) -> Option<T> {
+    text.as_deref()?.trim().replace(',', ".").parse().ok()
+}
+
+/// A DD.MM.YYYY date, which is mapped to noon so that it falls on the same day
+/// regardless of the rollover hour.
+fn parse_date(text: &str) -> Option<i32> {
+    let noon = NaiveDate::parse_from_str(text.trim(), "%d.%m.%Y")
+        .ok()?
+        .and_hms_opt(12, 0, 0)?;
+    Local
+        .from_local_datetime(&noon)
+        .single()
+        .map(|datetime| datetime.timestamp() as i32)
+}
+
+/// Scales SuperMemo's A-factor to an ease factor.
+/// Adapted from <http://www.supermemo.com/beta/xml/xml-core.htm>.
+fn afactor_to_efactor(afactor: f32) -> f32 {
+    const AF_MIN: f32 = 1.2;
+    const AF_MAX: f32 = 6.9;
+    const EF_MIN: f32 = 1.3;
+    const EF_MAX: f32 = 3.3;
+    let scaled = (afactor.clamp(AF_MIN, AF_MAX) - AF_MIN) / (AF_MAX - AF_MIN);
+    EF_MIN + scaled * (EF_MAX - EF_MIN)
+}
+
+fn fudge_text(text: &str) -> String {
+    text.replace("&amp;", "&")
+        .replace("\n\r", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Turns topic titles like `[100] foo_bar baz` into tags like `fooBarBaz`.
+fn topic_tags(topics: &[String]) -> Vec<String> {
+    lazy_static! {
+        static ref NUMBERED: Regex = Regex::new(r"\[[0-9]+\]").unwrap();
+        static ref NON_WORD: Regex = Regex::new(r"\W").unwrap();
+        static ref NUMBERS_ONLY: Regex = Regex::new(r"^[0-9 ]+$").unwrap();
+    }
+    topics
+        .iter()
+        .map(|topic| {
+            let topic = without_combining(topic);
+            let topic = NUMBERED.replace_all(&topic, " ").replace('_', " ");
+            let topic = NON_WORD.replace_all(&topic, " ");
+            let topic = NUMBERS_ONLY.replace(&topic, "");
+            let mut tag: String = topic.split_whitespace().map(capitalise).collect();
+            if let Some(first) = tag.get(..1) {
+                tag.replace_range(..1, &first.to_lowercase());
+            }
+            tag
+        })
+        .filter(|tag| !tag.is_empty())
+        .unique()
+        .collect()
+}
+
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| {
+            first
+                .to_uppercase()
+                .chain(chars.flat_map(char::to_lowercase))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<SuperMemoCollection>
+  <Count>3572</Count>
+  <SuperMemoElement>
+    <ID>1</ID>
+    <Type>Topic</Type>
+    <Content>
+      <Question />
+      <Answer />
+    </Content>
+    <SuperMemoElement>
+      <ID>40326</ID>
+      <Title>Pŕíklad_topic</Title>
+      <Type>Topic</Type>
+      <SuperMemoElement>
+        <ID>40327</ID>
+        <Title>[1] 1-400</Title>
+        <Type>Topic</Type>
+        <SuperMemoElement>
+          <ID>10247</ID>
+          <Type>Item</Type>
+          <Content>
+            <Question>aoeu &amp;amp; aoeu</Question>
+            <Answer>aoeu</Answer>
+          </Content>
+          <LearningData>
+            <Interval>1844</Interval>
+            <Repetitions>7</Repetitions>
+            <Lapses>0</Lapses>
+            <LastRepetition>19.09.2002</LastRepetition>
+            <AFactor>5,701</AFactor>
+            <UFactor>2,452</UFactor>
+          </LearningData>
+        </SuperMemoElement>
+      </SuperMemoElement>
+      <Content>
+        <Question>aoeu</Question>
+        <Answer />
+      </Content>
+    </SuperMemoElement>
+  </SuperMemoElement>
+</SuperMemoCollection>"#;
+
+    #[test]
+    fn should_parse_items_with_topics_and_learning_data() -> Result<()> {
+        let items = parse_items(XML)?;
+        assert_eq!(items.len(), 1);
+        let note = items.into_iter().next().unwrap().into_foreign_note();
+
+        assert_eq!(
+            note.fields,
+            [Some("aoeu & aoeu".to_string()), Some("aoeu".to_string())]
+        );
+        assert_eq!(
+            note.tags.unwrap(),
+            ["prikladTopic", "Memorized"].map(ToString::to_string)
+        );
+        let card = &note.cards[0];
+        assert_eq!(card.interval, Some(1844));
+        assert_eq!(card.reps, Some(7));
+        assert_eq!((card.ease_factor.unwrap() * 1000.0) as u32, 2879);
+        Ok(())
+    }
+
+    #[test]
+    fn should_import_items_as_basic_notes() -> Result<()> {
+        let mut col = Collection::new();
+        let data = ForeignData::from_supermemo(XML, DeckId(1))?;
+        let progress = col.new_progress_handler();
+        data.import(&mut col, progress)?;
+
+        assert_eq!(col.storage.notes_table_len(), 1);
+        let card = &col.storage.get_all_cards()[0];
+        assert_eq!(card.interval, 1844);
+        assert_eq!(card.ease_factor, 2879);
+        Ok(())
+    }
+}
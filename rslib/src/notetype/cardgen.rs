@@ -282,6 +282,12 @@ impl Collection {
         target_deck_id: Option<DeckId>,
         cache: &mut CardGenCache,
     ) -> Result<()> {
+        // cards added to an existing note inherit its prerequisites
+        let prerequisites = if cards.is_empty() {
+            vec![]
+        } else {
+            self.note_prerequisites(nid)?
+        };
         for c in cards {
             let (did, dcid) = self.deck_for_adding(c.did.or(target_deck_id))?;
             let due = if let Some(due) = c.due {
@@ -291,6 +297,7 @@ impl Collection {
                 self.due_for_deck(did, dcid, cache)?
             };
             let mut card = Card::new(nid, c.ord as u16, did, due as i32);
+            card.prerequisites = prerequisites.clone();
             self.add_card(&mut card)?;
         }
 
@@ -32,6 +32,8 @@ pub enum Op {
     SetCardDeck,
     SetDueDate,
     SetFlag,
+    SetIntervalLimits,
+    SetPrerequisites,
     SortCards,
     Suspend,
     UnburyUnsuspend,
@@ -72,6 +74,8 @@ impl Op {
             Op::UpdateTag => tr.actions_update_tag(),
             Op::SetCardDeck => tr.browsing_change_deck(),
             Op::SetFlag => tr.actions_set_flag(),
+            Op::SetIntervalLimits => tr.actions_set_interval_limits(),
+            Op::SetPrerequisites => tr.actions_set_prerequisites(),
             Op::FindAndReplace => tr.browsing_find_and_replace(),
             Op::ClearUnusedTags => tr.browsing_clear_unused_tags(),
             Op::SortCards => tr.browsing_reschedule(),
@@ -0,0 +1,132 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+use crate::card::CardQueue;
+use crate::card::CardType;
+use crate::prelude::*;
+
+impl Collection {
+    /// Apply the leech actions of the card's preset, after the card has been
+    /// answered and saved. Suspension is handled when the new state is
+    /// applied.
+    pub(super) fn apply_leech_actions(
+        &mut self,
+        card: &mut Card,
+        config: &DeckConfig,
+        usn: Usn,
+    ) -> Result<()> {
+        let config = &config.inner;
+        let tags = if config.leech_tag_lapses {
+            format!("leech leech::{}", card.lapses)
+        } else {
+            "leech".to_string()
+        };
+        self.add_tags_to_notes_inner(&[card.note_id], &tags)?;
+
+        let original = card.clone();
+        let old_relearn_steps = config.relearn_steps.clone();
+        let mut moved = false;
+        if !config.leech_deck.trim().is_empty() {
+            let deck = self.get_or_create_normal_deck(&config.leech_deck)?;
+            // the deck may have been converted into a filtered deck since it was
+            // chosen
+            if !deck.is_filtered() && card.original_or_current_deck_id() != deck.id {
+                card.set_deck(deck.id);
+                moved = true;
+            }
+        }
+        if config.leech_reset_to_new {
+            let suspended = card.queue == CardQueue::Suspended;
+            let position = self.get_next_card_position();
+            if card.schedule_as_new(position, false, true) {
+                self.set_next_card_position(position + 1)?;
+            }
+            // like a manual reset, so it can be told apart in the history
+            self.log_manually_scheduled_review(card, original.interval, usn)?;
+            if suspended {
+                card.queue = CardQueue::Suspended;
+            }
+        }
+        if config.leech_flag > 0 {
+            card.set_flag(config.leech_flag as u8);
+        }
+        self.update_card_inner(card, original, usn)?;
+
+        if moved && card.ctype == CardType::Relearn {
+            let new_config = self.deck_config_for_card(card)?;
+            self.adjust_remaining_steps(
+                card,
+                &old_relearn_steps,
+                &new_config.inner.relearn_steps,
+                usn,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::card::CardQueue;
+    use crate::card::CardType;
+    use crate::deckconfig::LeechAction;
+    use crate::prelude::*;
+    use crate::revlog::RevlogReviewKind;
+    use crate::tests::*;
+
+    #[test]
+    fn leech_actions() -> Result<()> {
+        let mut col = Collection::new();
+        col.update_default_deck_config(|config| {
+            config.learn_steps = vec![];
+            config.relearn_steps = vec![];
+            config.leech_threshold = 2;
+            config.leech_repeat_interval = 1;
+            config.leech_action = LeechAction::Suspend as i32;
+            config.leech_deck = "Leeches".into();
+            config.leech_reset_to_new = true;
+            config.leech_flag = 1;
+            config.leech_tag_lapses = true;
+        });
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+        let cid = col.storage.all_cards_of_note(note.id)?[0].id;
+        col.answer_good();
+        col.storage.db.execute_batch("update cards set due = 0")?;
+        col.clear_study_queues();
+        col.answer_again();
+        // the first lapse doesn't reach the threshold
+        let card = col.storage.get_card(cid)?.unwrap();
+        assert_eq!(card.lapses, 1);
+        assert_eq!(card.flags, 0);
+
+        col.storage.db.execute_batch("update cards set due = 0")?;
+        col.clear_study_queues();
+        col.answer_again();
+        let card = col.storage.get_card(cid)?.unwrap();
+        let leech_deck = col.get_deck_id("Leeches")?.unwrap();
+        assert_eq!(card.deck_id, leech_deck);
+        assert_eq!(card.ctype, CardType::New);
+        assert_eq!(card.queue, CardQueue::Suspended);
+        assert_eq!(card.flags, 1);
+        let note = col.storage.get_note(note.id)?.unwrap();
+        assert_eq!(note.tags, vec!["leech", "leech::2"]);
+        let revlog = col.storage.get_revlog_entries_for_card(cid)?;
+        assert_eq!(revlog.len(), 4);
+        assert_eq!(revlog[3].review_kind, RevlogReviewKind::Manual);
+
+        // all actions are undone with the answer
+        col.undo()?;
+        let card = col.storage.get_card(cid)?.unwrap();
+        assert_eq!(card.deck_id, DeckId(1));
+        assert_eq!(card.ctype, CardType::Review);
+        assert_eq!(card.flags, 0);
+        let note = col.storage.get_note(note.id)?.unwrap();
+        assert!(note.tags.is_empty());
+        let revlog = col.storage.get_revlog_entries_for_card(cid)?;
+        assert_eq!(revlog.len(), 2);
+        assert!(revlog
+            .iter()
+            .all(|entry| entry.review_kind != RevlogReviewKind::Manual));
+        Ok(())
+    }
+}
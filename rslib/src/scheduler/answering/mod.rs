@@ -3,6 +3,7 @@
 
 mod current;
 mod learning;
+mod leech;
 mod preview;
 mod relearning;
 mod review;
@@ -10,11 +11,15 @@ mod revlog;
 
 use fsrs::NextStates;
 use fsrs::FSRS;
+use itertools::Itertools;
 use rand::prelude::*;
 use rand::rngs::StdRng;
 use revlog::RevlogEntryPartial;
 
+use super::customizer::CardStateScript;
+use super::customizer::ScriptInput;
 use super::queue::BuryMode;
+use super::states::steps::AdaptiveSteps;
 use super::states::steps::LearningSteps;
 use super::states::CardState;
 use super::states::FilteredState;
@@ -69,6 +74,10 @@ struct CardStateUpdater {
     fsrs_next_states: Option<NextStates>,
     /// Set if FSRS is enabled.
     desired_retention: Option<f32>,
+    /// Relative to today. Only set if sibling dispersal is enabled.
+    sibling_due_days: Vec<i32>,
+    /// Only set if adaptive learning steps are enabled.
+    adaptive_steps: Option<AdaptiveSteps>,
 }
 
 impl CardStateUpdater {
@@ -78,16 +87,23 @@ impl CardStateUpdater {
     pub(crate) fn state_context(&self) -> StateContext<'_> {
         StateContext {
             fuzz_factor: get_fuzz_factor(self.fuzz_seed),
-            steps: self.learn_steps(),
+            steps: self.learn_steps().with_adaptive(self.adaptive_steps),
             graduating_interval_good: self.config.inner.graduating_interval_good,
             graduating_interval_easy: self.config.inner.graduating_interval_easy,
             initial_ease_factor: self.config.inner.initial_ease,
             hard_multiplier: self.config.inner.hard_multiplier,
             easy_multiplier: self.config.inner.easy_multiplier,
             interval_multiplier: self.config.inner.interval_multiplier,
-            maximum_review_interval: self.config.inner.maximum_review_interval,
+            maximum_review_interval: self
+                .card
+                .review_interval_limits(self.config.inner.maximum_review_interval)
+                .1,
+            minimum_review_interval: self.card.minimum_interval.unwrap_or_default(),
             leech_threshold: self.config.inner.leech_threshold,
-            relearn_steps: self.relearn_steps(),
+            leech_repeat_interval: self.config.inner.leech_repeat_interval,
+            sibling_dispersal_days: self.config.inner.sibling_dispersal_days,
+            sibling_due_days: &self.sibling_due_days,
+            relearn_steps: self.relearn_steps().with_adaptive(self.adaptive_steps),
             lapse_multiplier: self.config.inner.lapse_multiplier,
             minimum_lapse_interval: self.config.inner.minimum_lapse_interval,
             in_filtered_deck: self.deck.is_filtered(),
@@ -112,6 +128,48 @@ impl CardStateUpdater {
         self.timing.next_day_at.elapsed_secs_since(self.now) as u32
     }
 
+    /// The next states, modified by the card state script if provided. Also
+    /// returns the card's new custom data, if the script changed it.
+    fn next_states(
+        &self,
+        script: Option<&CardStateScript>,
+    ) -> Result<(SchedulingStates, Option<String>)> {
+        let ctx = self.state_context();
+        let mut states = self.current_card_state().next_states(&ctx);
+        let custom_data = match script {
+            Some(script) => script.apply(
+                ScriptInput {
+                    card: &self.card,
+                    deck: &self.deck,
+                    days_elapsed: self.timing.days_elapsed,
+                    interval_limits: ctx.min_and_max_review_intervals(1),
+                },
+                &mut states,
+            )?,
+            None => None,
+        };
+        Ok((states, custom_data))
+    }
+
+    /// Clients that don't know about the card state script may pass in an
+    /// unmodified state, which is replaced with the scripted one. Custom data
+    /// provided by the client takes precedence over that of the script.
+    fn apply_card_state_script(
+        &self,
+        script: &CardStateScript,
+        answer: &mut CardAnswer,
+    ) -> Result<()> {
+        let (unmodified, _) = self.next_states(None)?;
+        let (scripted, custom_data) = self.next_states(Some(script))?;
+        if answer.new_state == answer.rating.pick(&unmodified) {
+            answer.new_state = answer.rating.pick(&scripted);
+        }
+        if answer.custom_data.is_none() {
+            answer.custom_data = custom_data;
+        }
+        Ok(())
+    }
+
     fn into_card(self) -> Card {
         self.card
     }
@@ -194,16 +252,24 @@ impl Rating {
             Rating::Easy => 4,
         }
     }
+
+    fn pick(self, states: &SchedulingStates) -> CardState {
+        match self {
+            Rating::Again => states.again,
+            Rating::Hard => states.hard,
+            Rating::Good => states.good,
+            Rating::Easy => states.easy,
+        }
+    }
 }
 
 impl Collection {
     /// Return the next states that will be applied for each answer button.
     pub fn get_scheduling_states(&mut self, cid: CardId) -> Result<SchedulingStates> {
         let card = self.storage.get_card(cid)?.or_not_found(cid)?;
+        let script = self.card_state_script();
         let ctx = self.card_state_updater(card)?;
-        let current = ctx.current_card_state();
-        let state_ctx = ctx.state_context();
-        Ok(current.next_states(&state_ctx))
+        Ok(ctx.next_states(script.as_ref())?.0)
     }
 
     /// Describe the next intervals, to display on the answer buttons.
@@ -275,6 +341,9 @@ impl Collection {
             "card was modified: {current_state:#?} {:#?}",
             answer.current_state,
         );
+        if let Some(script) = self.card_state_script() {
+            updater.apply_card_state_script(&script, answer)?;
+        }
 
         let revlog_partial = updater.apply_study_state(current_state, answer.new_state)?;
         self.add_partial_revlog(revlog_partial, usn, answer)?;
@@ -282,6 +351,7 @@ impl Collection {
         self.update_deck_stats_from_answer(usn, answer, &updater, original.queue)?;
         self.maybe_bury_siblings(&original, &updater.config)?;
         let timing = updater.timing;
+        let config = updater.config.clone();
         let mut card = updater.into_card();
         if let Some(data) = answer.custom_data.take() {
             card.custom_data = data;
@@ -289,7 +359,7 @@ impl Collection {
         }
         self.update_card_inner(&mut card, original, usn)?;
         if answer.new_state.leeched() {
-            self.add_leech_tag(card.note_id)?;
+            self.apply_leech_actions(&mut card, &config, usn)?;
         }
 
         self.update_queues_after_answering_card(&card, timing)
@@ -354,8 +424,13 @@ impl Collection {
             .or_not_found(card.deck_id)?;
         let config = self.home_deck_config(deck.config_id(), card.original_deck_id)?;
         let fsrs_enabled = self.get_config_bool(BoolKey::Fsrs);
-        let fsrs_next_states = if fsrs_enabled {
-            let fsrs = FSRS::new(Some(&config.inner.fsrs_weights))?;
+        let desired_retention = if fsrs_enabled {
+            Some(self.desired_retention_for_card(&card, &config)?)
+        } else {
+            None
+        };
+        let fsrs_next_states = if let Some(desired_retention) = desired_retention {
+            let fsrs = FSRS::new(Some(self.fsrs_weights_for_card(&card, &config)?))?;
             if card.memory_state.is_none() && card.ctype != CardType::New {
                 // Card has been moved or imported into an FSRS deck after weights were set,
                 // and will need its initial memory state to be calculated based on review
@@ -376,22 +451,76 @@ impl Collection {
                 .unwrap_or_default() as u32;
             Some(fsrs.next_states(
                 card.memory_state.map(Into::into),
-                config.inner.desired_retention,
+                desired_retention,
                 days_elapsed,
             ))
         } else {
             None
         };
-        let desired_retention = fsrs_enabled.then_some(config.inner.desired_retention);
+        let sibling_due_days = if config.inner.sibling_dispersal_days > 0 {
+            self.storage
+                .sibling_review_due_days(card.id, card.note_id)?
+                .into_iter()
+                .map(|due| due - timing.days_elapsed as i32)
+                .collect()
+        } else {
+            vec![]
+        };
+        let now = TimestampSecs::now();
+        let adaptive_steps = if config.inner.adaptive_learning_steps {
+            Some(self.adaptive_steps(&card, timing, now, fsrs_next_states.as_ref())?)
+        } else {
+            None
+        };
         Ok(CardStateUpdater {
             fuzz_seed: get_fuzz_seed(&card),
             card,
             deck,
             config,
             timing,
-            now: TimestampSecs::now(),
+            now,
             fsrs_next_states,
             desired_retention,
+            sibling_due_days,
+            adaptive_steps,
+        })
+    }
+
+    /// Gather the card's answers today, which the steps are adapted to.
+    fn adaptive_steps(
+        &self,
+        card: &Card,
+        timing: SchedTimingToday,
+        now: TimestampSecs,
+        fsrs_next_states: Option<&NextStates>,
+    ) -> Result<AdaptiveSteps> {
+        let day_start = timing.next_day_at.adding_secs(-86_400);
+        let mut buttons = self
+            .storage
+            .get_revlog_entries_for_card_after_stamp(card.id, day_start)?
+            .into_iter()
+            .filter(|e| e.button_chosen > 0)
+            .map(|e| e.button_chosen)
+            .collect_vec();
+        buttons.reverse();
+        let streak = |passed: bool| {
+            buttons
+                .iter()
+                .take_while(|&&button| (button > 1) == passed)
+                .count() as u32
+        };
+        Ok(AdaptiveSteps {
+            again_streak: streak(false),
+            pass_streak: streak(true),
+            fsrs_stability_change: fsrs_next_states.zip(card.memory_state).map(
+                |(states, memory)| {
+                    (
+                        states.again.memory.stability / memory.stability,
+                        states.good.memory.stability / memory.stability,
+                    )
+                },
+            ),
+            secs_until_rollover: timing.next_day_at.elapsed_secs_since(now) as u32,
         })
     }
 
@@ -412,11 +541,6 @@ impl Collection {
 
         Ok(self.storage.get_deck_config(config_id)?.unwrap_or_default())
     }
-
-    fn add_leech_tag(&mut self, nid: NoteId) -> Result<()> {
-        self.add_tags_to_notes_inner(&[nid], "leech")?;
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -497,6 +621,7 @@ mod test {
     use crate::card::CardType;
     use crate::deckconfig::ReviewMix;
     use crate::search::SortMode;
+    use crate::tests::NoteAdder;
 
     fn current_state(col: &mut Collection, card_id: CardId) -> CardState {
         col.get_scheduling_states(card_id).unwrap().current
@@ -644,6 +769,49 @@ mod test {
         }};
     }
 
+    #[test]
+    fn adaptive_steps_use_fsrs_stability_change() -> Result<()> {
+        let mut col = Collection::new();
+        col.set_config_bool(BoolKey::Fsrs, true, false)?;
+        col.update_default_deck_config(|config| {
+            config.learn_steps = vec![1.0, 10.0, 60.0];
+            config.adaptive_learning_steps = true;
+        });
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+        let cid = col.storage.all_cards_of_note(note.id)?[0].id;
+
+        // FSRS provides the memory state, but its long-term stability doesn't
+        // push a new card to the last step. The adapted step can only grow
+        // when moved before the rollover, so this holds at any time of day.
+        let states = col.get_scheduling_states(cid)?;
+        let CardState::Normal(NormalState::Learning(good)) = states.good else {
+            panic!("expected learning state: {:?}", states.good);
+        };
+        assert!(good.memory_state.is_some());
+        assert_eq!(good.scheduled_secs, 600);
+        let card = col.storage.get_card(cid)?.unwrap();
+        let adaptive = col.card_state_updater(card)?.adaptive_steps.unwrap();
+        assert_eq!(adaptive.fsrs_stability_change, None);
+
+        // once the card has a memory state, its relative change is used
+        col.answer_good();
+        let card = col.storage.get_card(cid)?.unwrap();
+        let memory = card.memory_state.unwrap();
+        let updater = col.card_state_updater(card)?;
+        let next = updater.fsrs_next_states.as_ref().unwrap();
+        let adaptive = updater.adaptive_steps.unwrap();
+        assert_eq!(adaptive.pass_streak, 1);
+        assert_eq!(
+            adaptive.fsrs_stability_change,
+            Some((
+                next.again.memory.stability / memory.stability,
+                next.good.memory.stability / memory.stability,
+            ))
+        );
+
+        Ok(())
+    }
+
     // FIXME: This fails between 3:50-4:00 GMT
     #[test]
     fn new_limited_by_reviews() -> Result<()> {
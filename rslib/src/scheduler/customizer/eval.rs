@@ -0,0 +1,198 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+use super::parser::BinaryOp;
+use super::parser::Expr;
+use super::parser::Statement;
+use super::parser::UnaryOp;
+use super::parser::Value;
+use crate::prelude::*;
+
+/// The variables a script can read and assign to.
+pub(super) trait Environment {
+    fn get(&self, path: &[String]) -> Result<Value>;
+    fn set(&mut self, path: &[String], value: Value) -> Result<()>;
+}
+
+pub(super) fn run(statements: &[Statement], env: &mut impl Environment) -> Result<()> {
+    for statement in statements {
+        match statement {
+            Statement::Assign(path, expr) => {
+                let value = eval(expr, env)?;
+                env.set(path, value)?;
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if eval(condition, env)?.truthy() {
+                    run(then, env)?;
+                } else {
+                    run(otherwise, env)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn eval(expr: &Expr, env: &impl Environment) -> Result<Value> {
+    Ok(match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Path(path) => env.get(path)?,
+        Expr::Unary(op, expr) => {
+            let value = eval(expr, env)?;
+            match op {
+                UnaryOp::Neg => Value::Number(-number(&value)?),
+                UnaryOp::Not => Value::Bool(!value.truthy()),
+            }
+        }
+        Expr::Binary(BinaryOp::And, lhs, rhs) => {
+            Value::Bool(eval(lhs, env)?.truthy() && eval(rhs, env)?.truthy())
+        }
+        Expr::Binary(BinaryOp::Or, lhs, rhs) => {
+            Value::Bool(eval(lhs, env)?.truthy() || eval(rhs, env)?.truthy())
+        }
+        Expr::Binary(op, lhs, rhs) => binary(*op, eval(lhs, env)?, eval(rhs, env)?)?,
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, env))
+                .collect::<Result<Vec<_>>>()?;
+            call(name, &args)?
+        }
+    })
+}
+
+fn binary(op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value> {
+    Ok(match op {
+        BinaryOp::Eq => Value::Bool(lhs == rhs),
+        BinaryOp::Ne => Value::Bool(lhs != rhs),
+        BinaryOp::Add => match (&lhs, &rhs) {
+            (Value::String(_), _) | (_, Value::String(_)) => Value::String(format!("{lhs}{rhs}")),
+            _ => finite(number(&lhs)? + number(&rhs)?)?,
+        },
+        BinaryOp::Sub => finite(number(&lhs)? - number(&rhs)?)?,
+        BinaryOp::Mul => finite(number(&lhs)? * number(&rhs)?)?,
+        BinaryOp::Div => finite(number(&lhs)? / number(&rhs)?)?,
+        BinaryOp::Rem => finite(number(&lhs)? % number(&rhs)?)?,
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            let ordering = match (&lhs, &rhs) {
+                (Value::String(l), Value::String(r)) => l.cmp(r),
+                _ => number(&lhs)?.total_cmp(&number(&rhs)?),
+            };
+            Value::Bool(match op {
+                BinaryOp::Lt => ordering.is_lt(),
+                BinaryOp::Le => ordering.is_le(),
+                BinaryOp::Gt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            })
+        }
+        BinaryOp::And | BinaryOp::Or => unreachable!("handled by caller"),
+    })
+}
+
+fn call(name: &str, args: &[Value]) -> Result<Value> {
+    let numbers = args.iter().map(number).collect::<Result<Vec<_>>>()?;
+    let single = || match numbers[..] {
+        [n] => Ok(n),
+        _ => invalid_input!("{name}() expects one argument"),
+    };
+    let value = match name {
+        "min" | "max" => {
+            require!(
+                !numbers.is_empty(),
+                "{name}() expects at least one argument"
+            );
+            numbers
+                .iter()
+                .copied()
+                .reduce(|a, b| if name == "min" { a.min(b) } else { a.max(b) })
+                .unwrap()
+        }
+        "clamp" => match numbers[..] {
+            [n, low, high] if low <= high => n.clamp(low, high),
+            _ => invalid_input!("clamp() expects a value, a minimum and a larger maximum"),
+        },
+        "round" => single()?.round(),
+        "floor" => single()?.floor(),
+        "ceil" => single()?.ceil(),
+        "abs" => single()?.abs(),
+        _ => invalid_input!("unknown function in card state script: {name}"),
+    };
+    finite(value)
+}
+
+fn number(value: &Value) -> Result<f64> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        Value::Bool(b) => Ok(*b as u8 as f64),
+        other => invalid_input!("expected a number in card state script, got {other}"),
+    }
+}
+
+/// Division by zero and the like are treated as errors.
+fn finite(n: f64) -> Result<Value> {
+    require!(
+        n.is_finite(),
+        "card state script produced an invalid number"
+    );
+    Ok(Value::Number(n))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::scheduler::customizer::parser::parse;
+
+    #[derive(Default)]
+    struct Vars(HashMap<String, Value>);
+
+    impl Environment for Vars {
+        fn get(&self, path: &[String]) -> Result<Value> {
+            Ok(self.0.get(&path.join(".")).cloned().unwrap_or(Value::Null))
+        }
+
+        fn set(&mut self, path: &[String], value: Value) -> Result<()> {
+            self.0.insert(path.join("."), value);
+            Ok(())
+        }
+    }
+
+    fn eval_str(script: &str) -> Result<Value> {
+        let mut vars = Vars::default();
+        run(&parse(&format!("out = {script}"))?, &mut vars)?;
+        vars.get(&["out".to_string()])
+    }
+
+    #[test]
+    fn evaluation() -> Result<()> {
+        assert_eq!(eval_str("1 + 2 * 3 - 4 / 2")?, Value::Number(5.0));
+        assert_eq!(eval_str("(1 + 2) * 3 % 4")?, Value::Number(1.0));
+        assert_eq!(eval_str("-min(3, 1, 2) + max(1, 5)")?, Value::Number(4.0));
+        assert_eq!(
+            eval_str("clamp(50, 1, 7) + round(1.5)")?,
+            Value::Number(9.0)
+        );
+        assert_eq!(eval_str("\"a\" + 1")?, Value::String("a1".into()));
+        assert_eq!(eval_str("1 < 2 && !(2 <= 1) || x")?, Value::Bool(true));
+        assert_eq!(eval_str("undefined == null")?, Value::Bool(true));
+        assert_eq!(eval_str("\"b\" > \"a\"")?, Value::Bool(true));
+
+        assert!(eval_str("1 / 0").is_err());
+        assert!(eval_str("null + 1").is_err());
+        assert!(eval_str("random()").is_err());
+        assert!(eval_str("clamp(1, 5, 2)").is_err());
+
+        let mut vars = Vars::default();
+        run(
+            &parse("a = 5\nif a > 3 { b = 1 } else if a > 1 { b = 2 } else { b = 3 }")?,
+            &mut vars,
+        )?;
+        assert_eq!(vars.get(&["b".to_string()])?, Value::Number(1.0));
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! A small scripting language for customizing the next states of a card.
+//! Unlike the JavaScript card state customizer, which only runs in the
+//! desktop reviewer, scripts are evaluated by the backend whenever
+//! scheduling states are computed or a card is answered, so they behave the
+//! same on all clients.
+//!
+//! A script is a list of statements, separated by newlines or semicolons:
+//!
+//! ```text
+//! # comments start with a hash
+//! if card.lapses >= 4 && deck.name == "Pharmacology" {
+//!     states.good.interval = min(states.good.interval, 14)
+//! } else {
+//!     states.easy.interval = max(states.easy.interval, states.good.interval + 2)
+//! }
+//! custom_data.seen = 1
+//! ```
+//!
+//! The following can be read:
+//! - `today`: days since the collection was created
+//! - `card.id`, `card.note_id`, `card.reps`, `card.lapses`, `card.interval`,
+//!   `card.ease`, `card.due`, `card.flags`, `card.template`, and `card.type`
+//!   (one of "new", "learn", "review", "relearn")
+//! - `deck.id` and `deck.name`
+//! - `states.current`, `states.again`, `states.hard`, `states.good` and
+//!   `states.easy`, each with `kind` (one of "new", "learning", "review",
+//!   "relearning", "preview"), `interval` (days), `ease`, `seconds` (learning
+//!   delay), `stability` and `difficulty`. Fields that don't apply to a state
+//!   are null.
+//! - `custom_data.<key>`: the card's custom data
+//!
+//! The interval, ease and seconds of the next states, and custom data keys can
+//! be assigned to. Expressions support numbers, strings, true/false/null,
+//! arithmetic, comparisons, `&&`, `||`, `!`, and the functions min(), max(),
+//! clamp(), round(), floor(), ceil() and abs(). There are no loops or sources
+//! of randomness, so evaluation always terminates with the same result.
+
+mod eval;
+mod parser;
+
+use serde_json::Map;
+use serde_json::Number;
+use tracing::warn;
+
+use self::eval::Environment;
+use self::parser::Statement;
+use self::parser::Value;
+use super::states::review::MINIMUM_EASE_FACTOR;
+use super::states::CardState;
+use super::states::FilteredState;
+use super::states::NormalState;
+use super::states::SchedulingStates;
+use crate::card::CardType;
+use crate::config::StringKey;
+use crate::prelude::*;
+
+#[derive(Debug, Clone)]
+pub(crate) struct CardStateScript {
+    statements: Vec<Statement>,
+}
+
+/// What a script can read, in addition to the states.
+pub(crate) struct ScriptInput<'a> {
+    pub card: &'a Card,
+    pub deck: &'a Deck,
+    pub days_elapsed: u32,
+    /// The minimum and maximum review interval of the card, which assigned
+    /// intervals are clamped to.
+    pub interval_limits: (u32, u32),
+}
+
+impl CardStateScript {
+    pub(crate) fn parse(script: &str) -> Result<Self> {
+        Ok(Self {
+            statements: parser::parse(script)?,
+        })
+    }
+
+    /// Modify the provided states according to the script. Returns the card's
+    /// new custom data, if it was changed.
+    pub(crate) fn apply(
+        &self,
+        input: ScriptInput<'_>,
+        states: &mut SchedulingStates,
+    ) -> Result<Option<String>> {
+        let custom_data = if input.card.custom_data.is_empty() {
+            Map::new()
+        } else {
+            serde_json::from_str(&input.card.custom_data).unwrap_or_default()
+        };
+        let mut env = ScriptEnvironment {
+            input,
+            states,
+            custom_data,
+            custom_data_changed: false,
+        };
+        eval::run(&self.statements, &mut env)?;
+        Ok(if env.custom_data_changed {
+            Some(serde_json::to_string(&env.custom_data)?)
+        } else {
+            None
+        })
+    }
+}
+
+impl Collection {
+    /// The parsed card state script, if the user has set one. A script that
+    /// can't be parsed, e.g. because it was synced from a newer client, is
+    /// logged and ignored, so cards can still be studied.
+    pub(crate) fn card_state_script(&self) -> Option<CardStateScript> {
+        let script = self.get_config_string(StringKey::CardStateScript);
+        if script.trim().is_empty() {
+            return None;
+        }
+        CardStateScript::parse(&script)
+            .map_err(|err| warn!("ignoring invalid card state script: {err:?}"))
+            .ok()
+    }
+}
+
+struct ScriptEnvironment<'a> {
+    input: ScriptInput<'a>,
+    states: &'a mut SchedulingStates,
+    custom_data: Map<String, serde_json::Value>,
+    custom_data_changed: bool,
+}
+
+impl ScriptEnvironment<'_> {
+    fn state(&self, name: &str) -> Option<CardState> {
+        Some(match name {
+            "current" => self.states.current,
+            "again" => self.states.again,
+            "hard" => self.states.hard,
+            "good" => self.states.good,
+            "easy" => self.states.easy,
+            _ => return None,
+        })
+    }
+
+    /// The current state can't be modified.
+    fn next_state_mut(&mut self, name: &str) -> Option<&mut CardState> {
+        Some(match name {
+            "again" => &mut self.states.again,
+            "hard" => &mut self.states.hard,
+            "good" => &mut self.states.good,
+            "easy" => &mut self.states.easy,
+            _ => return None,
+        })
+    }
+
+    fn card_value(&self, field: &str) -> Option<Value> {
+        let card = self.input.card;
+        Some(match field {
+            "id" => Value::Number(card.id.0 as f64),
+            "note_id" => Value::Number(card.note_id.0 as f64),
+            "reps" => Value::Number(card.reps as f64),
+            "lapses" => Value::Number(card.lapses as f64),
+            "interval" => Value::Number(card.interval as f64),
+            "ease" => Value::Number(card.ease_factor() as f64),
+            "due" => Value::Number(card.due as f64),
+            "flags" => Value::Number(card.flags as f64),
+            "template" => Value::Number(card.template_idx as f64),
+            "type" => Value::String(
+                match card.ctype {
+                    CardType::New => "new",
+                    CardType::Learn => "learn",
+                    CardType::Review => "review",
+                    CardType::Relearn => "relearn",
+                }
+                .into(),
+            ),
+            _ => return None,
+        })
+    }
+}
+
+impl Environment for ScriptEnvironment<'_> {
+    fn get(&self, path: &[String]) -> Result<Value> {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        let value = match path[..] {
+            ["today"] => Some(Value::Number(self.input.days_elapsed as f64)),
+            ["card", field] => self.card_value(field),
+            ["deck", "id"] => Some(Value::Number(self.input.deck.id.0 as f64)),
+            ["deck", "name"] => Some(Value::String(self.input.deck.human_name())),
+            ["states", state, field] => self
+                .state(state)
+                .and_then(|state| state_value(state, field)),
+            ["custom_data", key] => Some(
+                self.custom_data
+                    .get(key)
+                    .map(json_to_value)
+                    .unwrap_or(Value::Null),
+            ),
+            _ => None,
+        };
+        value.or_invalid(format!(
+            "unknown name in card state script: {}",
+            path.join(".")
+        ))
+    }
+
+    fn set(&mut self, path: &[String], value: Value) -> Result<()> {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        match path[..] {
+            ["states", state, field] => {
+                let Some(state) = self.next_state_mut(state) else {
+                    invalid_input!("only next states can be assigned to: {}", path.join("."));
+                };
+                set_state_value(state, field, value, self.input.interval_limits)
+            }
+            ["custom_data", key] => {
+                let json = match value {
+                    Value::Null => {
+                        self.custom_data.remove(key);
+                        self.custom_data_changed = true;
+                        return Ok(());
+                    }
+                    Value::Bool(b) => serde_json::Value::Bool(b),
+                    // whole numbers are stored without a fractional part
+                    Value::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+                        serde_json::Value::Number(Number::from(n as i64))
+                    }
+                    Value::Number(n) => Number::from_f64(n)
+                        .map(serde_json::Value::Number)
+                        .unwrap_or_default(),
+                    Value::String(s) => serde_json::Value::String(s),
+                };
+                self.custom_data.insert(key.to_string(), json);
+                self.custom_data_changed = true;
+                Ok(())
+            }
+            _ => invalid_input!("can not assign to {} in card state script", path.join(".")),
+        }
+    }
+}
+
+fn json_to_value(json: &serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(*b),
+        serde_json::Value::Number(n) => n.as_f64().map(Value::Number).unwrap_or(Value::Null),
+        serde_json::Value::String(s) => Value::String(s.clone()),
+        other => Value::String(other.to_string()),
+    }
+}
+
+/// The normal state, or the normal state wrapped by a rescheduling filtered
+/// deck.
+fn normal_state_mut(state: &mut CardState) -> Option<&mut NormalState> {
+    match state {
+        CardState::Normal(normal) => Some(normal),
+        CardState::Filtered(FilteredState::Rescheduling(filtered)) => {
+            Some(&mut filtered.original_state)
+        }
+        CardState::Filtered(FilteredState::Preview(_)) => None,
+    }
+}
+
+fn state_value(mut state: CardState, field: &str) -> Option<Value> {
+    if let ("kind", CardState::Filtered(FilteredState::Preview(_))) = (field, state) {
+        return Some(Value::String("preview".into()));
+    }
+    if let ("seconds", CardState::Filtered(FilteredState::Preview(preview))) = (field, state) {
+        return Some(Value::Number(preview.scheduled_secs as f64));
+    }
+    let normal = normal_state_mut(&mut state).copied();
+    let review = normal.and_then(|n| n.review_state());
+    let memory_state = match normal {
+        Some(NormalState::Learning(learn)) => learn.memory_state,
+        _ => review.and_then(|r| r.memory_state),
+    };
+    let number = |n: Option<f64>| Some(n.map(Value::Number).unwrap_or(Value::Null));
+    match field {
+        "kind" => Some(Value::String(
+            match normal? {
+                NormalState::New(_) => "new",
+                NormalState::Learning(_) => "learning",
+                NormalState::Review(_) => "review",
+                NormalState::Relearning(_) => "relearning",
+            }
+            .into(),
+        )),
+        "interval" => number(review.map(|r| r.scheduled_days as f64)),
+        "ease" => number(review.map(|r| r.ease_factor as f64)),
+        "seconds" => number(match normal {
+            Some(NormalState::Learning(learn)) => Some(learn.scheduled_secs as f64),
+            Some(NormalState::Relearning(relearn)) => Some(relearn.learning.scheduled_secs as f64),
+            _ => None,
+        }),
+        "stability" => number(memory_state.map(|m| m.stability as f64)),
+        "difficulty" => number(memory_state.map(|m| m.difficulty as f64)),
+        _ => None,
+    }
+}
+
+/// Intervals are clamped to `interval_limits`, and eases to the scheduler's
+/// minimum.
+fn set_state_value(
+    state: &mut CardState,
+    field: &str,
+    value: Value,
+    (minimum_interval, maximum_interval): (u32, u32),
+) -> Result<()> {
+    let Value::Number(number) = value else {
+        invalid_input!("{field} must be set to a number, got {value}");
+    };
+    require!(number >= 0.0, "{field} can not be negative");
+    let normal = match state {
+        CardState::Filtered(FilteredState::Preview(preview)) if field == "seconds" => {
+            preview.scheduled_secs = number.round() as u32;
+            return Ok(());
+        }
+        other => normal_state_mut(other),
+    };
+    let interval = (number.round() as u32).clamp(minimum_interval, maximum_interval);
+    let ease = (number as f32).max(MINIMUM_EASE_FACTOR);
+    match (field, normal) {
+        ("interval", Some(NormalState::Review(review))) => review.scheduled_days = interval,
+        ("interval", Some(NormalState::Relearning(relearn))) => {
+            relearn.review.scheduled_days = interval
+        }
+        ("ease", Some(NormalState::Review(review))) => review.ease_factor = ease,
+        ("ease", Some(NormalState::Relearning(relearn))) => relearn.review.ease_factor = ease,
+        ("seconds", Some(NormalState::Learning(learn))) => {
+            learn.scheduled_secs = number.round() as u32
+        }
+        ("seconds", Some(NormalState::Relearning(relearn))) => {
+            relearn.learning.scheduled_secs = number.round() as u32
+        }
+        // ignore fields that don't apply to the state, so scripts don't need
+        // to check the kind of every state
+        ("interval" | "ease" | "seconds", _) => (),
+        _ => invalid_input!("can not assign to {field} in card state script"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scheduler::states::ReviewState;
+    use crate::tests::*;
+
+    fn review_states() -> SchedulingStates {
+        let review = |scheduled_days| {
+            CardState::from(ReviewState {
+                scheduled_days,
+                ..Default::default()
+            })
+        };
+        SchedulingStates {
+            current: review(10),
+            again: review(1),
+            hard: review(12),
+            good: review(25),
+            easy: review(40),
+        }
+    }
+
+    fn interval(state: CardState) -> u32 {
+        state.review_state().unwrap().scheduled_days
+    }
+
+    #[test]
+    fn scripts_modify_states_and_custom_data() -> Result<()> {
+        let card = Card {
+            lapses: 5,
+            custom_data: r#"{"n":1}"#.into(),
+            ..Default::default()
+        };
+        let deck = Deck::new_normal();
+        let script = CardStateScript::parse(
+            "if card.lapses > 4 { states.good.interval = min(states.good.interval, 14) }
+            states.easy.interval = states.good.interval + 1
+            custom_data.n = custom_data.n + 1",
+        )?;
+        let mut states = review_states();
+        let input = ScriptInput {
+            card: &card,
+            deck: &deck,
+            days_elapsed: 0,
+            interval_limits: (1, 36500),
+        };
+        let custom_data = script.apply(input, &mut states)?;
+        assert_eq!(interval(states.good), 14);
+        assert_eq!(interval(states.easy), 15);
+        assert_eq!(custom_data.as_deref(), Some(r#"{"n":2}"#));
+
+        // the current state is read-only
+        let script = CardStateScript::parse("states.current.interval = 1")?;
+        let input = ScriptInput {
+            card: &card,
+            deck: &deck,
+            days_elapsed: 0,
+            interval_limits: (1, 36500),
+        };
+        assert!(script.apply(input, &mut states).is_err());
+
+        // assigned values are kept within the scheduler's limits
+        let script =
+            CardStateScript::parse("states.good.interval = 1000000000000; states.good.ease = 0")?;
+        let input = ScriptInput {
+            card: &card,
+            deck: &deck,
+            days_elapsed: 0,
+            interval_limits: (1, 36500),
+        };
+        script.apply(input, &mut states)?;
+        assert_eq!(interval(states.good), 36500);
+        assert_eq!(states.good.review_state().unwrap().ease_factor, 1.3);
+        Ok(())
+    }
+
+    #[test]
+    fn script_applies_when_answering() -> Result<()> {
+        let mut col = Collection::new();
+        col.set_config_string_inner(
+            StringKey::CardStateScript,
+            "states.easy.interval = 100; custom_data.x = \"y\"",
+        )?;
+        NoteAdder::basic(&mut col).add(&mut col);
+        let card_id = col.search_cards("", crate::search::SortMode::NoOrder)?[0];
+        let states = col.get_scheduling_states(card_id)?;
+        assert_eq!(interval(states.easy), 100);
+
+        col.answer_easy();
+        let card = col.storage.get_card(card_id)?.unwrap();
+        assert_eq!(card.interval, 100);
+        assert_eq!(card.custom_data, r#"{"x":"y"}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_script_is_ignored() -> Result<()> {
+        let mut col = Collection::new();
+        col.set_config_string_inner(StringKey::CardStateScript, "states.good.interval =")?;
+        NoteAdder::basic(&mut col).add(&mut col);
+        let card_id = col.search_cards("", crate::search::SortMode::NoOrder)?[0];
+        col.get_scheduling_states(card_id)?;
+        col.answer_good();
+        assert_eq!(col.storage.get_card(card_id)?.unwrap().reps, 1);
+        Ok(())
+    }
+}
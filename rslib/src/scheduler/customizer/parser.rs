@@ -0,0 +1,453 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+use std::fmt;
+
+use crate::prelude::*;
+
+/// Longer scripts are rejected, to keep evaluation cheap.
+const MAX_SCRIPT_LEN: usize = 10_000;
+/// Limits the nesting of expressions and blocks.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    pub(super) fn truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::String(s) => !s.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expr {
+    Literal(Value),
+    Path(Vec<String>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Statement {
+    Assign(Vec<String>, Expr),
+    If {
+        condition: Expr,
+        then: Vec<Statement>,
+        otherwise: Vec<Statement>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Ident(String),
+    Symbol(&'static str),
+    /// A newline or semicolon.
+    Separator,
+}
+
+/// Two-character symbols must come first, so they are matched before their
+/// first character.
+static SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", ",", ".", "=", "<", ">", "+", "-", "*",
+    "/", "%", "!",
+];
+
+pub(super) fn parse(script: &str) -> Result<Vec<Statement>> {
+    require!(script.len() <= MAX_SCRIPT_LEN, "card state script too long");
+    let mut parser = Parser {
+        tokens: tokenize(script)?,
+        pos: 0,
+        depth: 0,
+    };
+    let statements = parser.block()?;
+    if let Some(token) = parser.peek() {
+        invalid_input!("unexpected {token:?} in card state script");
+    }
+    Ok(statements)
+}
+
+fn tokenize(script: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = script.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '\n' | ';' => {
+                chars.next();
+                tokens.push(Token::Separator);
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '0'..='9' => {
+                let mut number = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                let number = number
+                    .parse::<f64>()
+                    .or_invalid(format!("invalid number in card state script: {number}"))?;
+                tokens.push(Token::Number(number));
+            }
+            '"' => {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => string.push('\n'),
+                            Some(c) => string.push(c),
+                            None => invalid_input!("unterminated string in card state script"),
+                        },
+                        Some(c) => string.push(c),
+                        None => invalid_input!("unterminated string in card state script"),
+                    }
+                }
+                tokens.push(Token::String(string));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => {
+                let next_two: String = chars.clone().take(2).collect();
+                let Some(symbol) = SYMBOLS.iter().find(|s| next_two.starts_with(**s)) else {
+                    invalid_input!("unexpected '{c}' in card state script");
+                };
+                for _ in 0..symbol.len() {
+                    chars.next();
+                }
+                tokens.push(Token::Symbol(*symbol));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn next_is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s == keyword)
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        match self.next() {
+            Some(Token::Symbol(s)) if s == symbol => Ok(()),
+            other => invalid_input!("expected '{symbol}' in card state script, got {other:?}"),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Ident(ident)) => Ok(ident),
+            other => invalid_input!("expected a name in card state script, got {other:?}"),
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.peek() == Some(&Token::Separator) {
+            self.pos += 1;
+        }
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        require!(
+            self.depth <= MAX_DEPTH,
+            "card state script nested too deeply"
+        );
+        Ok(())
+    }
+
+    /// Statements until the end of the script or a closing brace.
+    fn block(&mut self) -> Result<Vec<Statement>> {
+        let mut statements = vec![];
+        loop {
+            self.skip_separators();
+            if self.peek().is_none() || self.next_is_symbol("}") {
+                return Ok(statements);
+            }
+            statements.push(self.statement()?);
+            match self.peek() {
+                None | Some(Token::Separator) => (),
+                Some(Token::Symbol("}")) => (),
+                Some(token) => invalid_input!("unexpected {token:?} in card state script"),
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement> {
+        if self.next_is_keyword("if") {
+            self.next();
+            return self.if_statement();
+        }
+        let path = self.path()?;
+        self.expect_symbol("=")?;
+        Ok(Statement::Assign(path, self.expr()?))
+    }
+
+    /// Called after the 'if' keyword has been consumed.
+    fn if_statement(&mut self) -> Result<Statement> {
+        self.enter()?;
+        let condition = self.expr()?;
+        let then = self.braced_block()?;
+        let otherwise = if self.next_is_keyword("else") {
+            self.next();
+            if self.next_is_keyword("if") {
+                self.next();
+                vec![self.if_statement()?]
+            } else {
+                self.braced_block()?
+            }
+        } else {
+            vec![]
+        };
+        self.depth -= 1;
+        Ok(Statement::If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+
+    fn braced_block(&mut self) -> Result<Vec<Statement>> {
+        self.expect_symbol("{")?;
+        let statements = self.block()?;
+        self.expect_symbol("}")?;
+        Ok(statements)
+    }
+
+    fn path(&mut self) -> Result<Vec<String>> {
+        let mut path = vec![self.expect_ident()?];
+        while self.next_is_symbol(".") {
+            self.next();
+            path.push(self.expect_ident()?);
+        }
+        Ok(path)
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.enter()?;
+        let expr = self.binary(0)?;
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    /// Parse binary operators of the given precedence level or higher.
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        static LEVELS: &[&[(&str, BinaryOp)]] = &[
+            &[("||", BinaryOp::Or)],
+            &[("&&", BinaryOp::And)],
+            &[("==", BinaryOp::Eq), ("!=", BinaryOp::Ne)],
+            &[
+                ("<", BinaryOp::Lt),
+                ("<=", BinaryOp::Le),
+                (">", BinaryOp::Gt),
+                (">=", BinaryOp::Ge),
+            ],
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+            &[
+                ("*", BinaryOp::Mul),
+                ("/", BinaryOp::Div),
+                ("%", BinaryOp::Rem),
+            ],
+        ];
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let mut lhs = self.binary(level + 1)?;
+        while let Some((_, op)) = operators.iter().find(|(s, _)| self.next_is_symbol(s)) {
+            self.next();
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        let op = if self.next_is_symbol("-") {
+            UnaryOp::Neg
+        } else if self.next_is_symbol("!") {
+            UnaryOp::Not
+        } else {
+            return self.primary();
+        };
+        self.next();
+        self.enter()?;
+        let expr = Expr::Unary(op, Box::new(self.unary()?));
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let expr = match self.next() {
+            Some(Token::Number(n)) => Expr::Literal(Value::Number(n)),
+            Some(Token::String(s)) => Expr::Literal(Value::String(s)),
+            Some(Token::Symbol("(")) => {
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                expr
+            }
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "null" => Expr::Literal(Value::Null),
+                _ => {
+                    // re-read the name as part of a path
+                    self.pos -= 1;
+                    let path = self.path()?;
+                    if self.next_is_symbol("(") {
+                        self.next();
+                        Expr::Call(path.join("."), self.call_args()?)
+                    } else {
+                        Expr::Path(path)
+                    }
+                }
+            },
+            other => invalid_input!("expected a value in card state script, got {other:?}"),
+        };
+        Ok(expr)
+    }
+
+    /// Called after the opening parenthesis has been consumed.
+    fn call_args(&mut self) -> Result<Vec<Expr>> {
+        let mut args = vec![];
+        while !self.next_is_symbol(")") {
+            args.push(self.expr()?);
+            if !self.next_is_symbol(")") {
+                self.expect_symbol(",")?;
+            }
+        }
+        self.next();
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn path(s: &str) -> Vec<String> {
+        s.split('.').map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parsing() -> Result<()> {
+        assert_eq!(parse("")?, vec![]);
+        assert_eq!(
+            parse("states.good.interval = 1 + 2 * 3 # comment")?,
+            vec![Statement::Assign(
+                path("states.good.interval"),
+                Expr::Binary(
+                    BinaryOp::Add,
+                    Box::new(Expr::Literal(Value::Number(1.0))),
+                    Box::new(Expr::Binary(
+                        BinaryOp::Mul,
+                        Box::new(Expr::Literal(Value::Number(2.0))),
+                        Box::new(Expr::Literal(Value::Number(3.0))),
+                    )),
+                ),
+            )]
+        );
+        assert_eq!(
+            parse("if card.lapses >= 2 { a = min(1, b) } else { a = \"x\" }")?,
+            vec![Statement::If {
+                condition: Expr::Binary(
+                    BinaryOp::Ge,
+                    Box::new(Expr::Path(path("card.lapses"))),
+                    Box::new(Expr::Literal(Value::Number(2.0))),
+                ),
+                then: vec![Statement::Assign(
+                    path("a"),
+                    Expr::Call(
+                        "min".into(),
+                        vec![Expr::Literal(Value::Number(1.0)), Expr::Path(path("b"))]
+                    ),
+                )],
+                otherwise: vec![Statement::Assign(
+                    path("a"),
+                    Expr::Literal(Value::String("x".into()))
+                )],
+            }]
+        );
+        assert_eq!(parse("a = 1; b = 2\n\nc = 3")?.len(), 3);
+
+        assert!(parse("a = ").is_err());
+        assert!(parse("a = 1 b = 2").is_err());
+        assert!(parse("if a { b = 1").is_err());
+        assert!(parse("a = \"unterminated").is_err());
+        assert!(parse("a = 1 @ 2").is_err());
+        assert!(parse(&format!("a = {}1{}", "(".repeat(100), ")".repeat(100))).is_err());
+        Ok(())
+    }
+}
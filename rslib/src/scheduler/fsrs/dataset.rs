@@ -0,0 +1,251 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! The reviews FSRS trains on can be exported without note content or real
+//! card ids, so that review data can be shared and weights benchmarked on
+//! other collections. Weights can be trained on an exported file with
+//! ComputeFsrsWeightsFromItems.
+
+use std::path::Path;
+
+use anki_io::read_file;
+use anki_io::write_file;
+use anki_proto::scheduler::FsrsItemColumns;
+use anki_proto::scheduler::FsrsItemsFormat;
+use fsrs::FSRSItem;
+use fsrs::FSRSReview;
+use itertools::Itertools;
+use prost::Message;
+
+use super::weights::reviews_for_fsrs;
+use crate::prelude::*;
+use crate::revlog::RevlogEntry;
+
+const CSV_HEADER: [&str; 4] = ["card_id", "review_th", "delta_t", "rating"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ExportedReview {
+    card_id: u64,
+    delta_t: u32,
+    rating: u32,
+}
+
+impl Collection {
+    /// Returns the number of reviews written.
+    pub fn export_fsrs_items(
+        &mut self,
+        search: &str,
+        path: &Path,
+        format: FsrsItemsFormat,
+    ) -> Result<u32> {
+        let next_day_at = self.timing_today()?.next_day_at;
+        let revlogs = self.revlog_for_srs(search)?;
+        let reviews = anonymized_reviews(revlogs, next_day_at, rand::random());
+        let data = match format {
+            FsrsItemsFormat::Csv => reviews_to_csv(&reviews)?,
+            FsrsItemsFormat::Columnar => reviews_to_columns(&reviews).encode_to_vec(),
+        };
+        write_file(path, data)?;
+        Ok(reviews.len() as u32)
+    }
+}
+
+/// Read a file written by [Collection::export_fsrs_items] into items for
+/// training.
+pub(crate) fn read_fsrs_items(path: &Path) -> Result<Vec<FSRSItem>> {
+    let data = read_file(path)?;
+    let reviews = if data.starts_with(CSV_HEADER[0].as_bytes()) {
+        reviews_from_csv(&data)?
+    } else {
+        reviews_from_columns(FsrsItemColumns::decode(data.as_slice())?)?
+    };
+    Ok(items_from_reviews(&reviews))
+}
+
+/// Card ids are creation times, so they are replaced with a hash that can't
+/// be traced back to the card without the salt.
+fn anonymized_reviews(
+    revlogs: Vec<RevlogEntry>,
+    next_day_at: TimestampSecs,
+    salt: u64,
+) -> Vec<ExportedReview> {
+    revlogs
+        .into_iter()
+        .group_by(|r| r.cid)
+        .into_iter()
+        .filter_map(|(card_id, entries)| {
+            let (entries, delta_ts, _) = reviews_for_fsrs(entries.collect(), next_day_at, true)?;
+            let card_id = hash_card_id(card_id, salt);
+            Some(
+                entries
+                    .into_iter()
+                    .zip(delta_ts)
+                    .map(|(entry, delta_t)| ExportedReview {
+                        card_id,
+                        delta_t,
+                        rating: entry.button_chosen as u32,
+                    })
+                    .collect_vec(),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+fn hash_card_id(card_id: CardId, salt: u64) -> u64 {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&salt.to_le_bytes());
+    hasher.update(&card_id.0.to_le_bytes());
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hasher.finalize().as_bytes()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn reviews_to_csv(reviews: &[ExportedReview]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(CSV_HEADER).or_invalid("invalid csv")?;
+    for group in &reviews.iter().group_by(|r| r.card_id) {
+        for (review_th, review) in group.1.enumerate() {
+            writer
+                .write_record([
+                    review.card_id.to_string(),
+                    (review_th + 1).to_string(),
+                    review.delta_t.to_string(),
+                    review.rating.to_string(),
+                ])
+                .or_invalid("invalid csv")?;
+        }
+    }
+    writer.into_inner().or_invalid("invalid csv")
+}
+
+fn reviews_from_csv(data: &[u8]) -> Result<Vec<ExportedReview>> {
+    let mut reader = csv::Reader::from_reader(data);
+    let headers = reader.headers().or_invalid("invalid csv")?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .or_invalid(format!("missing column: {name}"))
+    };
+    let (card_id, delta_t, rating) = (column("card_id")?, column("delta_t")?, column("rating")?);
+    reader
+        .records()
+        .map(|record| {
+            let record = record.or_invalid("invalid csv")?;
+            let field = |idx: usize| {
+                record
+                    .get(idx)
+                    .and_then(|field| field.trim().parse::<u64>().ok())
+                    .or_invalid("invalid number in csv")
+            };
+            Ok(ExportedReview {
+                card_id: field(card_id)?,
+                delta_t: field(delta_t)? as u32,
+                rating: field(rating)? as u32,
+            })
+        })
+        .collect()
+}
+
+fn reviews_to_columns(reviews: &[ExportedReview]) -> FsrsItemColumns {
+    FsrsItemColumns {
+        card_ids: reviews.iter().map(|r| r.card_id).collect(),
+        delta_ts: reviews.iter().map(|r| r.delta_t).collect(),
+        ratings: reviews.iter().map(|r| r.rating).collect(),
+    }
+}
+
+fn reviews_from_columns(columns: FsrsItemColumns) -> Result<Vec<ExportedReview>> {
+    require!(
+        columns.card_ids.len() == columns.delta_ts.len()
+            && columns.card_ids.len() == columns.ratings.len(),
+        "columns have different lengths"
+    );
+    Ok(columns
+        .card_ids
+        .into_iter()
+        .zip(columns.delta_ts)
+        .zip(columns.ratings)
+        .map(|((card_id, delta_t), rating)| ExportedReview {
+            card_id,
+            delta_t,
+            rating,
+        })
+        .collect())
+}
+
+/// Like training on a collection, each review after a card's first becomes
+/// an item containing the reviews up to it.
+fn items_from_reviews(reviews: &[ExportedReview]) -> Vec<FSRSItem> {
+    let mut items = reviews
+        .iter()
+        .filter(|r| (1..=4).contains(&r.rating))
+        .group_by(|r| r.card_id)
+        .into_iter()
+        .flat_map(|(_, group)| {
+            let reviews = group
+                .map(|r| FSRSReview {
+                    rating: r.rating,
+                    delta_t: r.delta_t,
+                })
+                .collect_vec();
+            (2..=reviews.len())
+                .map(|len| FSRSItem {
+                    reviews: reviews[..len].to_vec(),
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+    items.sort_by_cached_key(|item| item.reviews.len());
+    items
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::revlog::RevlogReviewKind;
+    use crate::scheduler::fsrs::weights::tests::revlog;
+
+    const NEXT_DAY_AT: TimestampSecs = TimestampSecs(86400 * 100);
+
+    #[test]
+    fn exported_reviews_round_trip() -> Result<()> {
+        let revlogs = [
+            (1, RevlogReviewKind::Learning, 10),
+            (1, RevlogReviewKind::Review, 5),
+        ]
+        .into_iter()
+        .chain([
+            (2, RevlogReviewKind::Learning, 3),
+            (2, RevlogReviewKind::Review, 2),
+            (2, RevlogReviewKind::Review, 0),
+        ])
+        .map(|(cid, kind, days_ago)| RevlogEntry {
+            cid: CardId(cid),
+            ..revlog(kind, days_ago)
+        })
+        .collect_vec();
+        let reviews = anonymized_reviews(revlogs, NEXT_DAY_AT, 123);
+        assert_eq!(reviews.len(), 5);
+        assert_ne!(reviews[0].card_id, 1);
+        assert_eq!(
+            reviews.iter().map(|r| r.delta_t).collect_vec(),
+            [0, 5, 0, 1, 2]
+        );
+
+        let csv = reviews_to_csv(&reviews)?;
+        assert!(String::from_utf8(csv.clone())
+            .unwrap()
+            .starts_with("card_id,review_th,delta_t,rating\n"));
+        assert_eq!(reviews_from_csv(&csv)?, reviews);
+        assert_eq!(reviews_from_columns(reviews_to_columns(&reviews))?, reviews);
+
+        let items = items_from_reviews(&reviews);
+        assert_eq!(
+            items.iter().map(|i| i.reviews.len()).collect_vec(),
+            [2, 2, 3]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! A breakdown of how well weights predict the recall of past reviews, to
+//! help judge whether they are an improvement over the current weights or
+//! SM-2. Each review after a card's first is predicted from the memory state
+//! replayed from the reviews before it.
+
+use std::collections::HashMap;
+
+use anki_proto::scheduler::fsrs_evaluation_report::CalibrationBin;
+use anki_proto::scheduler::fsrs_evaluation_report::Group;
+use anki_proto::scheduler::fsrs_evaluation_report::IntervalGroup;
+use anki_proto::scheduler::FsrsEvaluationReport;
+use fsrs::MemoryState;
+use fsrs::FSRS;
+use itertools::Itertools;
+
+use super::weights::reviews_for_fsrs;
+use crate::prelude::*;
+use crate::revlog::RevlogEntry;
+use crate::search::SortMode;
+
+const CALIBRATION_BINS: usize = 10;
+/// Lower bounds of the days-elapsed buckets.
+const INTERVAL_BUCKETS: [u32; 9] = [0, 1, 2, 4, 8, 16, 31, 91, 366];
+
+impl Collection {
+    pub(crate) fn fsrs_evaluation_report(
+        &mut self,
+        weights: &[f32],
+        search: &str,
+        sm2_retention: f32,
+    ) -> Result<FsrsEvaluationReport> {
+        let next_day_at = self.timing_today()?.next_day_at;
+        let guard = self.search_cards_into_table(search, SortMode::NoOrder)?;
+        let decks = guard
+            .col
+            .storage
+            .all_searched_cards()?
+            .into_iter()
+            .map(|card| (card.id, card.original_or_current_deck_id()))
+            .collect();
+        let revlogs = guard
+            .col
+            .storage
+            .get_revlog_entries_for_searched_cards_in_card_order()?;
+        let fsrs = FSRS::new(Some(weights))?;
+        Ok(evaluation_report(
+            &fsrs,
+            revlogs,
+            &decks,
+            next_day_at,
+            sm2_retention,
+        ))
+    }
+}
+
+fn evaluation_report(
+    fsrs: &FSRS,
+    revlogs: Vec<RevlogEntry>,
+    decks: &HashMap<CardId, DeckId>,
+    next_day_at: TimestampSecs,
+    sm2_retention: f32,
+) -> FsrsEvaluationReport {
+    let mut report = ReportBuilder::default();
+    for (card_id, entries) in &revlogs.into_iter().group_by(|r| r.cid) {
+        let Some((entries, delta_ts, _)) = reviews_for_fsrs(entries.collect(), next_day_at, true)
+        else {
+            continue;
+        };
+        let mut state: Option<MemoryState> = None;
+        let mut previous: Option<&RevlogEntry> = None;
+        for (entry, delta_t) in entries.iter().zip(delta_ts) {
+            if let (Some(state), Some(previous)) = (state, previous) {
+                let review = Review {
+                    fsrs: fsrs.current_retrievability(state, delta_t),
+                    sm2: sm2_retrievability(fsrs, previous, delta_t, sm2_retention),
+                    recalled: entry.button_chosen > 1,
+                };
+                report.add(review, entry, decks.get(&card_id).copied(), delta_t);
+            }
+            let next = fsrs.next_states(state, 0.9, delta_t);
+            state = Some(
+                match entry.button_chosen {
+                    1 => next.again,
+                    2 => next.hard,
+                    3 => next.good,
+                    _ => next.easy,
+                }
+                .memory,
+            );
+            previous = Some(entry);
+        }
+    }
+    report.build()
+}
+
+/// The retrievability SM-2 implied when it scheduled the review, given the
+/// interval and ease it assigned at the previous review.
+fn sm2_retrievability(
+    fsrs: &FSRS,
+    previous: &RevlogEntry,
+    delta_t: u32,
+    sm2_retention: f32,
+) -> f32 {
+    // learning steps are scheduled in seconds, and are due the next day at the
+    // latest
+    let interval = previous.interval.max(1) as f32;
+    let ease = if previous.ease_factor == 0 {
+        2.5
+    } else {
+        previous.ease_factor as f32 / 1000.0
+    };
+    let state = fsrs.memory_state_from_sm2(ease, interval, sm2_retention);
+    fsrs.current_retrievability(state, delta_t)
+}
+
+#[derive(Clone, Copy)]
+struct Review {
+    fsrs: f32,
+    sm2: f32,
+    recalled: bool,
+}
+
+#[derive(Default)]
+struct GroupBuilder {
+    reviews: u32,
+    recalled: u32,
+    fsrs: f64,
+    sm2: f64,
+    fsrs_log_loss: f64,
+    sm2_log_loss: f64,
+}
+
+impl GroupBuilder {
+    fn add(&mut self, review: Review) {
+        self.reviews += 1;
+        self.recalled += review.recalled as u32;
+        self.fsrs += review.fsrs as f64;
+        self.sm2 += review.sm2 as f64;
+        self.fsrs_log_loss += log_loss(review.fsrs, review.recalled);
+        self.sm2_log_loss += log_loss(review.sm2, review.recalled);
+    }
+
+    fn build(&self) -> Group {
+        let mean = |total: f64| {
+            if self.reviews == 0 {
+                0.0
+            } else {
+                (total / self.reviews as f64) as f32
+            }
+        };
+        Group {
+            reviews: self.reviews,
+            actual_retention: mean(self.recalled as f64),
+            fsrs_retention: mean(self.fsrs),
+            sm2_retention: mean(self.sm2),
+            fsrs_log_loss: mean(self.fsrs_log_loss),
+            sm2_log_loss: mean(self.sm2_log_loss),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CalibrationBuilder {
+    bins: [(u32, f64, u32); CALIBRATION_BINS],
+}
+
+impl CalibrationBuilder {
+    fn add(&mut self, predicted: f32, recalled: bool) {
+        let idx = ((predicted * CALIBRATION_BINS as f32) as usize).min(CALIBRATION_BINS - 1);
+        let bin = &mut self.bins[idx];
+        bin.0 += 1;
+        bin.1 += predicted as f64;
+        bin.2 += recalled as u32;
+    }
+
+    fn build(&self) -> Vec<CalibrationBin> {
+        self.bins
+            .iter()
+            .enumerate()
+            .map(|(idx, &(reviews, predicted, recalled))| CalibrationBin {
+                min_predicted: idx as f32 / CALIBRATION_BINS as f32,
+                max_predicted: (idx + 1) as f32 / CALIBRATION_BINS as f32,
+                reviews,
+                mean_predicted: if reviews == 0 {
+                    0.0
+                } else {
+                    (predicted / reviews as f64) as f32
+                },
+                actual_retention: if reviews == 0 {
+                    0.0
+                } else {
+                    recalled as f32 / reviews as f32
+                },
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct ReportBuilder {
+    overall: GroupBuilder,
+    fsrs_calibration: CalibrationBuilder,
+    sm2_calibration: CalibrationBuilder,
+    by_state: HashMap<i32, GroupBuilder>,
+    by_deck: HashMap<i64, GroupBuilder>,
+    by_interval: [GroupBuilder; INTERVAL_BUCKETS.len()],
+}
+
+impl ReportBuilder {
+    fn add(&mut self, review: Review, entry: &RevlogEntry, deck: Option<DeckId>, delta_t: u32) {
+        self.overall.add(review);
+        self.fsrs_calibration.add(review.fsrs, review.recalled);
+        self.sm2_calibration.add(review.sm2, review.recalled);
+        self.by_state
+            .entry(entry.review_kind as i32)
+            .or_default()
+            .add(review);
+        if let Some(deck) = deck {
+            self.by_deck.entry(deck.0).or_default().add(review);
+        }
+        let bucket = INTERVAL_BUCKETS
+            .iter()
+            .rposition(|&min| delta_t >= min)
+            .unwrap_or_default();
+        self.by_interval[bucket].add(review);
+    }
+
+    fn build(self) -> FsrsEvaluationReport {
+        FsrsEvaluationReport {
+            overall: Some(self.overall.build()),
+            fsrs_calibration: self.fsrs_calibration.build(),
+            sm2_calibration: self.sm2_calibration.build(),
+            by_state: self
+                .by_state
+                .iter()
+                .map(|(kind, group)| (*kind, group.build()))
+                .collect(),
+            by_deck: self
+                .by_deck
+                .iter()
+                .map(|(deck, group)| (*deck, group.build()))
+                .collect(),
+            by_interval: self
+                .by_interval
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| group.reviews > 0)
+                .map(|(idx, group)| IntervalGroup {
+                    min_days: INTERVAL_BUCKETS[idx],
+                    max_days: INTERVAL_BUCKETS
+                        .get(idx + 1)
+                        .map(|next| next - 1)
+                        .unwrap_or_default(),
+                    group: Some(group.build()),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn log_loss(predicted: f32, recalled: bool) -> f64 {
+    let predicted = (predicted as f64).clamp(1e-4, 1.0 - 1e-4);
+    if recalled {
+        -predicted.ln()
+    } else {
+        -(1.0 - predicted).ln()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::revlog::RevlogReviewKind;
+    use crate::scheduler::fsrs::weights::tests::revlog;
+
+    const NEXT_DAY_AT: TimestampSecs = TimestampSecs(86400 * 100);
+
+    fn card_revlog(cid: i64, entries: Vec<RevlogEntry>) -> Vec<RevlogEntry> {
+        entries
+            .into_iter()
+            .map(|entry| RevlogEntry {
+                cid: CardId(cid),
+                interval: 3,
+                ..entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reviews_are_grouped() {
+        let fsrs = FSRS::new(Some(&[])).unwrap();
+        let mut revlogs = card_revlog(
+            1,
+            vec![
+                revlog(RevlogReviewKind::Learning, 10),
+                revlog(RevlogReviewKind::Review, 7),
+                RevlogEntry {
+                    button_chosen: 1,
+                    ..revlog(RevlogReviewKind::Review, 1)
+                },
+            ],
+        );
+        // cards without learning steps are ignored
+        revlogs.extend(card_revlog(
+            2,
+            vec![
+                revlog(RevlogReviewKind::Review, 10),
+                revlog(RevlogReviewKind::Review, 1),
+            ],
+        ));
+        let decks = [(CardId(1), DeckId(5))].into_iter().collect();
+        let report = evaluation_report(&fsrs, revlogs, &decks, NEXT_DAY_AT, 0.9);
+
+        let overall = report.overall.unwrap();
+        assert_eq!(overall.reviews, 2);
+        assert_eq!(overall.actual_retention, 0.5);
+        assert_eq!(report.by_deck[&5].reviews, 2);
+        assert_eq!(
+            report.by_state[&(RevlogReviewKind::Review as i32)].reviews,
+            2
+        );
+        assert_eq!(
+            report
+                .by_interval
+                .iter()
+                .map(|g| (g.min_days, g.max_days, g.group.as_ref().unwrap().reviews))
+                .collect_vec(),
+            [(2, 3, 1), (4, 7, 1)]
+        );
+        for calibration in [&report.fsrs_calibration, &report.sm2_calibration] {
+            assert_eq!(calibration.len(), CALIBRATION_BINS);
+            assert_eq!(calibration.iter().map(|bin| bin.reviews).sum::<u32>(), 2);
+        }
+    }
+}
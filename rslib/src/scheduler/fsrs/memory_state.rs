@@ -2,23 +2,38 @@
 // License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::num::NonZeroUsize;
+use std::thread;
 
 use anki_proto::scheduler::ComputeMemoryStateResponse;
+use fnv::FnvHasher;
 use fsrs::FSRSItem;
 use fsrs::MemoryState;
 use fsrs::FSRS;
 use itertools::Itertools;
 
 use crate::card::CardType;
+use crate::deckconfig::DesiredRetentionOverride;
+use crate::deckconfig::FsrsWeightsOverride;
 use crate::prelude::*;
 use crate::revlog::RevlogEntry;
 use crate::revlog::RevlogReviewKind;
 use crate::scheduler::fsrs::weights::single_card_revlog_to_items;
 use crate::scheduler::fsrs::weights::Weights;
+use crate::scheduler::fsrs::weights_overrides::split_search_by_weights;
 use crate::scheduler::states::fuzz::with_review_fuzz;
 use crate::search::Negated;
+use crate::search::Node;
 use crate::search::SearchNode;
+use crate::search::SortMode;
 use crate::search::StateKind;
+use crate::storage::comma_separated_ids;
+
+/// Cards are loaded, replayed and saved in batches of this size.
+const MEMORY_STATE_BATCH_SIZE: usize = 1000;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ComputeMemoryProgress {
@@ -26,15 +41,31 @@ pub struct ComputeMemoryProgress {
     pub total_cards: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct UpdateMemoryStateRequest {
     pub weights: Weights,
+    pub weights_overrides: Vec<FsrsWeightsOverride>,
     pub desired_retention: f32,
+    pub desired_retention_overrides: Vec<DesiredRetentionOverride>,
     pub sm2_retention: f32,
     pub max_interval: u32,
     pub reschedule: bool,
 }
 
+impl UpdateMemoryStateRequest {
+    pub(crate) fn from_config(config: &DeckConfig, reschedule: bool) -> Self {
+        Self {
+            weights: config.inner.fsrs_weights.clone(),
+            weights_overrides: config.inner.fsrs_weights_overrides.clone(),
+            desired_retention: config.inner.desired_retention,
+            desired_retention_overrides: config.inner.desired_retention_overrides.clone(),
+            max_interval: config.inner.maximum_review_interval,
+            reschedule,
+            sm2_retention: config.inner.sm2_retention,
+        }
+    }
+}
+
 impl Collection {
     /// For each provided set of weights, locate cards with the provided search,
     /// and update their memory state.
@@ -47,11 +78,48 @@ impl Collection {
     ) -> Result<()> {
         let timing = self.timing_today()?;
         let usn = self.usn()?;
-        for (req, search) in entries {
-            let search =
-                SearchBuilder::all([search.into(), SearchNode::State(StateKind::New).negated()]);
-            let revlog = self.revlog_for_srs(search)?;
+        for (req, search) in split_entries_by_weights(entries)? {
+            let retention_overrides = match &req {
+                Some(req) => self.desired_retention_overrides_for_search(
+                    search.clone(),
+                    &req.desired_retention_overrides,
+                )?,
+                None => HashMap::new(),
+            };
+            let search = SearchBuilder::all([search, SearchNode::State(StateKind::New).negated()]);
             let reschedule = req.as_ref().map(|e| e.reschedule).unwrap_or_default();
+            let (hashes, stored_hashes) = match &req {
+                Some(req) => self.memory_state_hashes(search.clone(), req, timing.next_day_at)?,
+                None => Default::default(),
+            };
+            // cards whose review history and weights haven't changed since their
+            // memory state was last computed don't need to be replayed, so only
+            // the history of the other cards is loaded
+            let up_to_date: HashSet<CardId> = if reschedule {
+                HashSet::new()
+            } else {
+                hashes
+                    .iter()
+                    .filter(|&(cid, hash)| {
+                        stored_hashes.get(cid).map(|(stored, _)| stored) == Some(hash)
+                    })
+                    .map(|(cid, _)| *cid)
+                    .collect()
+            };
+            let revlog = if up_to_date.is_empty() {
+                self.revlog_for_srs(search)?
+            } else {
+                let stale = hashes
+                    .keys()
+                    .filter(|cid| !up_to_date.contains(*cid))
+                    .copied()
+                    .collect_vec();
+                if stale.is_empty() {
+                    vec![]
+                } else {
+                    self.revlog_for_srs(SearchNode::CardIds(comma_separated_ids(&stale)))?
+                }
+            };
             let last_revlog_info = if reschedule {
                 Some(get_last_revlog_info(&revlog))
             } else {
@@ -67,69 +135,173 @@ impl Collection {
             );
             let desired_retention = req.as_ref().map(|w| w.desired_retention);
             let mut progress = self.new_progress_handler::<ComputeMemoryProgress>();
-            progress.update(false, |s| s.total_cards = items.len() as u32)?;
-            for (idx, (card_id, item)) in items.into_iter().enumerate() {
-                progress.update(true, |state| state.current_cards = idx as u32 + 1)?;
-                let mut card = self.storage.get_card(card_id)?.or_not_found(card_id)?;
-                let original = card.clone();
-                if let Some(req) = &req {
-                    card.set_memory_state(&fsrs, item, sm2_retention.unwrap());
-                    card.desired_retention = desired_retention;
-                    // if rescheduling
-                    if let Some(reviews) = &last_revlog_info {
-                        // and we have a last review time for the card
-                        if let Some(last_info) = reviews.get(&card.id) {
-                            if let Some(last_review) = &last_info.last_reviewed_at {
-                                let days_elapsed =
-                                    timing.next_day_at.elapsed_days_since(*last_review) as i32;
-                                // and the card's not new
-                                if let Some(state) = &card.memory_state {
-                                    // or in (re)learning
-                                    if card.ctype == CardType::Review {
-                                        // reschedule it
-                                        let original_interval = card.interval;
-                                        let interval = fsrs.next_interval(
-                                            Some(state.stability),
-                                            card.desired_retention.unwrap(),
-                                            0,
-                                        )
-                                            as f32;
-                                        card.interval = with_review_fuzz(
-                                            card.get_fuzz_factor(),
-                                            interval,
-                                            1,
-                                            req.max_interval,
-                                        );
-                                        let due = if card.original_due != 0 {
-                                            &mut card.original_due
-                                        } else {
-                                            &mut card.due
-                                        };
-                                        *due = (timing.days_elapsed as i32) - days_elapsed
-                                            + card.interval as i32;
-                                        // Add a manual revlog entry if the last entry wasn't manual
-                                        if !last_info.last_revlog_is_manual {
-                                            self.log_manually_scheduled_review(
-                                                &card,
-                                                original_interval,
-                                                usn,
-                                            )?;
+            let total_cards = items.len() as u32;
+            progress.update(false, |s| s.total_cards = total_cards)?;
+            let mut current_cards = 0;
+            for batch in &items.into_iter().chunks(MEMORY_STATE_BATCH_SIZE) {
+                let mut batch = batch
+                    .map(|(card_id, item)| {
+                        let card = self.storage.get_card(card_id)?.or_not_found(card_id)?;
+                        Ok((card, item))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let mut computed = match &req {
+                    Some(req) => compute_memory_states(
+                        &req.weights,
+                        batch.iter().filter_map(|(_, item)| item.as_ref()).collect(),
+                    )?,
+                    None => vec![],
+                }
+                .into_iter();
+                for (card, item) in &mut batch {
+                    let original = card.clone();
+                    if let Some(req) = &req {
+                        let state = item.as_ref().and_then(|_| computed.next());
+                        card.set_computed_memory_state(&fsrs, state, sm2_retention.unwrap());
+                        card.memory_state_hash =
+                            item.as_ref().and_then(|_| hashes.get(&card.id).copied());
+                        card.desired_retention = retention_overrides
+                            .get(&card.id)
+                            .copied()
+                            .or(desired_retention);
+                        // if rescheduling
+                        if let Some(reviews) = &last_revlog_info {
+                            // and we have a last review time for the card
+                            if let Some(last_info) = reviews.get(&card.id) {
+                                if let Some(last_review) = &last_info.last_reviewed_at {
+                                    let days_elapsed =
+                                        timing.next_day_at.elapsed_days_since(*last_review) as i32;
+                                    // and the card's not new
+                                    if let Some(state) = &card.memory_state {
+                                        // or in (re)learning
+                                        if card.ctype == CardType::Review {
+                                            // reschedule it
+                                            let original_interval = card.interval;
+                                            let interval = fsrs.next_interval(
+                                                Some(state.stability),
+                                                card.desired_retention.unwrap(),
+                                                0,
+                                            )
+                                                as f32;
+                                            let (minimum, maximum) =
+                                                card.review_interval_limits(req.max_interval);
+                                            card.interval = with_review_fuzz(
+                                                card.get_fuzz_factor(),
+                                                interval,
+                                                minimum,
+                                                maximum,
+                                            );
+                                            let due = if card.original_due != 0 {
+                                                &mut card.original_due
+                                            } else {
+                                                &mut card.due
+                                            };
+                                            *due = (timing.days_elapsed as i32) - days_elapsed
+                                                + card.interval as i32;
+                                            // Add a manual revlog entry if the last entry wasn't manual
+                                            if !last_info.last_revlog_is_manual {
+                                                self.log_manually_scheduled_review(
+                                                    card,
+                                                    original_interval,
+                                                    usn,
+                                                )?;
+                                            }
                                         }
                                     }
                                 }
                             }
                         }
+                    } else {
+                        card.memory_state = None;
+                        card.memory_state_hash = None;
+                        card.desired_retention = None;
+                    }
+                    // saving an unchanged card would needlessly mark it for syncing
+                    if *card != original {
+                        self.update_card_inner(card, original, usn)?;
                     }
-                } else {
-                    card.memory_state = None;
-                    card.desired_retention = None;
                 }
-                self.update_card_inner(&mut card, original, usn)?;
+                current_cards += batch.len() as u32;
+                progress.update(true, |state| state.current_cards = current_cards)?;
+            }
+            // the desired retention of cards that weren't replayed may still
+            // have changed
+            if let Some(req) = &req {
+                for cid in up_to_date {
+                    let desired_retention = retention_overrides
+                        .get(&cid)
+                        .copied()
+                        .or(Some(req.desired_retention));
+                    if stored_hashes.get(&cid).map(|(_, retention)| *retention)
+                        == Some(desired_retention)
+                    {
+                        continue;
+                    }
+                    let mut card = self.storage.get_card(cid)?.or_not_found(cid)?;
+                    let original = card.clone();
+                    card.desired_retention = desired_retention;
+                    self.update_card_inner(&mut card, original, usn)?;
+                }
             }
         }
         Ok(())
     }
 
+    /// The hashes of the review history of the searched cards, and the hashes
+    /// and desired retention stored with their current memory state. The
+    /// review history is streamed, so the revlog isn't loaded into memory.
+    fn memory_state_hashes(
+        &mut self,
+        search: impl TryIntoSearch,
+        req: &UpdateMemoryStateRequest,
+        next_day_at: TimestampSecs,
+    ) -> Result<(HashMap<CardId, u32>, HashMap<CardId, (u32, Option<f32>)>)> {
+        let mut hashers: HashMap<CardId, FnvHasher> = HashMap::new();
+        let guard = self.search_cards_into_table(search, SortMode::NoOrder)?;
+        // entries are returned in ID order for each card
+        guard
+            .col
+            .storage
+            .for_each_revlog_entry_of_searched_cards(|entry| {
+                let hasher = hashers
+                    .entry(entry.cid)
+                    .or_insert_with(|| memory_state_hasher(req, next_day_at));
+                hash_revlog_entry(&entry, hasher);
+                Ok(())
+            })?;
+        let stored = guard.col.storage.memory_state_hashes_of_searched_cards()?;
+        let hashes = hashers
+            .into_iter()
+            .map(|(cid, hasher)| (cid, hasher.finish() as u32))
+            .collect();
+        Ok((hashes, stored))
+    }
+
+    /// Update the memory state of the provided cards with the presets of their
+    /// home decks. Should be called inside a transaction.
+    pub(crate) fn update_memory_state_of_cards(&mut self, cids: &[CardId]) -> Result<()> {
+        let mut cards_by_config: HashMap<DeckConfigId, Vec<CardId>> = HashMap::new();
+        for &cid in cids {
+            let card = self.storage.get_card(cid)?.or_not_found(cid)?;
+            let deck_id = card.original_or_current_deck_id();
+            let deck = self.get_deck(deck_id)?.or_not_found(deck_id)?;
+            if let Some(config_id) = deck.config_id() {
+                cards_by_config.entry(config_id).or_default().push(cid);
+            }
+        }
+        let entries = cards_by_config
+            .into_iter()
+            .map(|(config_id, cids)| {
+                let config = self.get_deck_config(config_id, true)?.unwrap();
+                Ok((
+                    Some(UpdateMemoryStateRequest::from_config(&config, false)),
+                    SearchNode::CardIds(comma_separated_ids(&cids)),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        self.update_memory_state(entries)
+    }
+
     pub fn compute_memory_state(&mut self, card_id: CardId) -> Result<ComputeMemoryStateResponse> {
         let mut card = self.storage.get_card(card_id)?.or_not_found(card_id)?;
         let deck_id = card.original_deck_id.or(card.deck_id);
@@ -139,9 +311,9 @@ impl Collection {
             .storage
             .get_deck_config(conf_id)?
             .or_not_found(conf_id)?;
-        let desired_retention = config.inner.desired_retention;
+        let desired_retention = self.desired_retention_for_card(&card, &config)?;
         let sm2_retention = config.inner.sm2_retention;
-        let fsrs = FSRS::new(Some(&config.inner.fsrs_weights))?;
+        let fsrs = FSRS::new(Some(self.fsrs_weights_for_card(&card, &config)?))?;
         let revlog = self.revlog_for_srs(SearchNode::CardIds(card.id.to_string()))?;
         let item = single_card_revlog_to_item(
             &fsrs,
@@ -157,6 +329,32 @@ impl Collection {
     }
 }
 
+/// Cards of notetypes/templates with their own weights are updated separately.
+fn split_entries_by_weights(
+    entries: Vec<(Option<UpdateMemoryStateRequest>, SearchNode)>,
+) -> Result<Vec<(Option<UpdateMemoryStateRequest>, Node)>> {
+    let mut split = vec![];
+    for (req, search) in entries {
+        match req {
+            Some(req) => {
+                for (weights, search) in
+                    split_search_by_weights(search.into(), &req.weights, &req.weights_overrides)?
+                {
+                    split.push((
+                        Some(UpdateMemoryStateRequest {
+                            weights,
+                            ..req.clone()
+                        }),
+                        search,
+                    ));
+                }
+            }
+            None => split.push((None, search.into())),
+        }
+    }
+    Ok(split)
+}
+
 impl Card {
     pub(crate) fn set_memory_state(
         &mut self,
@@ -164,8 +362,19 @@ impl Card {
         item: Option<FsrsItemWithStartingState>,
         sm2_retention: f32,
     ) {
-        self.memory_state = item
-            .map(|i| fsrs.memory_state(i.item, i.starting_state))
+        let state = item.map(|i| fsrs.memory_state(i.item, i.starting_state));
+        self.set_computed_memory_state(fsrs, state, sm2_retention);
+    }
+
+    /// Like [Card::set_memory_state], but with the state already replayed from
+    /// the card's revlog, if it had usable entries.
+    fn set_computed_memory_state(
+        &mut self,
+        fsrs: &FSRS,
+        state: Option<MemoryState>,
+        sm2_retention: f32,
+    ) {
+        self.memory_state = state
             .or_else(|| {
                 if self.ctype == CardType::New || self.interval == 0 {
                     None
@@ -182,6 +391,60 @@ impl Card {
     }
 }
 
+/// Replay the provided items across the available cores.
+fn compute_memory_states(
+    weights: &[f32],
+    items: Vec<&FsrsItemWithStartingState>,
+) -> Result<Vec<MemoryState>> {
+    if items.is_empty() {
+        return Ok(vec![]);
+    }
+    let threads = thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1);
+    let chunk_size = (items.len() + threads - 1) / threads;
+    let chunks: Vec<Result<Vec<MemoryState>>> = thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let fsrs = FSRS::new(Some(weights))?;
+                    Ok(chunk
+                        .iter()
+                        .map(|i| fsrs.memory_state(i.item.clone(), i.starting_state))
+                        .collect())
+                })
+            })
+            .collect_vec();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("memory state thread panicked"))
+            .collect()
+    });
+    chunks.into_iter().flatten_ok().collect()
+}
+
+/// A hasher seeded with the inputs that affect the memory state of all cards.
+fn memory_state_hasher(req: &UpdateMemoryStateRequest, next_day_at: TimestampSecs) -> FnvHasher {
+    let mut hasher = FnvHasher::default();
+    for weight in &req.weights {
+        weight.to_bits().hash(&mut hasher);
+    }
+    req.sm2_retention.to_bits().hash(&mut hasher);
+    // day boundaries depend on the rollover hour
+    (next_day_at.0 % 86_400).hash(&mut hasher);
+    hasher
+}
+
+fn hash_revlog_entry(entry: &RevlogEntry, hasher: &mut FnvHasher) {
+    entry.id.hash(hasher);
+    entry.button_chosen.hash(hasher);
+    (entry.review_kind as u8).hash(hasher);
+    entry.interval.hash(hasher);
+    entry.last_interval.hash(hasher);
+    entry.ease_factor.hash(hasher);
+}
+
 #[derive(Debug)]
 pub(crate) struct FsrsItemWithStartingState {
     pub item: FSRSItem,
@@ -315,10 +578,12 @@ mod tests {
     use fsrs::MemoryState;
 
     use super::*;
+    use crate::card::CardQueue;
     use crate::card::FsrsMemoryState;
     use crate::revlog::RevlogReviewKind;
     use crate::scheduler::fsrs::weights::tests::convert;
     use crate::scheduler::fsrs::weights::tests::revlog;
+    use crate::tests::*;
 
     #[test]
     fn bypassed_learning_is_handled() {
@@ -404,4 +669,80 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn unchanged_cards_are_not_replayed() -> Result<()> {
+        let mut col = Collection::new();
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+        let mut card = col.storage.all_cards_of_note(note.id)?[0].clone();
+        card.ctype = CardType::Review;
+        card.queue = CardQueue::Review;
+        card.interval = 5;
+        col.storage.update_card(&card)?;
+        let add_review = |col: &mut Collection, kind, days_ago: i64| {
+            col.storage.add_revlog_entry(
+                &RevlogEntry {
+                    id: ((TimestampSecs::now().0 - days_ago * 86_400) * 1000).into(),
+                    cid: card.id,
+                    interval: 5,
+                    ease_factor: 2500,
+                    ..revlog(kind, 0)
+                },
+                false,
+            )
+        };
+        add_review(&mut col, RevlogReviewKind::Learning, 10)?;
+        add_review(&mut col, RevlogReviewKind::Review, 5)?;
+        let req = UpdateMemoryStateRequest {
+            weights: vec![],
+            weights_overrides: vec![],
+            desired_retention: 0.9,
+            desired_retention_overrides: vec![],
+            sm2_retention: 0.9,
+            max_interval: 36500,
+            reschedule: false,
+        };
+        let update = |col: &mut Collection| {
+            col.transact_no_undo(|col| {
+                col.update_memory_state(vec![(Some(req.clone()), SearchNode::WholeCollection)])
+            })
+        };
+
+        update(&mut col)?;
+        let computed = col.storage.get_card(card.id)?.unwrap();
+        assert!(computed.memory_state.is_some());
+        assert!(computed.memory_state_hash.is_some());
+
+        // a state that doesn't match the history is left alone, as nothing has
+        // changed since it was computed, and the card is not saved again
+        let mut tampered = computed.clone();
+        tampered.memory_state = Some(FsrsMemoryState {
+            stability: 1.0,
+            difficulty: 1.0,
+        });
+        tampered.mtime = TimestampSecs(0);
+        tampered.usn = Usn(5);
+        col.storage.update_card(&tampered)?;
+        update(&mut col)?;
+        let card = col.storage.get_card(card.id)?.unwrap();
+        assert_eq!(card.memory_state, tampered.memory_state);
+        assert_eq!(card.mtime, TimestampSecs(0));
+        assert_eq!(card.usn, Usn(5));
+
+        // but a new review causes it to be replayed
+        add_review(&mut col, RevlogReviewKind::Review, 1)?;
+        update(&mut col)?;
+        let card = col.storage.get_card(card.id)?.unwrap();
+        assert_ne!(card.memory_state, tampered.memory_state);
+        assert_ne!(card.memory_state_hash, computed.memory_state_hash);
+
+        // and removing the state clears the hash
+        col.transact_no_undo(|col| {
+            col.update_memory_state(vec![(None, SearchNode::WholeCollection)])
+        })?;
+        let card = col.storage.get_card(card.id)?.unwrap();
+        assert_eq!(card.memory_state, None);
+        assert_eq!(card.memory_state_hash, None);
+        Ok(())
+    }
 }
@@ -1,7 +1,12 @@
 // Copyright: Ankitects Pty Ltd and contributors
 // License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+pub mod dataset;
 mod error;
+pub(crate) mod evaluation;
 pub mod memory_state;
 pub mod retention;
+pub(crate) mod retention_overrides;
+pub mod simulator;
 pub mod try_collect;
 pub mod weights;
+pub(crate) mod weights_overrides;
@@ -0,0 +1,197 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Presets may specify a different desired retention for the cards matching
+//! a search, eg. a tag. The first matching override in the list wins.
+
+use std::collections::HashMap;
+
+use tracing::warn;
+
+use crate::deckconfig::DesiredRetentionOverride;
+use crate::prelude::*;
+use crate::search::Node;
+use crate::search::SearchNode;
+use crate::search::SortMode;
+
+/// The matches of a preset's overrides, so that answering a card doesn't
+/// require a search. Cleared along with the study queues, and after answers
+/// and edits.
+#[derive(Debug)]
+pub(crate) struct CachedRetentionOverrides {
+    overrides: Vec<DesiredRetentionOverride>,
+    retentions: HashMap<CardId, f32>,
+}
+
+impl Collection {
+    /// The desired retention of the first override of the preset whose
+    /// search matches the card, or the preset's desired retention.
+    pub(crate) fn desired_retention_for_card(
+        &mut self,
+        card: &Card,
+        config: &DeckConfig,
+    ) -> Result<f32> {
+        let overrides = &config.inner.desired_retention_overrides;
+        if active_overrides(overrides).next().is_none() {
+            return Ok(config.inner.desired_retention);
+        }
+        let up_to_date = self
+            .state
+            .retention_overrides
+            .get(&config.id)
+            .is_some_and(|cached| &cached.overrides == overrides);
+        if !up_to_date {
+            let retentions = self.desired_retention_overrides_for_search(
+                SearchNode::WholeCollection.into(),
+                overrides,
+            )?;
+            self.state.retention_overrides.insert(
+                config.id,
+                CachedRetentionOverrides {
+                    overrides: overrides.clone(),
+                    retentions,
+                },
+            );
+        }
+        Ok(self.state.retention_overrides[&config.id]
+            .retentions
+            .get(&card.id)
+            .copied()
+            .unwrap_or(config.inner.desired_retention))
+    }
+
+    /// Return the overridden desired retention of all cards matching `search`
+    /// that match one of the provided overrides.
+    pub(crate) fn desired_retention_overrides_for_search(
+        &mut self,
+        search: Node,
+        overrides: &[DesiredRetentionOverride],
+    ) -> Result<HashMap<CardId, f32>> {
+        let mut retentions = HashMap::new();
+        for (override_search, desired_retention) in parsed_overrides(overrides) {
+            let search = SearchBuilder::all([search.clone(), override_search]);
+            for cid in self.search_cards(search, SortMode::NoOrder)? {
+                retentions.entry(cid).or_insert(desired_retention);
+            }
+        }
+        Ok(retentions)
+    }
+}
+
+/// Overrides with an empty search are ignored.
+fn active_overrides(
+    overrides: &[DesiredRetentionOverride],
+) -> impl Iterator<Item = &DesiredRetentionOverride> {
+    overrides
+        .iter()
+        .filter(|entry| !entry.search.trim().is_empty())
+}
+
+/// Overrides are only validated when saved in the deck options, so a search
+/// that arrived through eg. a sync or an older client may be invalid. Such
+/// overrides are skipped, instead of preventing cards from being answered.
+fn parsed_overrides(
+    overrides: &[DesiredRetentionOverride],
+) -> impl Iterator<Item = (Node, f32)> + '_ {
+    active_overrides(overrides).filter_map(|entry| match entry.search.as_str().try_into_search() {
+        Ok(search) => Some((search, entry.desired_retention)),
+        Err(err) => {
+            warn!(
+                "ignoring invalid retention override {:?}: {err:?}",
+                entry.search
+            );
+            None
+        }
+    })
+}
+
+/// Returns an error if the search of an override can't be parsed.
+pub(crate) fn validate_desired_retention_overrides(
+    overrides: &[DesiredRetentionOverride],
+) -> Result<()> {
+    for entry in active_overrides(overrides) {
+        entry.search.as_str().try_into_search()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn first_matching_override_wins() -> Result<()> {
+        let mut col = Collection::new();
+        let mut note = NoteAdder::basic(&mut col).add(&mut col);
+        note.tags = vec!["high-yield".into(), "trivia".into()];
+        col.update_note(&mut note)?;
+        let plain = NoteAdder::basic(&mut col).add(&mut col);
+        let mut config = DeckConfig::default();
+        config.inner.desired_retention_overrides = vec![
+            DesiredRetentionOverride {
+                search: "tag:high-yield".into(),
+                desired_retention: 0.95,
+            },
+            DesiredRetentionOverride {
+                search: "tag:trivia".into(),
+                desired_retention: 0.8,
+            },
+        ];
+
+        let card = col.storage.all_cards_of_note(note.id)?.pop().unwrap();
+        assert_eq!(col.desired_retention_for_card(&card, &config)?, 0.95);
+        let card = col.storage.all_cards_of_note(plain.id)?.pop().unwrap();
+        assert_eq!(col.desired_retention_for_card(&card, &config)?, 0.9);
+
+        config.inner.desired_retention_overrides.swap(0, 1);
+        let retentions = col.desired_retention_overrides_for_search(
+            SearchNode::WholeCollection.into(),
+            &config.inner.desired_retention_overrides,
+        )?;
+        assert_eq!(retentions.len(), 1);
+        assert_eq!(retentions.values().next(), Some(&0.8));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_overrides_are_skipped() -> Result<()> {
+        let mut col = Collection::new();
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+        let mut config = DeckConfig::default();
+        config.inner.desired_retention_overrides = vec![
+            DesiredRetentionOverride {
+                search: "prop:ivl>abc".into(),
+                desired_retention: 0.95,
+            },
+            DesiredRetentionOverride {
+                search: "is:new".into(),
+                desired_retention: 0.8,
+            },
+        ];
+
+        let card = col.storage.all_cards_of_note(note.id)?.pop().unwrap();
+        assert_eq!(col.desired_retention_for_card(&card, &config)?, 0.8);
+        Ok(())
+    }
+
+    #[test]
+    fn answering_updates_matches() -> Result<()> {
+        let mut col = Collection::new();
+        col.update_default_deck_config(|config| {
+            config.desired_retention_overrides = vec![DesiredRetentionOverride {
+                search: "is:new".into(),
+                desired_retention: 0.8,
+            }];
+        });
+        let config = col.get_deck_config(DeckConfigId(1), false)?.unwrap();
+        let note = NoteAdder::basic(&mut col).add(&mut col);
+
+        let card = col.storage.all_cards_of_note(note.id)?.pop().unwrap();
+        assert_eq!(col.desired_retention_for_card(&card, &config)?, 0.8);
+        col.answer_good();
+        let card = col.storage.get_card(card.id)?.unwrap();
+        assert_eq!(col.desired_retention_for_card(&card, &config)?, 0.9);
+        Ok(())
+    }
+}
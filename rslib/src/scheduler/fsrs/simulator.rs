@@ -0,0 +1,427 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Forecasting the future workload of a preset, by simulating reviews of its
+//! cards with FSRS.
+
+use std::collections::VecDeque;
+
+use anki_proto::scheduler::simulate_workload_response::Day as SimulatedDay;
+use anki_proto::scheduler::OptimalRetentionParameters;
+use anki_proto::scheduler::SimulateWorkloadRequest;
+use anki_proto::scheduler::SimulateWorkloadResponse;
+use fsrs::MemoryState;
+use fsrs::NextStates;
+use fsrs::FSRS;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::card::CardQueue;
+use crate::card::CardType;
+use crate::deckconfig::FsrsWeightsOverride;
+use crate::prelude::*;
+use crate::scheduler::fsrs::weights_overrides::split_search_by_weights;
+use crate::search::SearchNode;
+
+/// Used so that repeated simulations of the same collection produce the same
+/// result.
+const SIMULATION_SEED: u64 = 42;
+/// A century, which bounds the memory and time a simulation takes.
+const MAX_DAYS_TO_SIMULATE: u32 = 36_500;
+
+/// The preset settings that influence a simulation. Kept separate from
+/// [DeckConfig], so that hypothetical values can be evaluated before they are
+/// applied.
+#[derive(Debug, Clone)]
+pub(crate) struct WorkloadSimulationConfig {
+    pub weights: Vec<f32>,
+    /// Used instead of `weights` for the cards they apply to, as when
+    /// scheduling.
+    pub weights_overrides: Vec<FsrsWeightsOverride>,
+    pub desired_retention: f32,
+    pub sm2_retention: f32,
+    pub new_per_day: u32,
+    pub max_interval: u32,
+    pub days_to_simulate: u32,
+}
+
+impl WorkloadSimulationConfig {
+    pub(crate) fn from_deck_config(config: &DeckConfig, days_to_simulate: u32) -> Self {
+        Self {
+            weights: config.inner.fsrs_weights.clone(),
+            weights_overrides: config.inner.fsrs_weights_overrides.clone(),
+            desired_retention: config.inner.desired_retention,
+            sm2_retention: config.inner.sm2_retention,
+            new_per_day: config.inner.new_per_day,
+            max_interval: config.inner.maximum_review_interval,
+            days_to_simulate,
+        }
+    }
+}
+
+/// A card that has been introduced, and will be reviewed during the
+/// simulation.
+#[derive(Debug, Clone, Copy)]
+struct SimulatedCard {
+    /// Index of the card's weights in [WorkloadSimulator::fsrs].
+    fsrs: usize,
+    memory: MemoryState,
+    /// Relative to today; negative if reviewed in the past.
+    last_review: i32,
+}
+
+impl Collection {
+    /// Simulate the next `days_to_simulate` days of study of the cards in the
+    /// provided preset, optionally with some of its settings overridden.
+    pub fn simulate_workload(
+        &mut self,
+        req: SimulateWorkloadRequest,
+    ) -> Result<SimulateWorkloadResponse> {
+        if req.days_to_simulate == 0 || req.days_to_simulate > MAX_DAYS_TO_SIMULATE {
+            invalid_input!("days to simulate out of range")
+        }
+        let dcid = DeckConfigId(req.deck_config_id);
+        let config = self.get_deck_config(dcid, false)?.or_not_found(dcid)?;
+        let mut sim_config =
+            WorkloadSimulationConfig::from_deck_config(&config, req.days_to_simulate);
+        if let Some(desired_retention) = req.desired_retention {
+            require!(
+                desired_retention > 0.0 && desired_retention < 1.0,
+                "desired retention out of range"
+            );
+            sim_config.desired_retention = desired_retention;
+        }
+        if let Some(new_per_day) = req.new_per_day {
+            sim_config.new_per_day = new_per_day;
+        }
+        if let Some(max_interval) = req.max_interval {
+            sim_config.max_interval = max_interval;
+        }
+        if !req.weights.is_empty() {
+            sim_config.weights = req.weights;
+        }
+        let days =
+            self.simulate_workload_for_search(SearchNode::Preset(config.name), &sim_config)?;
+        Ok(SimulateWorkloadResponse { days })
+    }
+
+    /// Simulate reviews of the cards matching `search`, returning the expected
+    /// workload for each day, starting with today.
+    pub(crate) fn simulate_workload_for_search(
+        &mut self,
+        search: impl TryIntoSearch,
+        config: &WorkloadSimulationConfig,
+    ) -> Result<Vec<SimulatedDay>> {
+        let search = search.try_into_search()?;
+        let params = match self.get_optimal_retention_parameters(&search.to_string()) {
+            Ok(params) => params,
+            Err(AnkiError::FsrsInsufficientData) => default_simulation_parameters(),
+            Err(err) => return Err(err),
+        };
+        let today = self.timing_today()?.days_elapsed as i32;
+        let mut fsrs = vec![];
+        let mut cards = vec![];
+        for (weights, search) in
+            split_search_by_weights(search, &config.weights, &config.weights_overrides)?
+        {
+            cards.extend(
+                self.all_cards_for_search(search)?
+                    .into_iter()
+                    .map(|card| (fsrs.len(), card)),
+            );
+            fsrs.push(FSRS::new(Some(&weights))?);
+        }
+        // new cards are introduced in due order
+        cards.sort_unstable_by_key(|(_, c)| (c.ctype != CardType::New, c.due));
+        let mut simulator = WorkloadSimulator::new(fsrs, config, params);
+        for (fsrs, card) in &cards {
+            simulator.add_card(card, *fsrs, today);
+        }
+        Ok(simulator.run())
+    }
+}
+
+struct WorkloadSimulator<'a> {
+    /// One for each set of weights the cards use.
+    fsrs: Vec<FSRS>,
+    config: &'a WorkloadSimulationConfig,
+    params: OptimalRetentionParameters,
+    rng: StdRng,
+    /// Introduced cards.
+    cards: Vec<SimulatedCard>,
+    /// Indices into `cards`, for each simulated day.
+    due_by_day: Vec<Vec<usize>>,
+    /// The weights of the cards yet to be introduced, in the order they will
+    /// be.
+    new_cards: VecDeque<usize>,
+}
+
+impl<'a> WorkloadSimulator<'a> {
+    fn new(
+        fsrs: Vec<FSRS>,
+        config: &'a WorkloadSimulationConfig,
+        params: OptimalRetentionParameters,
+    ) -> Self {
+        Self {
+            fsrs,
+            config,
+            params,
+            rng: StdRng::seed_from_u64(SIMULATION_SEED),
+            cards: vec![],
+            due_by_day: vec![vec![]; config.days_to_simulate as usize],
+            new_cards: VecDeque::new(),
+        }
+    }
+
+    fn add_card(&mut self, card: &Card, fsrs: usize, today: i32) {
+        if card.queue == CardQueue::Suspended {
+            return;
+        }
+        match card.ctype {
+            CardType::New => self.new_cards.push_back(fsrs),
+            CardType::Learn => {
+                // still in its learning steps; assume it graduates today
+                let memory = card.memory_state.map(Into::into).unwrap_or_else(|| {
+                    self.fsrs[fsrs]
+                        .next_states(None, self.config.desired_retention, 0)
+                        .good
+                        .memory
+                });
+                self.schedule(
+                    SimulatedCard {
+                        fsrs,
+                        memory,
+                        last_review: 0,
+                    },
+                    0,
+                );
+            }
+            CardType::Review | CardType::Relearn => {
+                let due = if card.original_due != 0 {
+                    card.original_due
+                } else {
+                    card.due
+                };
+                let (due_in_days, last_review) =
+                    if matches!(card.queue, CardQueue::Learn | CardQueue::PreviewRepeat) {
+                        // intraday (re)learning cards have a timestamp as their due date
+                        (0, 0)
+                    } else {
+                        ((due - today).max(0), due - today - card.interval as i32)
+                    };
+                let memory = card.memory_state.map(Into::into).unwrap_or_else(|| {
+                    self.fsrs[fsrs].memory_state_from_sm2(
+                        card.ease_factor(),
+                        card.interval.max(1) as f32,
+                        self.config.sm2_retention,
+                    )
+                });
+                self.schedule(
+                    SimulatedCard {
+                        fsrs,
+                        memory,
+                        last_review,
+                    },
+                    due_in_days as u32,
+                );
+            }
+        }
+    }
+
+    /// Add the card to the provided day, if within the simulated range.
+    fn schedule(&mut self, card: SimulatedCard, day: u32) {
+        if let Some(due) = self.due_by_day.get_mut(day as usize) {
+            self.cards.push(card);
+            due.push(self.cards.len() - 1);
+        }
+    }
+
+    fn run(mut self) -> Vec<SimulatedDay> {
+        (0..self.config.days_to_simulate)
+            .map(|day| self.simulate_day(day))
+            .collect()
+    }
+
+    fn simulate_day(&mut self, day: u32) -> SimulatedDay {
+        let mut seconds = 0.0;
+        let due_today = std::mem::take(&mut self.due_by_day[day as usize]);
+        let review_count = due_today.len() as u32;
+        for idx in due_today {
+            let card = self.cards[idx];
+            let days_elapsed = (day as i32 - card.last_review).max(0) as u32;
+            let fsrs = &self.fsrs[card.fsrs];
+            let retrievability = fsrs.current_retrievability(card.memory, days_elapsed);
+            let states = fsrs.next_states(
+                Some(card.memory),
+                self.config.desired_retention,
+                days_elapsed,
+            );
+            let rating = if self.rng.gen::<f32>() < retrievability {
+                self.sample_rating(&[
+                    self.params.review_rating_probability_hard,
+                    self.params.review_rating_probability_good,
+                    self.params.review_rating_probability_easy,
+                ]) + 2
+            } else {
+                1
+            };
+            seconds += match rating {
+                1 => self.params.forget_secs,
+                2 => self.params.recall_secs_hard,
+                3 => self.params.recall_secs_good,
+                _ => self.params.recall_secs_easy,
+            };
+            self.reschedule(idx, day, &states, rating);
+        }
+
+        let new_count = self.new_cards.len().min(self.config.new_per_day as usize) as u32;
+        for fsrs in self
+            .new_cards
+            .drain(..new_count as usize)
+            .collect::<Vec<_>>()
+        {
+            let rating = self.sample_rating(&[
+                self.params.first_rating_probability_again,
+                self.params.first_rating_probability_hard,
+                self.params.first_rating_probability_good,
+                self.params.first_rating_probability_easy,
+            ]) + 1;
+            seconds += self.params.learn_secs;
+            let states = self.fsrs[fsrs].next_states(None, self.config.desired_retention, 0);
+            self.cards.push(SimulatedCard {
+                fsrs,
+                memory: states.good.memory,
+                last_review: day as i32,
+            });
+            self.reschedule(self.cards.len() - 1, day, &states, rating);
+        }
+
+        SimulatedDay {
+            day,
+            review_count,
+            new_count,
+            study_minutes: (seconds / 60.0) as f32,
+        }
+    }
+
+    /// Update the card's memory state according to the rating, and add it to
+    /// the day it will next be due.
+    fn reschedule(&mut self, idx: usize, day: u32, states: &NextStates, rating: u32) {
+        let state = match rating {
+            1 => &states.again,
+            2 => &states.hard,
+            3 => &states.good,
+            _ => &states.easy,
+        };
+        let interval = state.interval.clamp(1, self.config.max_interval.max(1));
+        self.cards[idx] = SimulatedCard {
+            fsrs: self.cards[idx].fsrs,
+            memory: state.memory,
+            last_review: day as i32,
+        };
+        if let Some(due) = self.due_by_day.get_mut((day + interval) as usize) {
+            due.push(idx);
+        }
+    }
+
+    /// Return the index of a randomly-chosen entry, weighted by the provided
+    /// probabilities.
+    fn sample_rating(&mut self, probabilities: &[f64]) -> u32 {
+        let total: f64 = probabilities.iter().sum();
+        let mut remaining = self.rng.gen::<f64>() * total;
+        for (idx, probability) in probabilities.iter().enumerate() {
+            if remaining < *probability {
+                return idx as u32;
+            }
+            remaining -= probability;
+        }
+        probabilities.len() as u32 - 1
+    }
+}
+
+/// Used when the collection does not have enough review history to derive
+/// the costs and rating probabilities from.
+fn default_simulation_parameters() -> OptimalRetentionParameters {
+    OptimalRetentionParameters {
+        recall_secs_hard: 14.0,
+        recall_secs_good: 10.0,
+        recall_secs_easy: 6.0,
+        forget_secs: 50.0,
+        learn_secs: 20.0,
+        first_rating_probability_again: 0.15,
+        first_rating_probability_hard: 0.2,
+        first_rating_probability_good: 0.6,
+        first_rating_probability_easy: 0.05,
+        review_rating_probability_hard: 0.3,
+        review_rating_probability_good: 0.6,
+        review_rating_probability_easy: 0.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_cards_are_introduced_up_to_limit() -> Result<()> {
+        let mut col = Collection::new();
+        for _ in 0..5 {
+            NoteAdder::basic(&mut col).add(&mut col);
+        }
+        let mut config = WorkloadSimulationConfig::from_deck_config(&DeckConfig::default(), 3);
+        config.new_per_day = 2;
+        let days = col.simulate_workload_for_search(SearchNode::WholeCollection, &config)?;
+        assert_eq!(
+            days.iter().map(|d| d.new_count).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        // nothing has been introduced yet, so there's nothing to review on the
+        // first day
+        assert_eq!(days[0].review_count, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn weights_overrides_are_used() -> Result<()> {
+        let mut col = Collection::new();
+        for _ in 0..20 {
+            NoteAdder::basic(&mut col).add(&mut col);
+        }
+        let basic = col.get_notetype_by_name("Basic")?.unwrap().id;
+        let mut slow_weights = fsrs::DEFAULT_WEIGHTS.to_vec();
+        slow_weights[2] = 100.0;
+        let mut config = WorkloadSimulationConfig::from_deck_config(&DeckConfig::default(), 30);
+        config.weights = fsrs::DEFAULT_WEIGHTS.to_vec();
+        let default_days =
+            col.simulate_workload_for_search(SearchNode::WholeCollection, &config)?;
+        config.weights_overrides = vec![FsrsWeightsOverride {
+            notetype_id: basic.0,
+            template_idx: None,
+            weights: slow_weights.clone(),
+        }];
+        let overridden_days =
+            col.simulate_workload_for_search(SearchNode::WholeCollection, &config)?;
+        config.weights = slow_weights;
+        config.weights_overrides.clear();
+        let slow_days = col.simulate_workload_for_search(SearchNode::WholeCollection, &config)?;
+
+        assert_eq!(overridden_days, slow_days);
+        assert_ne!(overridden_days, default_days);
+        Ok(())
+    }
+
+    #[test]
+    fn days_to_simulate_are_limited() {
+        let mut col = Collection::new();
+        let req = |days_to_simulate| SimulateWorkloadRequest {
+            deck_config_id: 1,
+            days_to_simulate,
+            ..Default::default()
+        };
+        assert!(col.simulate_workload(req(0)).is_err());
+        assert!(col
+            .simulate_workload(req(MAX_DAYS_TO_SIMULATE + 1))
+            .is_err());
+        assert!(col.simulate_workload(req(MAX_DAYS_TO_SIMULATE)).is_ok());
+    }
+}
@@ -17,6 +17,7 @@ use fsrs::FSRS;
 use itertools::Itertools;
 use prost::Message;
 
+use crate::deckconfig::FsrsWeightsGrouping;
 use crate::prelude::*;
 use crate::revlog::RevlogEntry;
 use crate::revlog::RevlogReviewKind;
@@ -29,18 +30,38 @@ pub(crate) type Weights = Vec<f32>;
 impl Collection {
     /// Note this does not return an error if there are less than 1000 items -
     /// the caller should instead check the fsrs_items count in the return
-    /// value.
+    /// value. Weights for notetypes/templates are only returned if there are
+    /// enough items for them.
     pub fn compute_weights(
         &mut self,
         search: &str,
+        grouping: FsrsWeightsGrouping,
         current_preset: u32,
         total_presets: u32,
     ) -> Result<ComputeFsrsWeightsResponse> {
-        let mut anki_progress = self.new_progress_handler::<ComputeWeightsProgress>();
         let timing = self.timing_today()?;
         let revlogs = self.revlog_for_srs(search)?;
         let items = fsrs_items_for_training(revlogs, timing.next_day_at);
         let fsrs_items = items.len() as u32;
+        let weights = self.train_weights(items, current_preset, total_presets)?;
+        let weights_overrides =
+            self.compute_weights_overrides(search, grouping, current_preset, total_presets)?;
+        Ok(ComputeFsrsWeightsResponse {
+            weights,
+            fsrs_items,
+            weights_overrides,
+        })
+    }
+
+    /// Train weights on the provided items, reporting progress.
+    pub(super) fn train_weights(
+        &mut self,
+        items: Vec<FSRSItem>,
+        current_preset: u32,
+        total_presets: u32,
+    ) -> Result<Weights> {
+        let mut anki_progress = self.new_progress_handler::<ComputeWeightsProgress>();
+        let fsrs_items = items.len() as u32;
         anki_progress.update(false, |p| {
             p.fsrs_items = fsrs_items;
             p.current_preset = current_preset;
@@ -65,11 +86,7 @@ impl Collection {
             }
         });
         let fsrs = FSRS::new(None)?;
-        let weights = fsrs.compute_weights(items, Some(progress2))?;
-        Ok(ComputeFsrsWeightsResponse {
-            weights,
-            fsrs_items,
-        })
+        Ok(fsrs.compute_weights(items, Some(progress2))?)
     }
 
     pub(crate) fn revlog_for_srs(
@@ -144,7 +161,10 @@ pub struct ComputeWeightsProgress {
 }
 
 /// Convert a series of revlog entries sorted by card id into FSRS items.
-fn fsrs_items_for_training(revlogs: Vec<RevlogEntry>, next_day_at: TimestampSecs) -> Vec<FSRSItem> {
+pub(super) fn fsrs_items_for_training(
+    revlogs: Vec<RevlogEntry>,
+    next_day_at: TimestampSecs,
+) -> Vec<FSRSItem> {
     let mut revlogs = revlogs
         .into_iter()
         .group_by(|r| r.cid)
@@ -165,10 +185,46 @@ fn fsrs_items_for_training(revlogs: Vec<RevlogEntry>, next_day_at: TimestampSecs
 /// state. Returns (items, found_learn_entry), the latter of which is used
 /// to determine whether the revlogs have been truncated when not training.
 pub(crate) fn single_card_revlog_to_items(
-    mut entries: Vec<RevlogEntry>,
+    entries: Vec<RevlogEntry>,
     next_day_at: TimestampSecs,
     training: bool,
 ) -> Option<(Vec<FSRSItem>, bool)> {
+    let (entries, delta_ts, found_learn_entry) = reviews_for_fsrs(entries, next_day_at, training)?;
+    let skip = if training { 1 } else { 0 };
+    // Convert the remaining entries into separate FSRSItems, where each item
+    // contains all reviews done until then.
+    let items = entries
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(outer_idx, _)| {
+            let reviews = entries
+                .iter()
+                .take(outer_idx + 1)
+                .enumerate()
+                .map(|(inner_idx, r)| FSRSReview {
+                    rating: r.button_chosen as u32,
+                    delta_t: delta_ts[inner_idx],
+                })
+                .collect();
+            FSRSItem { reviews }
+        })
+        .collect_vec();
+    if items.is_empty() {
+        None
+    } else {
+        Some((items, found_learn_entry))
+    }
+}
+
+/// The revlog entries of a card that FSRS considers, with the days elapsed
+/// since the previous one, and whether a learning entry was found. Returns
+/// None when training on a card without learning steps.
+pub(super) fn reviews_for_fsrs(
+    mut entries: Vec<RevlogEntry>,
+    next_day_at: TimestampSecs,
+    training: bool,
+) -> Option<(Vec<RevlogEntry>, Vec<u32>, bool)> {
     let mut last_learn_entry = None;
     let mut found_learn_entry = false;
     for (index, entry) in entries.iter().enumerate().rev() {
@@ -217,31 +273,7 @@ pub(crate) fn single_card_revlog_to_items(
         }))
         .collect_vec();
 
-    let skip = if training { 1 } else { 0 };
-    // Convert the remaining entries into separate FSRSItems, where each item
-    // contains all reviews done until then.
-    let items = entries
-        .iter()
-        .enumerate()
-        .skip(skip)
-        .map(|(outer_idx, _)| {
-            let reviews = entries
-                .iter()
-                .take(outer_idx + 1)
-                .enumerate()
-                .map(|(inner_idx, r)| FSRSReview {
-                    rating: r.button_chosen as u32,
-                    delta_t: delta_ts[inner_idx],
-                })
-                .collect();
-            FSRSItem { reviews }
-        })
-        .collect_vec();
-    if items.is_empty() {
-        None
-    } else {
-        Some((items, found_learn_entry))
-    }
+    Some((entries, delta_ts, found_learn_entry))
 }
 
 impl RevlogEntry {
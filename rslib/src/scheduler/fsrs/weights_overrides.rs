@@ -0,0 +1,210 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Presets may train separate weights for each notetype or template of their
+//! cards, as a single set of weights can serve very different kinds of cards
+//! (eg. cloze and reverse recall) poorly. Notetypes and templates without
+//! enough reviews use the preset's weights.
+
+use itertools::Itertools;
+
+use super::weights::fsrs_items_for_training;
+use crate::deckconfig::FsrsWeightsGrouping;
+use crate::deckconfig::FsrsWeightsOverride;
+use crate::prelude::*;
+use crate::search::Negated;
+use crate::search::Node;
+use crate::search::SearchNode;
+use crate::search::SortMode;
+use crate::search::TemplateKind;
+
+/// Notetypes/templates with fewer items than this use the preset's weights.
+const MIN_ITEMS_FOR_OVERRIDE: usize = 1000;
+
+impl DeckConfig {
+    /// The weights for cards of the given notetype and template: those trained
+    /// for the template, or else the notetype, or else the preset.
+    pub(crate) fn fsrs_weights_for(&self, notetype_id: NotetypeId, template_idx: u16) -> &[f32] {
+        let overrides = &self.inner.fsrs_weights_overrides;
+        let for_notetype = |template: Option<u32>| {
+            overrides
+                .iter()
+                .find(|o| o.notetype_id == notetype_id.0 && o.template_idx == template)
+        };
+        for_notetype(Some(template_idx as u32))
+            .or_else(|| for_notetype(None))
+            .map(|o| o.weights.as_slice())
+            .unwrap_or(&self.inner.fsrs_weights)
+    }
+}
+
+impl FsrsWeightsOverride {
+    fn search_node(&self) -> Node {
+        let notetype = SearchNode::NotetypeId(self.notetype_id.into());
+        match self.template_idx {
+            Some(idx) => Node::Group(vec![
+                notetype.into(),
+                Node::And,
+                SearchNode::CardTemplate(TemplateKind::Ordinal(idx as u16)).into(),
+            ]),
+            None => notetype.into(),
+        }
+    }
+}
+
+/// Split `search` into one search per weights override, and one for the
+/// remaining cards, which use `preset_weights`. Template overrides take
+/// precedence over notetype overrides.
+pub(crate) fn split_search_by_weights(
+    search: Node,
+    preset_weights: &[f32],
+    overrides: &[FsrsWeightsOverride],
+) -> Result<Vec<(Vec<f32>, Node)>> {
+    if overrides.is_empty() {
+        return Ok(vec![(preset_weights.to_vec(), search)]);
+    }
+    // most specific first, so each can exclude those that came before it
+    let sorted = overrides
+        .iter()
+        .sorted_by_key(|o| o.template_idx.is_none())
+        .collect_vec();
+    let mut splits = vec![];
+    let mut handled: Vec<Node> = vec![];
+    for entry in sorted {
+        let mut nodes = vec![search.clone(), entry.search_node()];
+        nodes.extend(handled.iter().map(|n| n.clone().negated()));
+        splits.push((
+            entry.weights.clone(),
+            SearchBuilder::all(nodes).try_into_search()?,
+        ));
+        handled.push(entry.search_node());
+    }
+    let mut nodes = vec![search];
+    nodes.extend(handled.into_iter().map(Negated::negated));
+    splits.push((
+        preset_weights.to_vec(),
+        SearchBuilder::all(nodes).try_into_search()?,
+    ));
+    Ok(splits)
+}
+
+impl Collection {
+    /// The weights of the preset for the provided card.
+    pub(crate) fn fsrs_weights_for_card<'a>(
+        &mut self,
+        card: &Card,
+        config: &'a DeckConfig,
+    ) -> Result<&'a [f32]> {
+        if config.inner.fsrs_weights_overrides.is_empty() {
+            return Ok(&config.inner.fsrs_weights);
+        }
+        let note = self
+            .storage
+            .get_note(card.note_id)?
+            .or_not_found(card.note_id)?;
+        Ok(config.fsrs_weights_for(note.notetype_id, card.template_idx))
+    }
+
+    /// Train weights for each notetype or template of the cards matching
+    /// `search` that has enough reviews.
+    pub(super) fn compute_weights_overrides(
+        &mut self,
+        search: &str,
+        grouping: FsrsWeightsGrouping,
+        current_preset: u32,
+        total_presets: u32,
+    ) -> Result<Vec<FsrsWeightsOverride>> {
+        let groups = match grouping {
+            FsrsWeightsGrouping::Preset => return Ok(vec![]),
+            FsrsWeightsGrouping::Notetype => self
+                .notetypes_and_templates_of_search(search)?
+                .into_iter()
+                .map(|(ntid, _)| (ntid, None))
+                .dedup()
+                .collect_vec(),
+            FsrsWeightsGrouping::Template => self
+                .notetypes_and_templates_of_search(search)?
+                .into_iter()
+                .map(|(ntid, idx)| (ntid, Some(idx as u32)))
+                .collect_vec(),
+        };
+        let next_day_at = self.timing_today()?.next_day_at;
+        let mut overrides = vec![];
+        for (notetype_id, template_idx) in groups {
+            let mut entry = FsrsWeightsOverride {
+                notetype_id: notetype_id.0,
+                template_idx,
+                weights: vec![],
+            };
+            let group_search = SearchBuilder::all([search.try_into_search()?, entry.search_node()]);
+            let revlogs = self.revlog_for_srs(group_search)?;
+            let items = fsrs_items_for_training(revlogs, next_day_at);
+            if items.len() < MIN_ITEMS_FOR_OVERRIDE {
+                continue;
+            }
+            entry.weights = self.train_weights(items, current_preset, total_presets)?;
+            overrides.push(entry);
+        }
+        Ok(overrides)
+    }
+
+    fn notetypes_and_templates_of_search(
+        &mut self,
+        search: &str,
+    ) -> Result<Vec<(NotetypeId, u16)>> {
+        self.search_cards_into_table(search, SortMode::NoOrder)?
+            .col
+            .storage
+            .notetypes_and_templates_of_searched_cards()
+    }
+}
+
+/// Returns an error if an override does not have a valid number of weights.
+pub(crate) fn validate_weights_overrides(overrides: &[FsrsWeightsOverride]) -> Result<()> {
+    if overrides.iter().any(|o| o.weights.len() != 17) {
+        return Err(AnkiError::FsrsWeightsInvalid);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(notetype_id: i64, template_idx: Option<u32>, weight: f32) -> FsrsWeightsOverride {
+        FsrsWeightsOverride {
+            notetype_id,
+            template_idx,
+            weights: vec![weight],
+        }
+    }
+
+    #[test]
+    fn most_specific_weights_win() -> Result<()> {
+        let mut config = DeckConfig::default();
+        config.inner.fsrs_weights = vec![1.0];
+        config.inner.fsrs_weights_overrides = vec![entry(5, None, 2.0), entry(5, Some(1), 3.0)];
+        assert_eq!(config.fsrs_weights_for(NotetypeId(5), 0), [2.0]);
+        assert_eq!(config.fsrs_weights_for(NotetypeId(5), 1), [3.0]);
+        assert_eq!(config.fsrs_weights_for(NotetypeId(6), 1), [1.0]);
+
+        let splits = split_search_by_weights(
+            SearchNode::Preset("Default".into()).into(),
+            &config.inner.fsrs_weights,
+            &config.inner.fsrs_weights_overrides,
+        )?;
+        let splits = splits
+            .into_iter()
+            .map(|(weights, node)| (weights[0], node.to_string()))
+            .collect_vec();
+        assert_eq!(
+            splits,
+            [
+                (3.0, "(preset:Default (mid:5 card:2))".to_string()),
+                (2.0, "(preset:Default mid:5 -(mid:5 card:2))".to_string()),
+                (1.0, "(preset:Default -(mid:5 card:2) -mid:5)".to_string()),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ use crate::prelude::*;
 pub mod answering;
 pub mod bury_and_suspend;
 pub(crate) mod congrats;
+pub(crate) mod customizer;
 pub(crate) mod filtered;
 pub mod fsrs;
 pub mod new;
@@ -157,6 +157,9 @@ impl QueueBuilder {
 
     // True if limit should be decremented.
     fn add_new_card(&mut self, card: NewCard) -> bool {
+        if self.context.blocked_cards.contains(&card.id) {
+            return false;
+        }
         let bury_this_card = self
             .get_and_update_bury_mode_for_note(card.into())
             .map(|mode| mode.bury_new)
@@ -8,6 +8,7 @@ pub(crate) mod sized_chain;
 mod sorting;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 
 use intersperser::Intersperser;
@@ -26,6 +27,10 @@ use crate::deckconfig::ReviewMix;
 use crate::decks::limits::LimitTreeMap;
 use crate::prelude::*;
 use crate::scheduler::timing::SchedTimingToday;
+use crate::search::JoinSearches;
+use crate::search::SearchNode;
+use crate::search::SortMode;
+use crate::search::StateKind;
 
 /// Temporary holder for review cards that will be built into a queue.
 #[derive(Debug, Clone, Copy)]
@@ -95,6 +100,7 @@ pub(super) struct QueueSortOptions {
     pub(super) new_order: NewCardSortOrder,
     pub(super) new_gather_priority: NewCardGatherPriority,
     pub(super) review_order: ReviewCardOrder,
+    pub(super) interleave_review_decks: bool,
     pub(super) day_learn_mix: ReviewMix,
     pub(super) new_review_mix: ReviewMix,
 }
@@ -119,6 +125,8 @@ struct Context {
     seen_note_ids: HashMap<NoteId, BuryMode>,
     deck_map: HashMap<DeckId, Deck>,
     fsrs: bool,
+    /// New cards whose prerequisite notes have not been learnt yet.
+    blocked_cards: HashSet<CardId>,
 }
 
 impl QueueBuilder {
@@ -143,6 +151,13 @@ impl QueueBuilder {
         );
         let sort_options = sort_options(&root_deck, &config_map);
         let deck_map = col.storage.get_decks_map()?;
+        let blocked_cards = col
+            .search_cards(
+                SearchNode::DeckIdWithChildren(deck_id).and(SearchNode::State(StateKind::Blocked)),
+                SortMode::NoOrder,
+            )?
+            .into_iter()
+            .collect();
 
         Ok(QueueBuilder {
             new: Vec::new(),
@@ -158,12 +173,14 @@ impl QueueBuilder {
                 seen_note_ids: HashMap::new(),
                 deck_map,
                 fsrs: col.get_config_bool(BoolKey::Fsrs),
+                blocked_cards,
             },
         })
     }
 
     pub(super) fn build(mut self, learn_ahead_secs: i64) -> CardQueues {
         self.sort_new();
+        self.sort_reviews();
 
         // intraday learning and total learn count
         let intraday_learning = sort_learning(self.learning);
@@ -213,6 +230,7 @@ fn sort_options(deck: &Deck, config_map: &HashMap<DeckConfigId, DeckConfig>) ->
             new_order: config.inner.new_card_sort_order(),
             new_gather_priority: config.inner.new_card_gather_priority(),
             review_order: config.inner.review_order(),
+            interleave_review_decks: config.inner.interleave_review_decks,
             day_learn_mix: config.inner.interday_learning_mix(),
             new_review_mix: config.inner.new_mix(),
         })
@@ -281,6 +299,7 @@ mod test {
     use super::*;
     use crate::card::CardQueue;
     use crate::card::CardType;
+    use crate::card::FsrsMemoryState;
 
     impl Collection {
         fn set_deck_gather_order(&mut self, deck: &mut Deck, order: NewCardGatherPriority) {
@@ -455,6 +474,40 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn retrievability_order_puts_cards_without_memory_state_last() -> Result<()> {
+        let mut col = Collection::new();
+        col.set_config_bool(BoolKey::Fsrs, true, false)?;
+        let mut deck = col.get_or_create_normal_deck("Default").unwrap();
+        let nt = col.get_notetype_by_name("Basic")?.unwrap();
+        let mut cards = vec![];
+
+        // (interval, stability); intervals only identify the cards
+        for (interval, stability) in [(1, None), (2, Some(100.0)), (3, Some(1.0))] {
+            let mut note = nt.new_note();
+            note.set_field(0, "foo")?;
+            col.add_note(&mut note, deck.id)?;
+            let mut card = col.storage.get_card_by_ordinal(note.id, 0)?.unwrap();
+            card.interval = interval;
+            card.due = 0;
+            card.ctype = CardType::Review;
+            card.queue = CardQueue::Review;
+            card.memory_state = stability.map(|stability| FsrsMemoryState {
+                stability,
+                difficulty: 5.0,
+            });
+            cards.push(card);
+        }
+        col.update_cards_maybe_undoable(cards, false)?;
+        col.set_deck_review_order(&mut deck, ReviewCardOrder::RetrievabilityAscending);
+        assert_eq!(
+            col.queue_as_due_and_ivl(deck.id),
+            vec![(0, 3), (0, 2), (0, 1)]
+        );
+
+        Ok(())
+    }
+
     impl Collection {
         fn card_queue_len(&mut self) -> usize {
             self.get_queued_cards(5, false).unwrap().cards.len()
@@ -523,4 +576,28 @@ mod test {
         col.set_current_deck(child.id).unwrap();
         assert_eq!(col.card_queue_len(), 0);
     }
+
+    #[test]
+    fn reviews_of_subdecks_may_be_interleaved() {
+        let mut col = Collection::new();
+        col.update_default_deck_config(|config| {
+            config.review_order = ReviewCardOrder::DeckThenDay as i32;
+        });
+        let child = DeckAdder::new("Default::child").add(&mut col);
+        for deck in [DeckId(1), DeckId(1), child.id, child.id] {
+            CardAdder::new().deck(deck).due_dates(["0"]).add(&mut col);
+        }
+        let decks = |col: &mut Collection| {
+            col.queue_as_deck_and_template(DeckId(1))
+                .into_iter()
+                .map(|(deck, _)| deck)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(decks(&mut col), [DeckId(1), DeckId(1), child.id, child.id]);
+
+        col.update_default_deck_config(|config| {
+            config.interleave_review_decks = true;
+        });
+        assert_eq!(decks(&mut col), [DeckId(1), child.id, DeckId(1), child.id]);
+    }
 }
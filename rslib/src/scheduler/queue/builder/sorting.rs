@@ -2,13 +2,17 @@
 // License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::hash::Hasher;
 
 use fnv::FnvHasher;
 
+use super::DueCard;
 use super::NewCard;
 use super::NewCardSortOrder;
 use super::QueueBuilder;
+use crate::prelude::*;
 
 impl QueueBuilder {
     pub(super) fn sort_new(&mut self) {
@@ -35,6 +39,14 @@ impl QueueBuilder {
         }
     }
 
+    /// Reviews are gathered in the review order, which is kept when they are
+    /// interleaved.
+    pub(super) fn sort_reviews(&mut self) {
+        if self.context.sort_options.interleave_review_decks {
+            self.review = interleave_decks(std::mem::take(&mut self.review));
+        }
+    }
+
     fn hash_new_cards_by_id(&mut self) {
         self.new
             .iter_mut()
@@ -48,6 +60,30 @@ impl QueueBuilder {
     }
 }
 
+/// Take one card from each deck in turn, visiting decks in the order their
+/// first card appears.
+fn interleave_decks(cards: Vec<DueCard>) -> Vec<DueCard> {
+    let total = cards.len();
+    let mut decks: Vec<VecDeque<DueCard>> = vec![];
+    let mut deck_indices: HashMap<DeckId, usize> = HashMap::new();
+    for card in cards {
+        let idx = *deck_indices.entry(card.current_deck_id).or_insert_with(|| {
+            decks.push(VecDeque::new());
+            decks.len() - 1
+        });
+        decks[idx].push_back(card);
+    }
+    let mut interleaved = Vec::with_capacity(total);
+    while interleaved.len() < total {
+        for deck in &mut decks {
+            if let Some(card) = deck.pop_front() {
+                interleaved.push(card);
+            }
+        }
+    }
+    interleaved
+}
+
 fn cmp_hash(a: &NewCard, b: &NewCard) -> Ordering {
     a.hash.cmp(&b.hash)
 }
@@ -204,10 +204,20 @@ impl Collection {
     /// transaction, you probably don't need this.
     pub(crate) fn clear_study_queues(&mut self) {
         self.state.card_queues = None;
+        self.state.retention_overrides.clear();
     }
 
     pub(crate) fn maybe_clear_study_queues_after_op(&mut self, op: &OpChanges) {
-        if op.op != Op::AnswerCard && op.requires_study_queue_rebuild() {
+        // answering or edits like tagging may change the cards an override
+        // matches
+        let changes = &op.changes;
+        if changes.card || changes.note || changes.tag || changes.deck || changes.deck_config {
+            self.state.retention_overrides.clear();
+        }
+        if op.op == Op::AnswerCard {
+            return;
+        }
+        if op.requires_study_queue_rebuild() {
             self.state.card_queues = None;
         }
     }
@@ -4,6 +4,8 @@
 mod answering;
 mod states;
 
+use std::path::Path;
+
 use anki_proto::cards;
 use anki_proto::generic;
 use anki_proto::scheduler;
@@ -17,9 +19,11 @@ use anki_proto::scheduler::GetOptimalRetentionParametersResponse;
 use fsrs::FSRSItem;
 use fsrs::FSRSReview;
 use fsrs::FSRS;
+use itertools::Itertools;
 
 use crate::backend::Backend;
 use crate::prelude::*;
+use crate::scheduler::fsrs::dataset::read_fsrs_items;
 use crate::scheduler::new::NewCardDueOrder;
 use crate::scheduler::states::CardState;
 use crate::scheduler::states::SchedulingStates;
@@ -254,7 +258,8 @@ impl crate::services::SchedulerService for Collection {
         &mut self,
         input: scheduler::ComputeFsrsWeightsRequest,
     ) -> Result<scheduler::ComputeFsrsWeightsResponse> {
-        self.compute_weights(&input.search, 1, 1)
+        let grouping = input.grouping();
+        self.compute_weights(&input.search, grouping, 1, 1)
     }
 
     fn compute_optimal_retention(
@@ -271,9 +276,20 @@ impl crate::services::SchedulerService for Collection {
         input: scheduler::EvaluateWeightsRequest,
     ) -> Result<scheduler::EvaluateWeightsResponse> {
         let ret = self.evaluate_weights(&input.weights, &input.search)?;
+        let report = if input.report {
+            let sm2_retention = if input.sm2_retention > 0.0 {
+                input.sm2_retention
+            } else {
+                0.9
+            };
+            Some(self.fsrs_evaluation_report(&input.weights, &input.search, sm2_retention)?)
+        } else {
+            None
+        };
         Ok(scheduler::EvaluateWeightsResponse {
             log_loss: ret.log_loss,
             rmse_bins: ret.rmse_bins,
+            report,
         })
     }
 
@@ -296,6 +312,21 @@ impl crate::services::SchedulerService for Collection {
             delta_days: self.get_fuzz_delta(input.card_id.into(), input.interval)?,
         })
     }
+
+    fn simulate_workload(
+        &mut self,
+        input: scheduler::SimulateWorkloadRequest,
+    ) -> Result<scheduler::SimulateWorkloadResponse> {
+        self.simulate_workload(input)
+    }
+
+    fn export_fsrs_items(
+        &mut self,
+        input: scheduler::ExportFsrsItemsRequest,
+    ) -> Result<generic::UInt32> {
+        self.export_fsrs_items(&input.search, Path::new(&input.out_path), input.format())
+            .map(Into::into)
+    }
 }
 
 impl crate::services::BackendSchedulerService for Backend {
@@ -304,14 +335,20 @@ impl crate::services::BackendSchedulerService for Backend {
         req: scheduler::ComputeFsrsWeightsFromItemsRequest,
     ) -> Result<scheduler::ComputeFsrsWeightsResponse> {
         let fsrs = FSRS::new(None)?;
-        let fsrs_items = req.items.len() as u32;
-        let weights = fsrs.compute_weights(
-            req.items.into_iter().map(fsrs_item_proto_to_fsrs).collect(),
-            None,
-        )?;
+        let mut items = req
+            .items
+            .into_iter()
+            .map(fsrs_item_proto_to_fsrs)
+            .collect_vec();
+        if !req.path.is_empty() {
+            items.extend(read_fsrs_items(Path::new(&req.path))?);
+        }
+        let fsrs_items = items.len() as u32;
+        let weights = fsrs.compute_weights(items, None)?;
         Ok(ComputeFsrsWeightsResponse {
             weights,
             fsrs_items,
+            weights_overrides: vec![],
         })
     }
 }
@@ -1,6 +1,8 @@
 // Copyright: Ankitects Pty Ltd and contributors
 // License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
 
+use std::cmp::Reverse;
+
 use super::StateContext;
 use crate::collection::Collection;
 use crate::prelude::*;
@@ -32,10 +34,24 @@ static FUZZ_RANGES: [FuzzRange; 3] = [
 ];
 
 impl<'a> StateContext<'a> {
-    /// Apply fuzz, respecting the passed bounds.
+    /// Apply fuzz, respecting the passed bounds. If sibling dispersal is
+    /// enabled, the fuzzed interval may be moved within the fuzz range to keep
+    /// away from siblings.
     /// Caller must ensure reasonable bounds.
     pub(crate) fn with_review_fuzz(&self, interval: f32, minimum: u32, maximum: u32) -> u32 {
-        with_review_fuzz(self.fuzz_factor, interval, minimum, maximum)
+        let fuzzed = with_review_fuzz(self.fuzz_factor, interval, minimum, maximum);
+        if self.fuzz_factor.is_some() && self.sibling_dispersal_days > 0 {
+            let (lower, upper) = constrained_fuzz_bounds(interval, minimum, maximum);
+            disperse_from_siblings(
+                fuzzed,
+                lower,
+                upper,
+                self.sibling_due_days,
+                self.sibling_dispersal_days,
+            )
+        } else {
+            fuzzed
+        }
     }
 }
 
@@ -48,12 +64,8 @@ impl Collection {
             .get_deck(card.deck_id)?
             .or_not_found(card.deck_id)?;
         let config = self.home_deck_config(deck.config_id(), card.original_deck_id)?;
-        let fuzzed = with_review_fuzz(
-            card.get_fuzz_factor(),
-            interval as f32,
-            1,
-            config.inner.maximum_review_interval,
-        );
+        let (minimum, maximum) = card.review_interval_limits(config.inner.maximum_review_interval);
+        let fuzzed = with_review_fuzz(card.get_fuzz_factor(), interval as f32, minimum, maximum);
         Ok((fuzzed as i32) - (interval as i32))
     }
 }
@@ -72,6 +84,34 @@ pub(crate) fn with_review_fuzz(
     }
 }
 
+/// Return the interval in `lower..=upper` closest to `interval` that is at
+/// least `gap` days away from the due days of all siblings. If there is no
+/// such interval, the one furthest from its nearest sibling is used instead.
+/// Ties are broken in favour of the longer interval.
+fn disperse_from_siblings(
+    interval: u32,
+    lower: u32,
+    upper: u32,
+    sibling_due_days: &[i32],
+    gap: u32,
+) -> u32 {
+    let distance_to_nearest_sibling = |ivl: u32| {
+        sibling_due_days
+            .iter()
+            .map(|due| (ivl as i32 - due).unsigned_abs())
+            .min()
+            .unwrap_or(u32::MAX)
+    };
+    (lower..=upper)
+        .max_by_key(|&ivl| {
+            (
+                distance_to_nearest_sibling(ivl).min(gap),
+                Reverse(ivl.abs_diff(interval)),
+            )
+        })
+        .unwrap_or(interval)
+}
+
 /// Return the bounds of the fuzz range, respecting `minimum` and `maximum`.
 /// Ensure the upper bound is larger than the lower bound, if `maximum` allows
 /// it and it is larger than 1.
@@ -169,6 +209,31 @@ mod test {
         assert_lower_middle_upper!(100.0, 97, 103, 97, 100, 103);
     }
 
+    #[test]
+    fn sibling_dispersal() {
+        let mut ctx = StateContext::defaults_for_testing();
+        ctx.fuzz_factor = Some(0.5);
+        ctx.sibling_dispersal_days = 2;
+        // fuzz range is 14-20; unchanged if siblings are far enough away
+        ctx.sibling_due_days = &[10, 30];
+        assert_eq!(ctx.with_review_fuzz(17.0, 1, 1000), 17);
+        // nudged to the nearest day keeping the gap
+        ctx.sibling_due_days = &[17];
+        assert_eq!(ctx.with_review_fuzz(17.0, 1, 1000), 19);
+        ctx.sibling_due_days = &[16];
+        assert_eq!(ctx.with_review_fuzz(17.0, 1, 1000), 18);
+        // no day in range keeps the gap, so the furthest one is picked
+        ctx.sibling_due_days = &[14, 17, 20];
+        assert_eq!(ctx.with_review_fuzz(17.0, 1, 1000), 18);
+        // never leaves the fuzz range
+        ctx.sibling_due_days = &[14, 15, 16, 17, 18, 19, 20];
+        assert_eq!(ctx.with_review_fuzz(17.0, 1, 1000), 17);
+        // disabled without fuzz
+        ctx.fuzz_factor = None;
+        ctx.sibling_due_days = &[17];
+        assert_eq!(ctx.with_review_fuzz(17.0, 1, 1000), 17);
+    }
+
     #[test]
     fn invalid_values_will_not_panic() {
         constrained_fuzz_bounds(1.0, 3, 2);
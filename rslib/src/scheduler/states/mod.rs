@@ -97,7 +97,17 @@ pub(crate) struct StateContext<'a> {
     pub easy_multiplier: f32,
     pub interval_multiplier: f32,
     pub maximum_review_interval: u32,
+    /// The card's minimum interval for passing reviews. 0 if it has none.
+    pub minimum_review_interval: u32,
     pub leech_threshold: u32,
+    /// Lapses between repeated leech actions. 0 for half the threshold.
+    pub leech_repeat_interval: u32,
+    /// Minimum number of days to keep between the due dates of siblings, if
+    /// the fuzz range allows it. 0 if disabled.
+    pub sibling_dispersal_days: u32,
+    /// Due dates of the card's siblings in the review queue, relative to
+    /// today.
+    pub sibling_due_days: &'a [i32],
 
     // relearning
     pub relearn_steps: LearningSteps<'a>,
@@ -112,10 +122,11 @@ pub(crate) struct StateContext<'a> {
 impl<'a> StateContext<'a> {
     /// Return the minimum and maximum review intervals.
     /// - `maximum` is `self.maximum_review_interval`, but at least 1.
-    /// - `minimum` is as passed, but at least 1, and at most `maximum`.
+    /// - `minimum` is as passed, but at least 1 and
+    ///   `self.minimum_review_interval`, and at most `maximum`.
     pub(crate) fn min_and_max_review_intervals(&self, minimum: u32) -> (u32, u32) {
         let maximum = self.maximum_review_interval.max(1);
-        let minimum = minimum.clamp(1, maximum);
+        let minimum = minimum.max(self.minimum_review_interval).clamp(1, maximum);
         (minimum, maximum)
     }
 
@@ -131,7 +142,11 @@ impl<'a> StateContext<'a> {
             easy_multiplier: 1.3,
             interval_multiplier: 1.0,
             maximum_review_interval: 36500,
+            minimum_review_interval: 0,
             leech_threshold: 8,
+            leech_repeat_interval: 0,
+            sibling_dispersal_days: 0,
+            sibling_due_days: &[],
             relearn_steps: LearningSteps::new(&[10.0]),
             lapse_multiplier: 0.0,
             minimum_lapse_interval: 1,
@@ -207,5 +222,9 @@ mod test {
         assert_eq!(ctx.min_and_max_review_intervals(0), (1, 3));
         assert_eq!(ctx.min_and_max_review_intervals(2), (2, 3));
         assert_eq!(ctx.min_and_max_review_intervals(4), (3, 3));
+        ctx.minimum_review_interval = 2;
+        assert_eq!(ctx.min_and_max_review_intervals(0), (2, 3));
+        ctx.minimum_review_interval = 5;
+        assert_eq!(ctx.min_and_max_review_intervals(0), (3, 3));
     }
 }
@@ -77,7 +77,12 @@ impl ReviewState {
         ctx: &StateContext,
     ) -> (u32, Option<FsrsMemoryState>) {
         if let Some(states) = &ctx.fsrs_next_states {
-            (states.again.interval, Some(states.again.memory.into()))
+            // the minimum only applies to passing reviews
+            let (_, maximum) = ctx.min_and_max_review_intervals(1);
+            (
+                states.again.interval.clamp(1, maximum),
+                Some(states.again.memory.into()),
+            )
         } else {
             let interval = (((self.scheduled_days as f32) * ctx.lapse_multiplier) as u32)
                 .max(ctx.minimum_lapse_interval)
@@ -88,7 +93,7 @@ impl ReviewState {
 
     fn answer_again(self, ctx: &StateContext) -> CardState {
         let lapses = self.lapses + 1;
-        let leeched = leech_threshold_met(lapses, ctx.leech_threshold);
+        let leeched = leech_threshold_met(lapses, ctx.leech_threshold, ctx.leech_repeat_interval);
         let (scheduled_days, memory_state) = self.failing_review_interval(ctx);
         let again_review = ReviewState {
             scheduled_days,
@@ -264,11 +269,15 @@ impl ReviewState {
 
 /// True when lapses is at threshold, or every half threshold after that.
 /// Non-even thresholds round up the half threshold.
-fn leech_threshold_met(lapses: u32, threshold: u32) -> bool {
+fn leech_threshold_met(lapses: u32, threshold: u32, repeat_interval: u32) -> bool {
     if threshold > 0 {
-        let half_threshold = (threshold as f32 / 2.0).ceil().max(1.0) as u32;
-        // at threshold, and every half threshold after that, rounding up
-        lapses >= threshold && (lapses - threshold) % half_threshold == 0
+        let repeat_interval = if repeat_interval > 0 {
+            repeat_interval
+        } else {
+            (threshold as f32 / 2.0).ceil().max(1.0) as u32
+        };
+        // at threshold, and every repeat interval after that
+        lapses >= threshold && (lapses - threshold) % repeat_interval == 0
     } else {
         false
     }
@@ -295,35 +304,44 @@ fn constrain_passing_interval(ctx: &StateContext, interval: f32, minimum: u32, f
 
 #[cfg(test)]
 mod test {
+    use fsrs::ItemState;
+    use fsrs::MemoryState;
+
     use super::*;
 
     #[test]
     fn leech_threshold() {
-        assert!(!leech_threshold_met(0, 3));
-        assert!(!leech_threshold_met(1, 3));
-        assert!(!leech_threshold_met(2, 3));
-        assert!(leech_threshold_met(3, 3));
-        assert!(!leech_threshold_met(4, 3));
-        assert!(leech_threshold_met(5, 3));
-        assert!(!leech_threshold_met(6, 3));
-        assert!(leech_threshold_met(7, 3));
-
-        assert!(!leech_threshold_met(7, 8));
-        assert!(leech_threshold_met(8, 8));
-        assert!(!leech_threshold_met(9, 8));
-        assert!(!leech_threshold_met(10, 8));
-        assert!(!leech_threshold_met(11, 8));
-        assert!(leech_threshold_met(12, 8));
-        assert!(!leech_threshold_met(13, 8));
+        assert!(!leech_threshold_met(0, 3, 0));
+        assert!(!leech_threshold_met(1, 3, 0));
+        assert!(!leech_threshold_met(2, 3, 0));
+        assert!(leech_threshold_met(3, 3, 0));
+        assert!(!leech_threshold_met(4, 3, 0));
+        assert!(leech_threshold_met(5, 3, 0));
+        assert!(!leech_threshold_met(6, 3, 0));
+        assert!(leech_threshold_met(7, 3, 0));
+
+        assert!(!leech_threshold_met(7, 8, 0));
+        assert!(leech_threshold_met(8, 8, 0));
+        assert!(!leech_threshold_met(9, 8, 0));
+        assert!(!leech_threshold_met(10, 8, 0));
+        assert!(!leech_threshold_met(11, 8, 0));
+        assert!(leech_threshold_met(12, 8, 0));
+        assert!(!leech_threshold_met(13, 8, 0));
 
         // 0 means off
-        assert!(!leech_threshold_met(0, 0));
+        assert!(!leech_threshold_met(0, 0, 0));
+
+        // custom repeat interval
+        assert!(leech_threshold_met(8, 8, 2));
+        assert!(!leech_threshold_met(9, 8, 2));
+        assert!(leech_threshold_met(10, 8, 2));
+        assert!(leech_threshold_met(12, 8, 2));
 
         // no div by zero; half of 1 is 1
-        assert!(!leech_threshold_met(0, 1));
-        assert!(leech_threshold_met(1, 1));
-        assert!(leech_threshold_met(2, 1));
-        assert!(leech_threshold_met(3, 1));
+        assert!(!leech_threshold_met(0, 1, 0));
+        assert!(leech_threshold_met(1, 1, 0));
+        assert!(leech_threshold_met(2, 1, 0));
+        assert!(leech_threshold_met(3, 1, 0));
     }
 
     #[test]
@@ -371,4 +389,33 @@ mod test {
         ctx.fuzz_factor = Some(0.0);
         assert_eq!(state.passing_review_intervals(&ctx), (1, 3, 4));
     }
+
+    #[test]
+    fn fsrs_failing_interval_respects_maximum() {
+        let mut ctx = StateContext::defaults_for_testing();
+        let item = |interval| ItemState {
+            memory: MemoryState {
+                stability: 50.0,
+                difficulty: 5.0,
+            },
+            interval,
+        };
+        ctx.fsrs_next_states = Some(NextStates {
+            again: item(30),
+            hard: item(60),
+            good: item(90),
+            easy: item(120),
+        });
+        let state = ReviewState {
+            scheduled_days: 100,
+            elapsed_days: 100,
+            ..Default::default()
+        };
+        assert_eq!(state.failing_review_interval(&ctx).0, 30);
+        // the minimum is for passing reviews only
+        ctx.minimum_review_interval = 40;
+        assert_eq!(state.failing_review_interval(&ctx).0, 30);
+        ctx.maximum_review_interval = 10;
+        assert_eq!(state.failing_review_interval(&ctx).0, 10);
+    }
 }
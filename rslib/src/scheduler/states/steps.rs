@@ -8,6 +8,36 @@ const DAY: u32 = 60 * 60 * 24;
 pub(crate) struct LearningSteps<'a> {
     /// The steps in minutes.
     steps: &'a [f32],
+    /// Set if the preset adapts the steps to the card's history.
+    adaptive: Option<AdaptiveSteps>,
+}
+
+/// The card's history on the current day, used to adapt the fixed steps. The
+/// adapted steps stay within the range of the configured ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct AdaptiveSteps {
+    /// Consecutive Again answers today, up to the current answer.
+    pub again_streak: u32,
+    /// Consecutive passing answers today, up to the current answer.
+    pub pass_streak: u32,
+    /// How FSRS expects an Again and a Good answer to change the card's
+    /// stability, as ratios of the next stability to the current one. Only set
+    /// if FSRS is enabled and the card has a memory state.
+    pub fsrs_stability_change: Option<(f32, f32)>,
+    pub secs_until_rollover: u32,
+}
+
+/// Limits how far FSRS's short-term stability change can scale a step.
+const MAX_FSRS_STEP_FACTOR: f32 = 2.0;
+
+/// Maps a stability ratio to a step factor, so that a stability FSRS doesn't
+/// expect to change leaves the step unchanged.
+fn fsrs_step_factor(ratio: f32) -> f32 {
+    if ratio.is_finite() && ratio > 0.0 {
+        ratio.clamp(1.0 / MAX_FSRS_STEP_FACTOR, MAX_FSRS_STEP_FACTOR)
+    } else {
+        1.0
+    }
 }
 
 fn to_secs(v: f32) -> u32 {
@@ -17,7 +47,14 @@ fn to_secs(v: f32) -> u32 {
 impl<'a> LearningSteps<'a> {
     /// Takes `steps` as minutes.
     pub(crate) fn new(steps: &[f32]) -> LearningSteps<'_> {
-        LearningSteps { steps }
+        LearningSteps {
+            steps,
+            adaptive: None,
+        }
+    }
+
+    pub(crate) fn with_adaptive(self, adaptive: Option<AdaptiveSteps>) -> Self {
+        LearningSteps { adaptive, ..self }
     }
 
     /// Strip off 'learning today', and ensure index is in bounds.
@@ -34,11 +71,13 @@ impl<'a> LearningSteps<'a> {
 
     /// Cards in learning must always have at least one learning step.
     pub(crate) fn again_delay_secs_learn(&self) -> u32 {
-        self.secs_at_index(0).unwrap_or(DEFAULT_SECS_IF_MISSING)
+        self.secs_at_index(0)
+            .map(|secs| self.adapt_again(secs))
+            .unwrap_or(DEFAULT_SECS_IF_MISSING)
     }
 
     pub(crate) fn again_delay_secs_relearn(&self) -> Option<u32> {
-        self.secs_at_index(0)
+        self.secs_at_index(0).map(|secs| self.adapt_again(secs))
     }
 
     pub(crate) fn hard_delay_secs(self, remaining: u32) -> Option<u32> {
@@ -74,6 +113,56 @@ impl<'a> LearningSteps<'a> {
     pub(crate) fn good_delay_secs(self, remaining: u32) -> Option<u32> {
         let idx = self.get_index(remaining);
         self.secs_at_index(idx + 1)
+            .map(|secs| self.adapt_good(secs))
+    }
+
+    /// Repeated failures bring the card back sooner, as does a drop in FSRS's
+    /// short-term stability.
+    fn adapt_again(self, secs: u32) -> u32 {
+        let Some(adaptive) = self.adaptive else {
+            return secs;
+        };
+        let fsrs_factor = adaptive
+            .fsrs_stability_change
+            .map(|(again, _)| fsrs_step_factor(again))
+            .unwrap_or(1.0);
+        let factor = 0.5f32.powi(adaptive.again_streak.min(4) as i32) * fsrs_factor;
+        self.clamp_adapted(secs, (secs as f32 * factor) as u32, adaptive)
+    }
+
+    /// Repeated successes push the card back further, as does a rise in FSRS's
+    /// short-term stability. The absolute stability is not used, as it
+    /// describes the long-term memory and would always select the last step;
+    /// the FSRS version in use has no separate short-term stability, so its
+    /// relative change stands in for it.
+    fn adapt_good(self, secs: u32) -> u32 {
+        let Some(adaptive) = self.adaptive else {
+            return secs;
+        };
+        let fsrs_factor = adaptive
+            .fsrs_stability_change
+            .map(|(_, good)| fsrs_step_factor(good))
+            .unwrap_or(1.0);
+        let factor = 1.5f32.powi(adaptive.pass_streak.min(4) as i32) * fsrs_factor;
+        self.clamp_adapted(secs, (secs as f32 * factor) as u32, adaptive)
+    }
+
+    /// Keep the adapted delay within the configured steps, and don't let it
+    /// move a step due today into the next day.
+    fn clamp_adapted(self, secs: u32, adapted: u32, adaptive: AdaptiveSteps) -> u32 {
+        let (min, max) = self
+            .steps
+            .iter()
+            .copied()
+            .map(to_secs)
+            .fold((u32::MAX, 0), |(min, max), step| {
+                (min.min(step), max.max(step))
+            });
+        let mut adapted = adapted.clamp(min.min(max), max);
+        if secs < adaptive.secs_until_rollover {
+            adapted = adapted.min(adaptive.secs_until_rollover.saturating_sub(1).max(secs));
+        }
+        adapted
     }
 
     pub(crate) fn current_delay_secs(self, remaining: u32) -> u32 {
@@ -115,6 +204,59 @@ mod test {
         };
     }
 
+    #[test]
+    fn adaptive_steps() {
+        let steps = [1.0, 10.0, 60.0];
+        let adaptive = |again_streak, pass_streak| {
+            LearningSteps::new(&steps).with_adaptive(Some(AdaptiveSteps {
+                again_streak,
+                pass_streak,
+                secs_until_rollover: DAY,
+                ..Default::default()
+            }))
+        };
+        // without a history, the fixed steps are used
+        assert_delay_secs!(steps, 3, 60, Some(330), Some(600));
+        assert_eq!(adaptive(0, 0).good_delay_secs(3), Some(600));
+        // failures shorten the again delay, but not below the first step
+        assert_eq!(adaptive(2, 0).again_delay_secs_learn(), 60);
+        assert_eq!(
+            LearningSteps::new(&[10.0, 1.0])
+                .with_adaptive(adaptive(1, 0).adaptive)
+                .again_delay_secs_learn(),
+            300
+        );
+        // successes lengthen the good delay, up to the last step
+        assert_eq!(adaptive(0, 1).good_delay_secs(3), Some(900));
+        assert_eq!(adaptive(0, 4).good_delay_secs(3), Some(3037));
+        assert_eq!(adaptive(0, 1).good_delay_secs(2), Some(3600));
+        // FSRS's stability change scales the steps, within limits
+        let with_fsrs = |fsrs_stability_change| {
+            LearningSteps::new(&steps).with_adaptive(Some(AdaptiveSteps {
+                fsrs_stability_change: Some(fsrs_stability_change),
+                secs_until_rollover: DAY,
+                ..Default::default()
+            }))
+        };
+        assert_eq!(with_fsrs((1.0, 1.0)).good_delay_secs(3), Some(600));
+        assert_eq!(with_fsrs((1.0, 1.2)).good_delay_secs(3), Some(720));
+        assert_eq!(with_fsrs((1.0, 100.0)).good_delay_secs(3), Some(1200));
+        assert_eq!(with_fsrs((1.0, f32::NAN)).good_delay_secs(3), Some(600));
+        assert_eq!(
+            LearningSteps::new(&[10.0, 1.0])
+                .with_adaptive(with_fsrs((0.25, 1.0)).adaptive)
+                .again_delay_secs_learn(),
+            300
+        );
+        // steps due today stay today
+        let near_rollover = LearningSteps::new(&steps).with_adaptive(Some(AdaptiveSteps {
+            pass_streak: 4,
+            secs_until_rollover: 1000,
+            ..Default::default()
+        }));
+        assert_eq!(near_rollover.good_delay_secs(3), Some(999));
+    }
+
     #[test]
     fn delay_secs() {
         // if no other step, hard delay is 50% above again secs
@@ -108,6 +108,8 @@ pub enum PropertyKind {
     Stability(f32),
     Difficulty(f32),
     Retrievability(f32),
+    MinimumInterval(u32),
+    MaximumInterval(u32),
     CustomDataNumber { key: String, value: f32 },
     CustomDataString { key: String, value: String },
 }
@@ -122,6 +124,8 @@ pub enum StateKind {
     UserBuried,
     SchedBuried,
     Suspended,
+    /// New cards waiting for their prerequisite notes to be learnt.
+    Blocked,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -414,6 +418,8 @@ fn parse_prop(prop_clause: &str) -> ParseResult<SearchNode> {
         tag("pos"),
         tag("rated"),
         tag("resched"),
+        tag("minivl"),
+        tag("maxivl"),
         tag("s"),
         tag("d"),
         tag("r"),
@@ -461,6 +467,8 @@ fn parse_prop(prop_clause: &str) -> ParseResult<SearchNode> {
         "s" => PropertyKind::Stability(parse_f32(num, prop_clause)?),
         "d" => PropertyKind::Difficulty(parse_f32(num, prop_clause)?),
         "r" => PropertyKind::Retrievability(parse_f32(num, prop_clause)?),
+        "minivl" => PropertyKind::MinimumInterval(parse_u32(num, prop_clause)?),
+        "maxivl" => PropertyKind::MaximumInterval(parse_u32(num, prop_clause)?),
         prop if prop.starts_with("cdn:") => PropertyKind::CustomDataNumber {
             key: prop.strip_prefix("cdn:").unwrap().into(),
             value: parse_f32(num, prop_clause)?,
@@ -605,6 +613,7 @@ fn parse_state(s: &str) -> ParseResult<SearchNode> {
         "buried-manually" => UserBuried,
         "buried-sibling" => SchedBuried,
         "suspended" => Suspended,
+        "blocked" => Blocked,
         _ => {
             return Err(parse_failure(
                 s,
@@ -379,6 +379,12 @@ impl SqlWriter<'_> {
                 let d = d * 9.0 + 1.0;
                 write!(self.sql, "extract_fsrs_variable(c.data, 'd') {op} {d}").unwrap()
             }
+            PropertyKind::MinimumInterval(ivl) => {
+                write!(self.sql, "extract_interval_limit(c.data, 'min') {op} {ivl}").unwrap()
+            }
+            PropertyKind::MaximumInterval(ivl) => {
+                write!(self.sql, "extract_interval_limit(c.data, 'max') {op} {ivl}").unwrap()
+            }
             PropertyKind::Retrievability(r) => {
                 let elap = self.col.timing_today()?.days_elapsed;
                 write!(
@@ -438,6 +444,15 @@ impl SqlWriter<'_> {
             StateKind::SchedBuried => {
                 write!(self.sql, "c.queue = {}", CardQueue::SchedBuried as i8)
             }
+            StateKind::Blocked => write!(
+                self.sql,
+                "(c.type = {new} and exists (select 1 from cards p where p.nid in \
+                (select value from json_each(extract_prerequisites(c.data))) \
+                and p.type in ({new},{lrn}) and p.queue != {suspended}))",
+                new = CardType::New as i8,
+                lrn = CardType::Learn as i8,
+                suspended = CardQueue::Suspended as i8,
+            ),
         }
         .unwrap();
         Ok(())
@@ -1181,6 +1196,12 @@ mod test {
             s(ctx, "is:new").0,
             format!("(c.type = {})", CardType::New as i8)
         );
+        assert_eq!(
+            s(ctx, "is:blocked").0,
+            "((c.type = 0 and exists (select 1 from cards p where p.nid in \
+            (select value from json_each(extract_prerequisites(c.data))) \
+            and p.type in (0,1) and p.queue != -1)))"
+        );
 
         // rated
         assert_eq!(
@@ -160,6 +160,7 @@ fn write_state(kind: &StateKind) -> String {
             UserBuried => "buried-manually",
             SchedBuried => "buried-sibling",
             Suspended => "suspended",
+            Blocked => "blocked",
         }
     )
 }
@@ -176,6 +177,8 @@ fn write_property(operator: &str, kind: &PropertyKind) -> String {
         Stability(u) => format!("prop:s{}{}", operator, u),
         Difficulty(u) => format!("prop:d{}{}", operator, u),
         Retrievability(u) => format!("prop:r{}{}", operator, u),
+        MinimumInterval(u) => format!("prop:minivl{}{}", operator, u),
+        MaximumInterval(u) => format!("prop:maxivl{}{}", operator, u),
         Rated(u, ease) => match ease {
             RatingKind::AnswerButton(val) => format!("prop:rated{}{}:{}", operator, u, val),
             RatingKind::AnyAnswerButton => format!("prop:rated{}{}", operator, u),
@@ -3,6 +3,7 @@
 
 mod card;
 mod graphs;
+mod replay;
 mod service;
 mod today;
 
@@ -0,0 +1,387 @@
+// Copyright: Ankitects Pty Ltd and contributors
+// License: GNU AGPL, version 3 or later; http://www.gnu.org/licenses/agpl.html
+
+//! Replays a card's review history through the scheduler, to explain how it
+//! ended up with its current interval.
+
+use anki_proto::stats::replay_card_history_response::step::Divergence;
+use anki_proto::stats::replay_card_history_response::Step;
+use anki_proto::stats::ReplayCardHistoryResponse;
+use fsrs::MemoryState;
+use fsrs::NextStates;
+use fsrs::FSRS;
+
+use crate::card::CardType;
+use crate::prelude::*;
+use crate::revlog::RevlogEntry;
+use crate::revlog::RevlogReviewKind;
+use crate::scheduler::states::fuzz::fuzz_bounds;
+use crate::scheduler::states::steps::LearningSteps;
+use crate::scheduler::states::CardState;
+use crate::scheduler::states::NewState;
+use crate::scheduler::states::NormalState;
+use crate::scheduler::states::ReviewState;
+use crate::scheduler::states::StateContext;
+
+impl Collection {
+    /// Replay the card's revlog with the provided preset, or the preset of
+    /// the card's home deck.
+    pub fn replay_card_history(
+        &mut self,
+        cid: CardId,
+        config_id: Option<DeckConfigId>,
+    ) -> Result<ReplayCardHistoryResponse> {
+        let card = self.storage.get_card(cid)?.or_not_found(cid)?;
+        let config = if let Some(config_id) = config_id {
+            self.get_deck_config(config_id, false)?
+                .or_not_found(config_id)?
+        } else {
+            let home_deck_id = card.original_or_current_deck_id();
+            let home_deck = self
+                .storage
+                .get_deck(home_deck_id)?
+                .or_not_found(home_deck_id)?;
+            self.home_deck_config(home_deck.config_id(), card.original_deck_id)?
+        };
+        let fsrs = if self.get_config_bool(BoolKey::Fsrs) {
+            let weights = self.fsrs_weights_for_card(&card, &config)?;
+            let retention = self.desired_retention_for_card(&card, &config)?;
+            Some((FSRS::new(Some(weights))?, retention))
+        } else {
+            None
+        };
+        let revlog = self.storage.get_revlog_entries_for_card(card.id)?;
+        let next_day_at = self.timing_today()?.next_day_at;
+        let steps = replay(
+            &revlog,
+            &config,
+            card.review_interval_limits(config.inner.maximum_review_interval),
+            fsrs.as_ref().map(|(fsrs, retention)| (fsrs, *retention)),
+            next_day_at,
+        );
+        let card_matches = state_matches_card(steps.last().map(|s| s.1), &card);
+        Ok(ReplayCardHistoryResponse {
+            steps: steps.into_iter().map(|(step, _)| step).collect(),
+            card_matches,
+        })
+    }
+}
+
+/// `interval_limits` are the card's minimum and maximum review intervals.
+fn replay(
+    revlog: &[RevlogEntry],
+    config: &DeckConfig,
+    interval_limits: (u32, u32),
+    fsrs: Option<(&FSRS, f32)>,
+    next_day_at: TimestampSecs,
+) -> Vec<(Step, CardState)> {
+    let mut state: CardState = NewState::default().into();
+    let mut memory: Option<MemoryState> = None;
+    let mut last_review_day: Option<u64> = None;
+    let mut steps = Vec::with_capacity(revlog.len());
+    for entry in revlog {
+        let day = next_day_at.elapsed_days_since(entry.id.as_secs());
+        let (replayed, divergence) = match entry.review_kind {
+            RevlogReviewKind::Manual => (manual_state(state, entry), Divergence::Manual),
+            _ if entry.button_chosen == 0 => (manual_state(state, entry), Divergence::Manual),
+            // early reviews are logged as filtered too, but reschedule the card
+            RevlogReviewKind::Filtered if is_preview(entry) => (state, Divergence::Filtered),
+            _ => {
+                let elapsed_days = last_review_day
+                    .map(|last| last.saturating_sub(day) as u32)
+                    .unwrap_or_default();
+                let fsrs_next_states =
+                    fsrs.map(|(fsrs, retention)| fsrs.next_states(memory, retention, elapsed_days));
+                let ctx = replay_context(config, interval_limits, fsrs_next_states.clone());
+                let current = with_elapsed_days(state, elapsed_days);
+                let next = current.next_states(&ctx);
+                let states = fsrs_next_states.as_ref();
+                let (answered, memory_state) = match entry.button_chosen {
+                    1 => (next.again, states.map(|s| s.again.memory)),
+                    2 => (next.hard, states.map(|s| s.hard.memory)),
+                    3 => (next.good, states.map(|s| s.good.memory)),
+                    _ => (next.easy, states.map(|s| s.easy.memory)),
+                };
+                let divergence = if current.revlog_kind() != entry.review_kind {
+                    Divergence::State
+                } else {
+                    interval_divergence(
+                        revlog_interval(answered, entry.id.as_secs(), next_day_at),
+                        entry.interval,
+                    )
+                };
+                memory = memory_state.or(memory);
+                last_review_day = Some(day);
+                (answered, divergence)
+            }
+        };
+        let replayed_interval = revlog_interval(replayed, entry.id.as_secs(), next_day_at);
+        let replayed_ease = replayed
+            .review_state()
+            .map(|review| (review.ease_factor * 1000.0).round() as u32)
+            .unwrap_or_default();
+        // continue from the logged interval, so that every step is compared
+        // with what the scheduler did at the time
+        state = with_scheduled_days(replayed, entry.interval);
+        steps.push((
+            Step {
+                time: entry.id.as_secs().0,
+                review_kind: entry.review_kind.into(),
+                button_chosen: entry.button_chosen as u32,
+                logged_interval: entry.interval,
+                replayed_interval,
+                logged_ease: entry.ease_factor,
+                replayed_ease,
+                state: Some(replayed.into()),
+                divergence: divergence as i32,
+            },
+            state,
+        ));
+    }
+    steps
+}
+
+fn replay_context(
+    config: &DeckConfig,
+    (minimum_interval, maximum_interval): (u32, u32),
+    fsrs_next_states: Option<NextStates>,
+) -> StateContext<'_> {
+    StateContext {
+        fuzz_factor: None,
+        fsrs_next_states,
+        steps: LearningSteps::new(&config.inner.learn_steps),
+        graduating_interval_good: config.inner.graduating_interval_good,
+        graduating_interval_easy: config.inner.graduating_interval_easy,
+        initial_ease_factor: config.inner.initial_ease,
+        hard_multiplier: config.inner.hard_multiplier,
+        easy_multiplier: config.inner.easy_multiplier,
+        interval_multiplier: config.inner.interval_multiplier,
+        maximum_review_interval: maximum_interval,
+        minimum_review_interval: minimum_interval,
+        leech_threshold: config.inner.leech_threshold,
+        leech_repeat_interval: config.inner.leech_repeat_interval,
+        sibling_dispersal_days: 0,
+        sibling_due_days: &[],
+        relearn_steps: LearningSteps::new(&config.inner.relearn_steps),
+        lapse_multiplier: config.inner.lapse_multiplier,
+        minimum_lapse_interval: config.inner.minimum_lapse_interval,
+        in_filtered_deck: false,
+        preview_step: 0,
+    }
+}
+
+/// Forgetting logs a zero interval and ease, and setting the due date the
+/// new interval.
+fn manual_state(state: CardState, entry: &RevlogEntry) -> CardState {
+    if entry.interval == 0 && entry.ease_factor == 0 {
+        NewState::default().into()
+    } else if entry.interval > 0 {
+        let review = state.review_state().unwrap_or_default();
+        ReviewState {
+            scheduled_days: entry.interval as u32,
+            elapsed_days: 0,
+            ease_factor: if entry.ease_factor > 0 {
+                entry.ease_factor as f32 / 1000.0
+            } else {
+                review.ease_factor
+            },
+            ..review
+        }
+        .into()
+    } else {
+        state
+    }
+}
+
+/// Previews don't change the card's scheduling, so they log no ease and a
+/// learning or zero interval.
+fn is_preview(entry: &RevlogEntry) -> bool {
+    entry.interval <= 0 && entry.ease_factor == 0
+}
+
+fn with_elapsed_days(state: CardState, elapsed_days: u32) -> CardState {
+    match state {
+        CardState::Normal(NormalState::Review(review)) => ReviewState {
+            elapsed_days,
+            ..review
+        }
+        .into(),
+        other => other,
+    }
+}
+
+fn with_scheduled_days(state: CardState, logged_interval: i32) -> CardState {
+    match state {
+        CardState::Normal(NormalState::Review(review)) if logged_interval > 0 => ReviewState {
+            scheduled_days: logged_interval as u32,
+            ..review
+        }
+        .into(),
+        other => other,
+    }
+}
+
+/// The interval of the state as it would have been logged at `time`.
+fn revlog_interval(state: CardState, time: TimestampSecs, next_day_at: TimestampSecs) -> i32 {
+    let secs_until_rollover = match (next_day_at.0 - time.0).rem_euclid(86_400) {
+        0 => 86_400,
+        secs => secs as u32,
+    };
+    state
+        .interval_kind()
+        .maybe_as_days(secs_until_rollover)
+        .as_revlog_interval()
+}
+
+fn interval_divergence(replayed: i32, logged: i32) -> Divergence {
+    if replayed == logged {
+        Divergence::None
+    } else if replayed > 0 && logged > 0 {
+        let (lower, upper) = fuzz_bounds(replayed as f32);
+        if (lower..=upper).contains(&(logged as u32)) {
+            Divergence::Fuzz
+        } else {
+            Divergence::Settings
+        }
+    } else {
+        Divergence::Settings
+    }
+}
+
+fn state_matches_card(state: Option<CardState>, card: &Card) -> bool {
+    match state.unwrap_or_else(|| NewState::default().into()) {
+        CardState::Normal(NormalState::New(_)) => card.ctype == CardType::New,
+        CardState::Normal(NormalState::Learning(_)) => card.ctype == CardType::Learn,
+        CardState::Normal(NormalState::Review(review)) => {
+            card.ctype == CardType::Review && card.interval == review.scheduled_days
+        }
+        CardState::Normal(NormalState::Relearning(_)) => card.ctype == CardType::Relearn,
+        CardState::Filtered(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scheduler::fsrs::weights::tests::revlog;
+
+    const NEXT_DAY_AT: TimestampSecs = TimestampSecs(86400 * 100);
+
+    fn entry(kind: RevlogReviewKind, days_ago: i64, button: u8, interval: i32) -> RevlogEntry {
+        RevlogEntry {
+            button_chosen: button,
+            interval,
+            ..revlog(kind, days_ago)
+        }
+    }
+
+    fn divergences(steps: &[(Step, CardState)]) -> Vec<Divergence> {
+        steps.iter().map(|(step, _)| step.divergence()).collect()
+    }
+
+    #[test]
+    fn divergences_are_highlighted() {
+        let config = DeckConfig::default();
+        // learning steps of 1 and 10 minutes, then graduating with 1 day
+        let steps = replay(
+            &[
+                entry(RevlogReviewKind::Learning, 20, 3, -600),
+                entry(RevlogReviewKind::Learning, 20, 3, 1),
+                entry(RevlogReviewKind::Review, 19, 3, 3),
+                entry(RevlogReviewKind::Review, 16, 3, 8),
+            ],
+            &config,
+            (1, config.inner.maximum_review_interval),
+            None,
+            NEXT_DAY_AT,
+        );
+        assert_eq!(
+            steps
+                .iter()
+                .map(|s| s.0.replayed_interval)
+                .collect::<Vec<_>>(),
+            [-600, 1, 3, 8]
+        );
+        assert!(divergences(&steps).iter().all(|d| *d == Divergence::None));
+
+        // the card's maximum interval is respected
+        let steps = replay(
+            &[
+                entry(RevlogReviewKind::Learning, 20, 3, -600),
+                entry(RevlogReviewKind::Learning, 20, 3, 1),
+                entry(RevlogReviewKind::Review, 19, 3, 3),
+                entry(RevlogReviewKind::Review, 16, 3, 5),
+            ],
+            &config,
+            (1, 5),
+            None,
+            NEXT_DAY_AT,
+        );
+        assert!(divergences(&steps).iter().all(|d| *d == Divergence::None));
+
+        // a fuzzed interval, a changed multiplier, and a manual reschedule
+        let steps = replay(
+            &[
+                entry(RevlogReviewKind::Learning, 30, 4, 4),
+                entry(RevlogReviewKind::Review, 26, 3, 11),
+                entry(RevlogReviewKind::Review, 15, 3, 50),
+                entry(RevlogReviewKind::Manual, 10, 0, 200),
+            ],
+            &config,
+            (1, config.inner.maximum_review_interval),
+            None,
+            NEXT_DAY_AT,
+        );
+        assert_eq!(
+            divergences(&steps),
+            [
+                Divergence::None,
+                Divergence::Fuzz,
+                Divergence::Settings,
+                Divergence::Manual
+            ]
+        );
+        let card = Card {
+            ctype: CardType::Review,
+            interval: 200,
+            ..Default::default()
+        };
+        assert!(state_matches_card(steps.last().map(|s| s.1), &card));
+    }
+
+    #[test]
+    fn early_reviews_reschedule_but_previews_dont() {
+        let config = DeckConfig::default();
+        let steps = replay(
+            &[
+                entry(RevlogReviewKind::Learning, 20, 3, -600),
+                entry(RevlogReviewKind::Learning, 20, 3, 1),
+                entry(RevlogReviewKind::Review, 19, 3, 3),
+                // reviewed a day early, so 2 days * 2.5 ease
+                entry(RevlogReviewKind::Filtered, 17, 3, 5),
+                entry(RevlogReviewKind::Filtered, 16, 3, 0),
+            ],
+            &config,
+            (1, config.inner.maximum_review_interval),
+            None,
+            NEXT_DAY_AT,
+        );
+        assert_eq!(
+            divergences(&steps),
+            [
+                Divergence::None,
+                Divergence::None,
+                Divergence::None,
+                Divergence::None,
+                Divergence::Filtered
+            ]
+        );
+        assert_eq!(steps[3].0.replayed_interval, 5);
+        let card = Card {
+            ctype: CardType::Review,
+            interval: 5,
+            ..Default::default()
+        };
+        assert!(state_matches_card(steps.last().map(|s| s.1), &card));
+    }
+}
@@ -12,6 +12,13 @@ impl crate::services::StatsService for Collection {
         self.card_stats(input.cid.into())
     }
 
+    fn replay_card_history(
+        &mut self,
+        input: anki_proto::stats::ReplayCardHistoryRequest,
+    ) -> error::Result<anki_proto::stats::ReplayCardHistoryResponse> {
+        self.replay_card_history(input.card_id.into(), input.config_id.map(Into::into))
+    }
+
     fn graphs(
         &mut self,
         input: anki_proto::stats::GraphsRequest,
@@ -48,6 +48,30 @@ pub(crate) struct CardData {
     /// state.
     #[serde(default, rename = "cd", skip_serializing_if = "meta_is_empty")]
     pub(crate) custom_data: String,
+    #[serde(
+        rename = "pre",
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "default_on_invalid"
+    )]
+    pub(crate) prerequisites: Vec<NoteId>,
+    #[serde(
+        rename = "mh",
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "default_on_invalid"
+    )]
+    pub(crate) memory_state_hash: Option<u32>,
+    #[serde(
+        rename = "minivl",
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "default_on_invalid"
+    )]
+    pub(crate) minimum_interval: Option<u32>,
+    #[serde(
+        rename = "maxivl",
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "default_on_invalid"
+    )]
+    pub(crate) maximum_interval: Option<u32>,
 }
 
 impl CardData {
@@ -58,6 +82,10 @@ impl CardData {
             fsrs_difficulty: card.memory_state.as_ref().map(|m| m.difficulty),
             fsrs_desired_retention: card.desired_retention,
             custom_data: card.custom_data.clone(),
+            prerequisites: card.prerequisites.clone(),
+            memory_state_hash: card.memory_state_hash,
+            minimum_interval: card.minimum_interval,
+            maximum_interval: card.maximum_interval,
         }
     }
 
@@ -160,6 +188,10 @@ mod test {
             fsrs_difficulty: Some(1.234567),
             fsrs_desired_retention: Some(0.987654),
             custom_data: "".to_string(),
+            prerequisites: vec![],
+            memory_state_hash: None,
+            minimum_interval: None,
+            maximum_interval: None,
         };
         assert_eq!(
             data.convert_to_json().unwrap(),
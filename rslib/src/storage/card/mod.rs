@@ -4,6 +4,7 @@
 pub(crate) mod data;
 pub(crate) mod filtered;
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt;
@@ -83,6 +84,10 @@ fn row_to_card(row: &Row) -> result::Result<Card, rusqlite::Error> {
         memory_state: data.memory_state(),
         desired_retention: data.fsrs_desired_retention,
         custom_data: data.custom_data,
+        prerequisites: data.prerequisites,
+        memory_state_hash: data.memory_state_hash,
+        minimum_interval: data.minimum_interval,
+        maximum_interval: data.maximum_interval,
     })
 }
 
@@ -451,6 +456,23 @@ impl super::SqliteStorage {
             .collect()
     }
 
+    /// The days on which the other cards of the note are due for review,
+    /// including cards that are currently in a filtered deck.
+    pub(crate) fn sibling_review_due_days(&self, cid: CardId, nid: NoteId) -> Result<Vec<i32>> {
+        self.db
+            .prepare_cached(&format!(
+                "select (case when odid != 0 and odue != 0 then odue else due end) \
+                from cards where nid = ? and id != ? and type in ({review},{relearn}) \
+                and queue in ({rev},{daylrn})",
+                review = CardType::Review as i8,
+                relearn = CardType::Relearn as i8,
+                rev = CardQueue::Review as i8,
+                daylrn = CardQueue::DayLearn as i8,
+            ))?
+            .query_and_then(params![nid, cid], |r| Ok(r.get(0)?))?
+            .collect()
+    }
+
     pub(crate) fn get_all_card_ids(&self) -> Result<HashSet<CardId>> {
         self.db
             .prepare("SELECT id FROM cards")?
@@ -552,6 +574,38 @@ impl super::SqliteStorage {
             .collect()
     }
 
+    /// The memory state hash and desired retention of the searched cards that
+    /// have a memory state, without loading the whole cards.
+    pub(crate) fn memory_state_hashes_of_searched_cards(
+        &self,
+    ) -> Result<HashMap<CardId, (u32, Option<f32>)>> {
+        let mut stmt = self.db.prepare_cached(
+            "select id, data from cards where id in (select cid from search_cids)",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut hashes = HashMap::new();
+        while let Some(row) = rows.next()? {
+            let data: CardData = row.get(1)?;
+            if let Some(hash) = data.memory_state().and(data.memory_state_hash) {
+                hashes.insert(row.get(0)?, (hash, data.fsrs_desired_retention));
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// The distinct notetypes and templates of the searched cards.
+    pub(crate) fn notetypes_and_templates_of_searched_cards(
+        &self,
+    ) -> Result<Vec<(NotetypeId, u16)>> {
+        self.db
+            .prepare(
+                "select distinct n.mid, c.ord from cards c, notes n where c.nid = n.id \
+                and c.id in (select cid from search_cids) order by n.mid, c.ord",
+            )?
+            .query_and_then([], |r| Ok((r.get(0)?, r.get(1)?)))?
+            .collect()
+    }
+
     pub(crate) fn all_searched_cards_in_search_order(&self) -> Result<Vec<Card>> {
         self.db
             .prepare_cached(concat!(
@@ -710,6 +764,9 @@ enum ReviewOrderSubclause {
     RelativeOverduenessFsrs {
         today: u32,
     },
+    RetrievabilityAscending {
+        today: u32,
+    },
 }
 
 impl fmt::Display for ReviewOrderSubclause {
@@ -734,6 +791,13 @@ impl fmt::Display for ReviewOrderSubclause {
                     format!("extract_fsrs_relative_overdueness(data, due, {today}, ivl) desc");
                 &temp_string
             }
+            ReviewOrderSubclause::RetrievabilityAscending { today } => {
+                // cards without a memory state have no retrievability
+                temp_string = format!(
+                    "extract_fsrs_retrievability(data, case when odue !=0 then odue else due end, ivl, {today}) asc nulls last"
+                );
+                &temp_string
+            }
         };
         write!(f, "{}", clause)
     }
@@ -765,6 +829,13 @@ fn review_order_sql(order: ReviewCardOrder, today: u32, fsrs: bool) -> String {
                 ReviewOrderSubclause::RelativeOverdueness { today }
             }]
         }
+        ReviewCardOrder::RetrievabilityAscending => {
+            vec![if fsrs {
+                ReviewOrderSubclause::RetrievabilityAscending { today }
+            } else {
+                ReviewOrderSubclause::RelativeOverdueness { today }
+            }]
+        }
         ReviewCardOrder::Random => vec![],
     };
     subclauses.push(ReviewOrderSubclause::Random);
@@ -307,6 +307,19 @@ impl super::SqliteStorage {
         Ok(())
     }
 
+    /// Removes notes from the search_nids table unless they or any of their
+    /// cards have been modified since the provided time.
+    pub(crate) fn retain_searched_notes_changed_since(&self, since: TimestampSecs) -> Result<()> {
+        self.db.execute(
+            "DELETE FROM search_nids WHERE nid NOT IN (
+                SELECT id FROM notes WHERE mod >= ?1
+                UNION SELECT nid FROM cards WHERE mod >= ?1
+            )",
+            [since],
+        )?;
+        Ok(())
+    }
+
     pub(crate) fn clear_searched_notes_table(&self) -> Result<()> {
         self.db.execute("drop table if exists search_nids", [])?;
         Ok(())
@@ -119,6 +119,20 @@ impl SqliteStorage {
             .collect()
     }
 
+    pub(crate) fn get_revlog_entries_for_card_after_stamp(
+        &self,
+        cid: CardId,
+        after: TimestampSecs,
+    ) -> Result<Vec<RevlogEntry>> {
+        self.db
+            .prepare_cached(concat!(
+                include_str!("get.sql"),
+                " where cid=? and id >= ? order by id"
+            ))?
+            .query_and_then(params![cid, after.0 * 1000], row_to_revlog_entry)?
+            .collect()
+    }
+
     pub(crate) fn get_revlog_entries_for_searched_cards_after_stamp(
         &self,
         after: TimestampSecs,
@@ -142,6 +156,23 @@ impl SqliteStorage {
             .collect()
     }
 
+    /// Calls `func` with the entries of the searched cards in ID order, without
+    /// loading them all into memory.
+    pub(crate) fn for_each_revlog_entry_of_searched_cards(
+        &self,
+        mut func: impl FnMut(RevlogEntry) -> Result<()>,
+    ) -> Result<()> {
+        let mut stmt = self.db.prepare_cached(concat!(
+            include_str!("get.sql"),
+            " where cid in (select cid from search_cids) order by id"
+        ))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            func(row_to_revlog_entry(row)?)?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_revlog_entries_for_searched_cards_in_card_order(
         &self,
     ) -> Result<Vec<RevlogEntry>> {
@@ -72,6 +72,8 @@ fn open_or_create_collection_db(path: &Path) -> Result<Connection> {
     add_fnvhash_function(&db)?;
     add_extract_custom_data_function(&db)?;
     add_extract_fsrs_variable(&db)?;
+    add_extract_interval_limit(&db)?;
+    add_extract_prerequisites(&db)?;
     add_extract_fsrs_retrievability(&db)?;
     add_extract_fsrs_relative_overdueness(&db)?;
 
@@ -265,6 +267,55 @@ fn add_extract_fsrs_variable(db: &Connection) -> rusqlite::Result<()> {
     )
 }
 
+/// eg. extract_interval_limit(card.data, 'max') -> 30 | null
+fn add_extract_interval_limit(db: &Connection) -> rusqlite::Result<()> {
+    db.create_scalar_function(
+        "extract_interval_limit",
+        2,
+        FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            assert_eq!(ctx.len(), 2, "called with unexpected number of arguments");
+
+            let Ok(card_data) = ctx.get_raw(0).as_str() else {
+                return Ok(None);
+            };
+            if card_data.is_empty() {
+                return Ok(None);
+            }
+            let Ok(key) = ctx.get_raw(1).as_str() else {
+                return Ok(None);
+            };
+            let card_data = &CardData::from_str(card_data);
+            Ok(match key {
+                "min" => card_data.minimum_interval,
+                "max" => card_data.maximum_interval,
+                _ => panic!("invalid key: {key}"),
+            })
+        },
+    )
+}
+
+/// eg. extract_prerequisites(card.data) -> json array | null
+fn add_extract_prerequisites(db: &Connection) -> rusqlite::Result<()> {
+    db.create_scalar_function(
+        "extract_prerequisites",
+        1,
+        FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            assert_eq!(ctx.len(), 1, "called with unexpected number of arguments");
+
+            let Ok(card_data) = ctx.get_raw(0).as_str() else {
+                return Ok(None);
+            };
+            if card_data.is_empty() {
+                return Ok(None);
+            }
+            let prerequisites = CardData::from_str(card_data).prerequisites;
+            Ok((!prerequisites.is_empty()).then(|| serde_json::to_string(&prerequisites).unwrap()))
+        },
+    )
+}
+
 /// eg. extract_fsrs_retrievability(card.data, card.due, card.ivl,
 /// timing.days_elapsed) -> float | null
 fn add_extract_fsrs_retrievability(db: &Connection) -> rusqlite::Result<()> {
@@ -488,6 +539,17 @@ impl SqliteStorage {
             })
     }
 
+    /// Write a consistent copy of the DB to the provided path, which must not
+    /// exist or be empty. Unlike reading the DB file, this does not require
+    /// the whole DB to fit into memory.
+    pub(crate) fn copy_to(&self, path: &Path) -> Result<()> {
+        self.db.execute(
+            "vacuum into ?",
+            [path.to_str().or_invalid("non-unicode path")?],
+        )?;
+        Ok(())
+    }
+
     // Standard transaction start/stop
     //////////////////////////////////////
 
@@ -333,6 +333,10 @@ impl From<CardEntry> for Card {
             memory_state: data.memory_state(),
             desired_retention: data.fsrs_desired_retention,
             custom_data: data.custom_data,
+            prerequisites: data.prerequisites,
+            memory_state_hash: data.memory_state_hash,
+            minimum_interval: data.minimum_interval,
+            maximum_interval: data.maximum_interval,
         }
     }
 }
@@ -10,6 +10,7 @@ import {
     DeckConfig_Config_ReviewCardOrder,
     DeckConfig_Config_ReviewMix,
 } from "@tslib/anki/deck_config_pb";
+import { FsrsWeightsGrouping } from "@tslib/anki/scheduler_pb";
 import * as tr from "@tslib/ftl";
 import type { Choice } from "components/EnumSelector.svelte";
 
@@ -101,6 +102,10 @@ export function reviewOrderChoices(fsrs: boolean): Choice<DeckConfig_Config_Revi
             label: tr.deckConfigSortOrderRelativeOverdueness(),
             value: DeckConfig_Config_ReviewCardOrder.RELATIVE_OVERDUENESS,
         },
+        {
+            label: tr.deckConfigSortOrderAscendingRetrievability(),
+            value: DeckConfig_Config_ReviewCardOrder.RETRIEVABILITY_ASCENDING,
+        },
         {
             label: tr.deckConfigSortOrderRandom(),
             value: DeckConfig_Config_ReviewCardOrder.RANDOM,
@@ -138,6 +143,36 @@ export function leechChoices(): Choice<DeckConfig_Config_LeechAction>[] {
     ];
 }
 
+export function leechFlagChoices(): Choice<number>[] {
+    return [
+        { label: tr.deckConfigLeechFlagNone(), value: 0 },
+        { label: tr.actionsFlagRed(), value: 1 },
+        { label: tr.actionsFlagOrange(), value: 2 },
+        { label: tr.actionsFlagGreen(), value: 3 },
+        { label: tr.actionsFlagBlue(), value: 4 },
+        { label: tr.actionsFlagPink(), value: 5 },
+        { label: tr.actionsFlagTurquoise(), value: 6 },
+        { label: tr.actionsFlagPurple(), value: 7 },
+    ];
+}
+
+export function weightsGroupingChoices(): Choice<FsrsWeightsGrouping>[] {
+    return [
+        {
+            label: tr.deckConfigWeightsGroupingPreset(),
+            value: FsrsWeightsGrouping.PRESET,
+        },
+        {
+            label: tr.deckConfigWeightsGroupingNotetype(),
+            value: FsrsWeightsGrouping.NOTETYPE,
+        },
+        {
+            label: tr.deckConfigWeightsGroupingTemplate(),
+            value: FsrsWeightsGrouping.TEMPLATE,
+        },
+    ];
+}
+
 export function newInsertOrderChoices(): Choice<DeckConfig_Config_NewCardInsertOrder>[] {
     return [
         {
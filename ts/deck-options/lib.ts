@@ -37,6 +37,7 @@ export class DeckOptionsState {
     readonly currentAuxData: Writable<Record<string, unknown>>;
     readonly configList: Readable<ConfigListEntry[]>;
     readonly cardStateCustomizer: Writable<string>;
+    readonly cardStateScript: Writable<string>;
     readonly currentDeck: DeckConfigsForUpdate_CurrentDeck;
     readonly deckLimits: Writable<DeckConfigsForUpdate_CurrentDeck_Limits>;
     readonly defaults: DeckConfig_Config;
@@ -74,6 +75,7 @@ export class DeckOptionsState {
         );
         this.sortConfigs();
         this.cardStateCustomizer = writable(data.cardStateCustomizer);
+        this.cardStateScript = writable(data.cardStateScript);
         this.deckLimits = writable(data.currentDeck?.limits ?? createLimits());
         this.newCardsIgnoreReviewLimit = writable(data.newCardsIgnoreReviewLimit);
         this.applyAllParentLimits = writable(data.applyAllParentLimits);
@@ -201,6 +203,7 @@ export class DeckOptionsState {
             configs,
             mode,
             cardStateCustomizer: get(this.cardStateCustomizer),
+            cardStateScript: get(this.cardStateScript),
             limits: get(this.deckLimits),
             newCardsIgnoreReviewLimit: get(this.newCardsIgnoreReviewLimit),
             applyAllParentLimits: get(this.applyAllParentLimits),
@@ -41,6 +41,10 @@ export function delimiterChoices(): Choice<CsvMetadata_Delimiter>[] {
 export function dupeResolutionChoices(): Choice<CsvMetadata_DupeResolution>[] {
     return [
         { label: tr.importingUpdate(), value: CsvMetadata_DupeResolution.UPDATE },
+        {
+            label: tr.importingUpdateIfNewerThanExisting(),
+            value: CsvMetadata_DupeResolution.UPDATE_IF_NEWER,
+        },
         { label: tr.importingPreserve(), value: CsvMetadata_DupeResolution.PRESERVE },
         { label: tr.importingDuplicate(), value: CsvMetadata_DupeResolution.DUPLICATE },
     ];
@@ -165,6 +165,7 @@ function getColumnOptions(
 ): ColumnOption[] {
     const notetypeColumn = getNotetypeColumn(metadata);
     const deckColumn = getDeckColumn(metadata);
+    const schedulingColumns = getSchedulingColumns(metadata);
     return [{ label: tr.changeNotetypeNothing(), value: 0, disabled: false }].concat(
         metadata.columnLabels.map((label, index) => {
             index += 1;
@@ -174,6 +175,10 @@ function getColumnOptions(
                 return columnOption(tr.decksDeck(), true, index);
             } else if (index === metadata.guidColumn) {
                 return columnOption("GUID", true, index);
+            } else if (index === metadata.mtimeColumn) {
+                return columnOption("mtime", true, index);
+            } else if (schedulingColumns.has(index)) {
+                return columnOption(schedulingColumns.get(index)!, true, index);
             } else if (label === "") {
                 return columnOption(metadata.preview[0].vals[index - 1], false, index, true);
             } else {
@@ -197,6 +202,17 @@ function columnOption(
     };
 }
 
+/** Maps column indices to the names of the scheduling values they contain. */
+function getSchedulingColumns(meta: CsvMetadata): Map<number, string> {
+    const columns = new Map<number, string>();
+    for (const [name, index] of Object.entries(meta.schedulingColumns ?? {})) {
+        if (typeof index === "number" && index > 0) {
+            columns.set(index, name);
+        }
+    }
+    return columns;
+}
+
 function getDeckColumn(meta: CsvMetadata): number | null {
     return meta.deck.case === "deckColumn" ? meta.deck.value : null;
 }
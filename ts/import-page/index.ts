@@ -3,7 +3,7 @@
 
 import "./import-page-base.scss";
 
-import { importJsonFile, importJsonString } from "@tslib/backend";
+import { importJsonFile, importJsonString, importMnemosyne, importSupermemo } from "@tslib/backend";
 import { ModuleName, setupI18n } from "@tslib/i18n";
 import { checkNightMode } from "@tslib/nightmode";
 
@@ -41,6 +41,16 @@ export async function setupImportPage(
                             return importJsonFile({ val: params.path }, postOptions);
                         case "json_string":
                             return importJsonString({ val: params.json }, postOptions);
+                        case "mnemosyne":
+                            return importMnemosyne(
+                                { path: params.path, deckId: BigInt(params.deckId) },
+                                postOptions,
+                            );
+                        case "supermemo":
+                            return importSupermemo(
+                                { path: params.path, deckId: BigInt(params.deckId) },
+                                postOptions,
+                            );
                     }
                 },
             },
@@ -88,6 +88,10 @@ export function getSummaries(log: ImportResponse_Log): SummarizedLogQueues[] {
                     notes: log.emptyFirstField,
                     reason: tr.importingNoteSkippedDueToEmptyFirstField(),
                 },
+                {
+                    notes: log.outdated,
+                    reason: tr.importingNoteSkippedAsCollectionHadNewer(),
+                },
             ],
             action: tr.importingSkipped(),
             summaryTemplate: tr.importingConflictingNotesSkipped2,
@@ -33,4 +33,10 @@ type JsonParams = {
     json: string;
 };
 
-export type LogParams = PathParams | JsonParams;
+type ForeignFileParams = {
+    type: "mnemosyne" | "supermemo";
+    path: string;
+    deckId: number;
+};
+
+export type LogParams = PathParams | JsonParams | ForeignFileParams;